@@ -0,0 +1,148 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    basta::{
+        binary_fasta_section::BinaryFastaSection,
+        metadata::{Metadata, MetadataEntry},
+    },
+    errors::BinaryFastaError,
+};
+
+// Header of the tables written by the metadata subcommand, skipped when reading one.
+pub const TABLE_HEADER: &str = "ID\tKEY\tVALUE";
+
+// Metadata to add to sections. Entries replace existing ones with the same key, and
+// the descriptor and sequence are passed on as they are.
+#[derive(Debug, Default)]
+pub struct Annotator {
+    // Remove the existing metadata first.
+    pub clear: bool,
+    // Added to every section, or only to the ones named in `names` if any.
+    pub entries: Vec<MetadataEntry>,
+    pub names: HashSet<String>,
+    // Entries by section ID, added after `entries`.
+    pub table: HashMap<String, Vec<MetadataEntry>>,
+}
+
+impl Annotator {
+    pub fn read_table(&mut self, path: &Path) -> Result<(), BinaryFastaError> {
+        let text = fs::read_to_string(path)?;
+        let rows = parse_table(&text).map_err(|line| BinaryFastaError::InvalidMetadataTable {
+            path: path.to_path_buf(),
+            line,
+        })?;
+        for (id, key, value) in rows {
+            let entry = MetadataEntry::parse(key, value)?;
+            self.table.entry(id.to_string()).or_default().push(entry);
+        }
+        Ok(())
+    }
+
+    pub fn annotate(&self, section: &mut BinaryFastaSection) -> Result<(), BinaryFastaError> {
+        if self.clear {
+            section.metadata = Metadata::new();
+        }
+
+        let selected = self.names.is_empty() || self.names.contains(section.id());
+        let entries = selected.then_some(&self.entries).into_iter().flatten();
+        let rows = self.table.get(section.id()).into_iter().flatten();
+        for entry in entries.chain(rows) {
+            section.metadata.insert(entry.clone())?;
+        }
+        Ok(())
+    }
+}
+
+// Three columns separated by tabs: the sequence ID, the key and the value, which may
+// contain spaces. Empty lines, lines starting with '#' and the header are skipped.
+// Returns the 1-based number of the first line that is not a row.
+fn parse_table(text: &str) -> Result<Vec<(&str, &str, &str)>, usize> {
+    let mut rows = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') || line == TABLE_HEADER {
+            continue;
+        }
+        let columns: Vec<&str> = line.split('\t').collect();
+        let [id, key, value] = columns[..] else {
+            return Err(i + 1);
+        };
+        if id.is_empty() || key.is_empty() {
+            return Err(i + 1);
+        }
+        rows.push((id, key, value));
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basta::metadata::Topology;
+
+    fn keys(section: &BinaryFastaSection) -> Vec<String> {
+        section
+            .metadata
+            .entries()
+            .iter()
+            .map(|entry| entry.key())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let rows = parse_table(
+            "ID\tKEY\tVALUE\n# comment\nchrM\ttopology\tcircular\r\n\nchrM\tnote\ta b\n",
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![("chrM", "topology", "circular"), ("chrM", "note", "a b")]
+        );
+
+        assert_eq!(parse_table("chrM topology circular\n"), Err(1));
+        assert_eq!(parse_table("chrM\ttopology\tcircular\n\tnote\tx\n"), Err(2));
+    }
+
+    #[test]
+    fn test_annotate() {
+        let mut annotator = Annotator {
+            entries: vec![MetadataEntry::parse("assembly", "GRCh38").unwrap()],
+            names: HashSet::from([String::from("chrM")]),
+            ..Annotator::default()
+        };
+        annotator.table.insert(
+            String::from("chrM"),
+            vec![MetadataEntry::parse("topology", "circular").unwrap()],
+        );
+
+        let mut chr_m = BinaryFastaSection::from_text("chrM mitochondrion", "ACGT");
+        annotator.annotate(&mut chr_m).unwrap();
+        assert_eq!(keys(&chr_m), vec!["assembly", "topology"]);
+        assert_eq!(
+            chr_m.metadata.entries()[1],
+            MetadataEntry::Topology(Topology::Circular)
+        );
+
+        let mut chr1 = BinaryFastaSection::from_text("chr1", "ACGT");
+        annotator.annotate(&mut chr1).unwrap();
+        assert!(chr1.metadata.is_empty());
+
+        // Without names every section is annotated, after clearing.
+        chr_m
+            .metadata
+            .insert(MetadataEntry::TaxonomyId(9606))
+            .unwrap();
+        let annotator = Annotator {
+            clear: true,
+            entries: vec![MetadataEntry::parse("source", "RefSeq").unwrap()],
+            ..Annotator::default()
+        };
+        annotator.annotate(&mut chr_m).unwrap();
+        assert_eq!(keys(&chr_m), vec!["source"]);
+    }
+}
@@ -0,0 +1,187 @@
+// IUPAC codes for ambiguous nucleotides, which are also amino acid letters.
+pub const AMBIGUOUS_NUCLEOTIDES: &[u8; 11] = b"NRYKMSWBDHV";
+
+// Bytes taken by a run in a section: start and length (4 bytes each, big-endian),
+// then the base.
+const RUN_BYTES: usize = 9;
+
+// A run of the same ambiguous nucleotide, such as N, which has no 2-bit code. The
+// packed sequence holds A at those positions.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct AmbiguousRun {
+    pub start: u32,
+    pub length: u32,
+    // Upper case IUPAC code, one of AMBIGUOUS_NUCLEOTIDES.
+    pub base: u8,
+}
+
+impl AmbiguousRun {
+    pub fn start(&self) -> usize {
+        self.start as usize
+    }
+
+    // 0-based, exclusive.
+    pub fn end(&self) -> usize {
+        self.start as usize + self.length as usize
+    }
+}
+
+pub fn is_ambiguous(base: u8) -> bool {
+    AMBIGUOUS_NUCLEOTIDES.contains(&base.to_ascii_uppercase())
+}
+
+// The code of the complementary bases: R (A or G) becomes Y (C or T), and so on.
+// N, S and W are their own complement.
+pub fn complement(base: u8) -> u8 {
+    match base {
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        other => other,
+    }
+}
+
+// Adds the ambiguous base at `position`, which comes after every run so far.
+pub fn push_base(runs: &mut Vec<AmbiguousRun>, position: usize, base: u8) {
+    let position = u32::try_from(position).expect("Sequence is too long.");
+    match runs.last_mut() {
+        Some(run) if run.base == base && run.start + run.length == position => run.length += 1,
+        _ => runs.push(AmbiguousRun {
+            start: position,
+            length: 1,
+            base,
+        }),
+    }
+}
+
+// The runs of the residues `start..end`, counted from `start`.
+pub fn clip(runs: &[AmbiguousRun], start: usize, end: usize) -> Vec<AmbiguousRun> {
+    runs.iter()
+        .filter(|run| run.end().min(end) > run.start().max(start))
+        .map(|run| {
+            let run_start = run.start().max(start);
+            AmbiguousRun {
+                start: (run_start - start) as u32,
+                length: (run.end().min(end) - run_start) as u32,
+                base: run.base,
+            }
+        })
+        .collect()
+}
+
+// The runs of the reverse complement of a sequence of `length` bases.
+pub fn reverse_complement(runs: &[AmbiguousRun], length: usize) -> Vec<AmbiguousRun> {
+    runs.iter()
+        .rev()
+        .map(|run| AmbiguousRun {
+            start: (length - run.end()) as u32,
+            length: run.length,
+            base: complement(run.base),
+        })
+        .collect()
+}
+
+pub fn convert_to_bytes(runs: &[AmbiguousRun]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(runs.len() * RUN_BYTES);
+    for run in runs {
+        bytes.extend_from_slice(&run.start.to_be_bytes());
+        bytes.extend_from_slice(&run.length.to_be_bytes());
+        bytes.push(run.base);
+    }
+    bytes
+}
+
+// Reads the runs, and checks that they are in order, do not
+// overlap and fit in a sequence of `length` bases. Returns None if they do not.
+pub fn from_bytes(bytes: &[u8], length: usize) -> Option<Vec<AmbiguousRun>> {
+    let mut runs: Vec<AmbiguousRun> = Vec::with_capacity(bytes.len() / RUN_BYTES);
+    let mut end = 0;
+    for chunk in bytes.chunks(RUN_BYTES) {
+        let [s0, s1, s2, s3, l0, l1, l2, l3, base] = chunk[..] else {
+            return None;
+        };
+        let run = AmbiguousRun {
+            start: u32::from_be_bytes([s0, s1, s2, s3]),
+            length: u32::from_be_bytes([l0, l1, l2, l3]),
+            base,
+        };
+        if run.length == 0
+            || run.start() < end
+            || run.end() > length
+            || !AMBIGUOUS_NUCLEOTIDES.contains(&base)
+        {
+            return None;
+        }
+        end = run.end();
+        runs.push(run);
+    }
+    Some(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(runs: &[(u32, u32, u8)]) -> Vec<AmbiguousRun> {
+        runs.iter()
+            .map(|&(start, length, base)| AmbiguousRun {
+                start,
+                length,
+                base,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_push_base() {
+        let mut pushed = Vec::new();
+        for (position, base) in [(2, b'N'), (3, b'N'), (4, b'R'), (7, b'R')] {
+            push_base(&mut pushed, position, base);
+        }
+        assert_eq!(pushed, runs(&[(2, 2, b'N'), (4, 1, b'R'), (7, 1, b'R')]));
+    }
+
+    #[test]
+    fn test_clip_and_reverse_complement() {
+        // ACNNNGTRA
+        let sequence = runs(&[(2, 3, b'N'), (7, 1, b'R')]);
+        assert_eq!(clip(&sequence, 3, 8), runs(&[(0, 2, b'N'), (4, 1, b'R')]));
+        assert!(clip(&sequence, 5, 7).is_empty());
+
+        // TYACNNNGT
+        assert_eq!(
+            reverse_complement(&sequence, 9),
+            runs(&[(1, 1, b'Y'), (4, 3, b'N')])
+        );
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let sequence = runs(&[(2, 3, b'N'), (7, 1, b'R')]);
+        let bytes = convert_to_bytes(&sequence);
+        assert_eq!(bytes.len(), 2 * RUN_BYTES);
+        assert_eq!(from_bytes(&bytes, 8), Some(sequence));
+
+        // Past the end of the sequence.
+        assert_eq!(from_bytes(&bytes, 7), None);
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_malformed_runs() {
+        for malformed in [
+            runs(&[(0, 0, b'N')]),
+            runs(&[(0, 2, b'N'), (1, 2, b'R')]),
+            runs(&[(4, 1, b'N'), (0, 1, b'N')]),
+            runs(&[(0, 1, b'A')]),
+        ] {
+            let bytes = convert_to_bytes(&malformed);
+            assert_eq!(from_bytes(&bytes, 10), None);
+        }
+        assert_eq!(from_bytes(&[0, 0, 0, 0, 0], 10), None);
+    }
+}
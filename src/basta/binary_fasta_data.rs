@@ -1,10 +1,13 @@
-use std::fs::File;
-use std::io::{self, prelude::*, BufWriter};
+use std::fs::{self, File};
+use std::io::{self, prelude::*, BufReader, SeekFrom};
 use std::path::Path;
 
-use crate::basta::binary_fasta_section::BinaryFastaSection;
+use crate::basta::binary_fasta_header::{BinaryFastaHeader, HEADER_LENGTH};
+use crate::basta::binary_fasta_section::{BinaryFastaSection, SectionHeader, MOLECULE_VERSION};
 use crate::errors::BinaryFastaError;
 use crate::fasta::fasta_section::FastaSection;
+use crate::output_file::OutputFile;
+use crate::twobit::two_bit_section::TwoBitSection;
 
 pub fn from_fasta<I>(
     fasta_data: I,
@@ -12,23 +15,46 @@ pub fn from_fasta<I>(
 where
     I: Iterator<Item = Result<FastaSection, BinaryFastaError>>,
 {
-    fasta_data.map(|res| match res {
-        Ok(section) => Ok(BinaryFastaSection::from_fasta(section)),
-        Err(_) => Err(BinaryFastaError::UnexpectedEof),
-    })
+    fasta_data.map(|res| res.and_then(BinaryFastaSection::from_fasta))
 }
 
-pub fn write<I>(iter: I, file_path: &Path) -> Result<(), BinaryFastaError>
+pub fn from_two_bit<I>(
+    two_bit_data: I,
+) -> impl Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>
+where
+    I: Iterator<Item = Result<TwoBitSection, BinaryFastaError>>,
+{
+    two_bit_data.map(|res| res.and_then(|section| section.to_basta()))
+}
+
+pub fn write<I>(iter: I, file_path: &Path, overwrite: bool) -> Result<(), BinaryFastaError>
+where
+    I: Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>,
+{
+    let mut writer = OutputFile::create(file_path, overwrite)?;
+    write_to(iter, &mut writer)?;
+    writer.commit()
+}
+
+pub fn write_to<I, W>(iter: I, writer: &mut W) -> Result<(), BinaryFastaError>
 where
     I: Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>,
+    W: Write + Seek,
 {
-    let mut writer = BufWriter::new(File::create(file_path)?);
+    let mut header = BinaryFastaHeader::new(MOLECULE_VERSION);
+    writer.write_all(&header.convert_to_bytes())?;
 
     for section in iter {
-        writer.write_all(&section?.convert_to_bytes())?;
+        let section = section?;
+        header.version = header.version.max(section.format_version());
+        writer.write_all(&section.convert_to_bytes()?)?;
     }
 
-    writer.flush()?;
+    // Sections are streamed, so the version they need is only known at the end.
+    if header.version != MOLECULE_VERSION {
+        writer.seek(SeekFrom::Start(0))?;
+        writer.write_all(&header.convert_to_bytes())?;
+    }
     Ok(())
 }
 
@@ -36,17 +62,35 @@ pub fn read(
     file_path: &Path,
 ) -> Result<impl Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>, BinaryFastaError> {
     let file = File::open(file_path)?;
-    let reader = io::BufReader::new(file);
+    let mut reader = io::BufReader::new(file);
+
+    // Files written before the header was introduced start directly with a section,
+    // in which case the bytes read here belong to it.
+    let mut start = Vec::with_capacity(HEADER_LENGTH);
+    (&mut reader)
+        .take(HEADER_LENGTH as u64)
+        .read_to_end(&mut start)?;
+    let version = match BinaryFastaHeader::from_bytes(&start)? {
+        Some(header) => {
+            start.clear();
+            header.version
+        }
+        None => BinaryFastaHeader::legacy().version,
+    };
 
-    let mut bytes_iter = reader
-        .bytes()
-        .map(|res| res.expect("I/O error while reading bytes"))
+    let mut bytes_iter = start
+        .into_iter()
+        .chain(
+            reader
+                .bytes()
+                .map(|res| res.expect("I/O error while reading bytes")),
+        )
         .peekable();
 
     Ok(std::iter::from_fn(move || {
         if bytes_iter.peek().is_some() {
             // Consume exactly one section from the byte stream
-            let section = BinaryFastaSection::from_bytes(&mut bytes_iter);
+            let section = BinaryFastaSection::from_bytes(&mut bytes_iter, version);
             Some(section)
         } else {
             None
@@ -54,9 +98,149 @@ pub fn read(
     }))
 }
 
+// Opens a BASTA file for reading with `BinaryFastaSection::read_section_header`,
+// positioned at its first section.
+pub fn open(file_path: &Path) -> Result<(BufReader<File>, BinaryFastaHeader), BinaryFastaError> {
+    let mut reader = BufReader::new(File::open(file_path)?);
+
+    let mut start = Vec::with_capacity(HEADER_LENGTH);
+    (&mut reader)
+        .take(HEADER_LENGTH as u64)
+        .read_to_end(&mut start)?;
+    match BinaryFastaHeader::from_bytes(&start)? {
+        Some(header) => Ok((reader, header)),
+        None => {
+            // Legacy files have no header, their first section starts at the beginning.
+            reader.seek(SeekFrom::Start(0))?;
+            Ok((reader, BinaryFastaHeader::legacy()))
+        }
+    }
+}
+
+// Where a section is in a BASTA file, as listed by `list_sections`.
+#[derive(Debug, PartialEq)]
+pub struct SectionEntry {
+    pub header: SectionHeader,
+    // Position of the first byte of the section (before its descriptor) in the file.
+    pub offset: u64,
+}
+
+// Lists the sections of a BASTA file from their headers, skipping every sequence.
+pub fn list_sections(
+    file_path: &Path,
+) -> Result<
+    (
+        BinaryFastaHeader,
+        impl Iterator<Item = Result<SectionEntry, BinaryFastaError>>,
+    ),
+    BinaryFastaError,
+> {
+    let (mut reader, header) = open(file_path)?;
+    let mut done = false;
+
+    let mut next_entry = move || -> Result<Option<SectionEntry>, BinaryFastaError> {
+        let offset = reader.stream_position()?;
+        let Some(section_header) =
+            BinaryFastaSection::read_section_header(&mut reader, header.version)?
+        else {
+            return Ok(None);
+        };
+        BinaryFastaSection::skip_section(&mut reader, &section_header)?;
+        Ok(Some(SectionEntry {
+            header: section_header,
+            offset,
+        }))
+    };
+
+    let entries = std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let res = next_entry().transpose();
+        done = !matches!(res, Some(Ok(_)));
+        res
+    });
+    Ok((header, entries))
+}
+
+// Reads only the sections whose descriptor is selected. The packed sequence of the
+// other sections is skipped by seeking past it, without reading or decoding it.
+pub fn read_selected<F>(
+    file_path: &Path,
+    mut select: F,
+) -> Result<impl Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>, BinaryFastaError>
+where
+    F: FnMut(&str) -> bool,
+{
+    // Seeking does not fail past the end of the file, so a skipped section that runs
+    // past it is only noticed by comparing with the file length.
+    let file_length = fs::metadata(file_path)?.len();
+    let (mut reader, header) = open(file_path)?;
+    let mut done = false;
+
+    let mut next_selected = move || -> Result<Option<BinaryFastaSection>, BinaryFastaError> {
+        while let Some(section_header) =
+            BinaryFastaSection::read_section_header(&mut reader, header.version)?
+        {
+            if section_header.data_offset + section_header.packed_length() as u64 > file_length {
+                return Err(BinaryFastaError::UnexpectedEof);
+            }
+            if select(&section_header.descriptor) {
+                return BinaryFastaSection::read_section_data(&mut reader, section_header)
+                    .map(Some);
+            }
+            BinaryFastaSection::skip_section(&mut reader, &section_header)?;
+        }
+        Ok(None)
+    };
+
+    Ok(std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let res = next_selected().transpose();
+        done = !matches!(res, Some(Ok(_)));
+        res
+    }))
+}
+
+// Reads the sections starting at `offsets` (as listed by `list_sections`), in the
+// order given, seeking to each of them.
+pub fn read_at<I>(
+    file_path: &Path,
+    offsets: I,
+) -> Result<impl Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>, BinaryFastaError>
+where
+    I: IntoIterator<Item = u64>,
+{
+    let (mut reader, header) = open(file_path)?;
+    let mut offsets = offsets.into_iter();
+    let mut done = false;
+
+    let mut read_next = move |offset: u64| -> Result<BinaryFastaSection, BinaryFastaError> {
+        reader.seek(SeekFrom::Start(offset))?;
+        let section_header = BinaryFastaSection::read_section_header(&mut reader, header.version)?
+            .ok_or(BinaryFastaError::UnexpectedEof)?;
+        BinaryFastaSection::read_section_data(&mut reader, section_header)
+    };
+
+    Ok(std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let res = read_next(offsets.next()?);
+        done = res.is_err();
+        Some(res)
+    }))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::basta::binary_fasta_data;
+    use crate::basta::{
+        binary_fasta_data,
+        binary_fasta_section::{Molecule, BLOCKS_VERSION},
+        metadata::{Metadata, MetadataEntry},
+    };
 
     use super::*;
 
@@ -86,11 +270,17 @@ mod tests {
                 descriptor: String::from(descr1),
                 sequence: vec![0b0000_0000, 0b0101_0101, 0b1010_1010, 0b1111_1111],
                 sequence_length: 16i32,
+                molecule: Molecule::Nucleotide,
+                metadata: Metadata::new(),
+                ambiguous_runs: Vec::new(),
             },
             BinaryFastaSection {
                 descriptor: String::from(descr2),
                 sequence: vec![0b0001_1011, 0b0110_0000],
                 sequence_length: 6i32,
+                molecule: Molecule::Nucleotide,
+                metadata: Metadata::new(),
+                ambiguous_runs: Vec::new(),
             },
         ];
         assert_eq!(expected, basta_vec);
@@ -122,13 +312,177 @@ mod tests {
                 descriptor: String::from(descr1),
                 sequence: vec![0b0000_0000, 0b0101_0101, 0b1010_1010, 0b1111_1111],
                 sequence_length: -16i32, // Length is negative because sign bit signals DNA (+) or RNA (-)
+                molecule: Molecule::Nucleotide,
+                metadata: Metadata::new(),
+                ambiguous_runs: Vec::new(),
             },
             BinaryFastaSection {
                 descriptor: String::from(descr2),
                 sequence: vec![0b0001_1011, 0b0110_0000],
                 sequence_length: -6i32, // Length is negative because sign bit signals DNA (+) or RNA (-)
+                molecule: Molecule::Nucleotide,
+                metadata: Metadata::new(),
+                ambiguous_runs: Vec::new(),
             },
         ];
         assert_eq!(expected, basta_vec);
     }
+
+    fn example_section() -> BinaryFastaSection {
+        BinaryFastaSection {
+            descriptor: String::from("test 1"),
+            sequence: vec![0b0001_1011, 0b0110_0000],
+            sequence_length: 6i32,
+            molecule: Molecule::Nucleotide,
+            metadata: Metadata::new(),
+            ambiguous_runs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_write_and_read_with_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.basta");
+
+        write(vec![Ok(example_section())].into_iter(), &path, false).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(b"BSTA"));
+
+        let sections: Vec<_> = read(&path).unwrap().map(Result::unwrap).collect();
+        assert_eq!(sections, vec![example_section()]);
+    }
+
+    #[test]
+    fn test_lowest_version_is_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain_path = dir.path().join("plain.basta");
+        let annotated_path = dir.path().join("annotated.basta");
+
+        // Files without metadata can be read by version 2 readers, as they were.
+        write(vec![Ok(example_section())].into_iter(), &plain_path, false).unwrap();
+        let bytes = std::fs::read(&plain_path).unwrap();
+        assert_eq!(bytes[..HEADER_LENGTH], *b"BSTA\x02\x00");
+        let mut stream = bytes[HEADER_LENGTH..].iter().copied();
+        assert_eq!(
+            BinaryFastaSection::from_bytes(&mut stream, MOLECULE_VERSION).unwrap(),
+            example_section()
+        );
+
+        let annotated = || {
+            let mut section = example_section();
+            section
+                .metadata
+                .insert(MetadataEntry::TaxonomyId(9606))
+                .unwrap();
+            section
+        };
+        let sections = vec![Ok(example_section()), Ok(annotated())];
+        write(sections.into_iter(), &annotated_path, false).unwrap();
+
+        let (header, _) = list_sections(&annotated_path).unwrap();
+        assert_eq!(header.version, BLOCKS_VERSION);
+        let sections: Vec<_> = read(&annotated_path).unwrap().map(Result::unwrap).collect();
+        assert_eq!(sections, vec![example_section(), annotated()]);
+    }
+
+    #[test]
+    fn test_read_legacy_file_without_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.basta");
+
+        // Sections had no molecule byte before version 2.
+        let mut bytes = vec![6u8];
+        bytes.extend(6i32.to_be_bytes());
+        bytes.extend(b"test 1");
+        bytes.extend([0b0001_1011, 0b0110_0000]);
+        std::fs::write(&path, bytes).unwrap();
+
+        let sections: Vec<_> = read(&path).unwrap().map(Result::unwrap).collect();
+        assert_eq!(sections, vec![example_section()]);
+    }
+
+    #[test]
+    fn test_read_selected_skips_other_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.basta");
+
+        let mut other = example_section();
+        other.descriptor = String::from("other");
+        let sections = vec![Ok(other), Ok(example_section())];
+        write(sections.into_iter(), &path, false).unwrap();
+
+        let selected: Vec<_> = read_selected(&path, |descriptor| descriptor.starts_with("test"))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(selected, vec![example_section()]);
+    }
+
+    #[test]
+    fn test_read_selected_reports_truncated_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("truncated.basta");
+
+        write(vec![Ok(example_section())].into_iter(), &path, false).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() - 1);
+        std::fs::write(&path, bytes).unwrap();
+
+        let mut selected = read_selected(&path, |_| true).unwrap();
+        assert!(matches!(
+            selected.next(),
+            Some(Err(BinaryFastaError::UnexpectedEof))
+        ));
+        assert!(selected.next().is_none());
+
+        // Also when the truncated section is skipped.
+        let mut skipped = read_selected(&path, |_| false).unwrap();
+        assert!(matches!(
+            skipped.next(),
+            Some(Err(BinaryFastaError::UnexpectedEof))
+        ));
+    }
+
+    #[test]
+    fn test_list_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.basta");
+
+        let mut other = example_section();
+        other.descriptor = String::from("other");
+        let sections = vec![Ok(example_section()), Ok(other)];
+        write(sections.into_iter(), &path, false).unwrap();
+
+        let (header, entries) = list_sections(&path).unwrap();
+        let entries: Vec<_> = entries.map(Result::unwrap).collect();
+
+        assert_eq!(header, BinaryFastaHeader::new(MOLECULE_VERSION));
+        // Header, then 6 bytes of fields, a 6 byte descriptor and 2 bytes of sequence.
+        let offsets: Vec<u64> = entries.iter().map(|entry| entry.offset).collect();
+        assert_eq!(offsets, vec![6, 20]);
+        assert_eq!(entries[1].header.descriptor, "other");
+        assert_eq!(entries[1].header.data_offset, 31);
+    }
+
+    #[test]
+    fn test_read_at() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.basta");
+
+        let mut other = example_section();
+        other.descriptor = String::from("other");
+        let sections = vec![Ok(example_section()), Ok(other)];
+        write(sections.into_iter(), &path, false).unwrap();
+
+        let descriptors: Vec<_> = read_at(&path, vec![20, 6, 20])
+            .unwrap()
+            .map(|res| res.unwrap().descriptor)
+            .collect();
+        assert_eq!(descriptors, vec!["other", "test 1", "other"]);
+
+        let mut past_end = read_at(&path, vec![100, 6]).unwrap();
+        assert!(past_end.next().unwrap().is_err());
+        assert!(past_end.next().is_none());
+    }
 }
@@ -0,0 +1,100 @@
+use crate::errors::BinaryFastaError;
+
+// Every BASTA file starts with these bytes, so it can be told apart from FASTA
+// (and other formats) by content rather than by extension.
+pub const MAGIC: &[u8; 4] = b"BSTA";
+
+// Latest version read and written by this program. Version 0 is used for files written
+// before the header existed, which start directly with the first section. Version 2
+// added a molecule type byte to every section, for protein sequences, and version 3 a
+// list of typed blocks, for metadata and runs of ambiguous bases such as N. Files are
+// written with the lowest version their sections need (see
+// `BinaryFastaSection::format_version`), so that older readers can read any file that
+// does not use the newer features.
+pub const FORMAT_VERSION: u8 = 3;
+
+// Magic (4 bytes) + version (1 byte) + flags (1 byte)
+pub const HEADER_LENGTH: usize = 6;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BinaryFastaHeader {
+    pub version: u8,
+    // Reserved for optional features, currently always 0.
+    pub flags: u8,
+}
+
+impl BinaryFastaHeader {
+    pub fn new(version: u8) -> Self {
+        BinaryFastaHeader { version, flags: 0 }
+    }
+
+    pub fn legacy() -> Self {
+        BinaryFastaHeader {
+            version: 0,
+            flags: 0,
+        }
+    }
+
+    pub fn is_basta(bytes: &[u8]) -> bool {
+        bytes.starts_with(MAGIC)
+    }
+
+    // Returns None if the bytes do not start with the BASTA magic, which means the
+    // file is either not a BASTA file, or a legacy file without a header.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Option<Self>, BinaryFastaError> {
+        if !Self::is_basta(bytes) {
+            return Ok(None);
+        }
+        if bytes.len() < HEADER_LENGTH {
+            return Err(BinaryFastaError::UnexpectedEof);
+        }
+
+        let version = bytes[4];
+        if version > FORMAT_VERSION {
+            return Err(BinaryFastaError::UnsupportedVersion { version });
+        }
+
+        Ok(Some(BinaryFastaHeader {
+            version,
+            flags: bytes[5],
+        }))
+    }
+
+    pub fn convert_to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(self.version);
+        bytes.push(self.flags);
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_round_trip() {
+        let header = BinaryFastaHeader::new(FORMAT_VERSION);
+        let bytes = header.convert_to_bytes();
+
+        assert_eq!(bytes, vec![b'B', b'S', b'T', b'A', FORMAT_VERSION, 0]);
+        assert_eq!(BinaryFastaHeader::from_bytes(&bytes).unwrap(), Some(header));
+    }
+
+    #[test]
+    fn test_legacy_file_has_no_header() {
+        // A legacy file starts with the descriptor length of the first section.
+        let bytes = [6u8, 0, 0, 0, 16, b't'];
+        assert_eq!(BinaryFastaHeader::from_bytes(&bytes).unwrap(), None);
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let bytes = [b'B', b'S', b'T', b'A', FORMAT_VERSION + 1, 0];
+        assert!(matches!(
+            BinaryFastaHeader::from_bytes(&bytes),
+            Err(BinaryFastaError::UnsupportedVersion { .. })
+        ));
+    }
+}
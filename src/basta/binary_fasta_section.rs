@@ -1,40 +1,362 @@
+use std::io::{self, Read, Seek};
+
 use itertools::Itertools;
 
-use crate::{errors::BinaryFastaError, fasta::fasta_section::FastaSection};
+use crate::{
+    basta::{
+        ambiguous::{self, AmbiguousRun},
+        metadata::Metadata,
+    },
+    errors::BinaryFastaError,
+    fasta::fasta_section::FastaSection,
+    translate::Translation,
+};
+
+// Format version that added the molecule byte to the start of every section.
+// Sections in older files are always nucleotide sequences.
+pub const MOLECULE_VERSION: u8 = 2;
+
+// Format version that added a list of typed blocks after the descriptor, for metadata
+// and ambiguous bases. The high bit of the molecule byte is set when the section has
+// blocks: the length of the list (4 bytes, big-endian), then every block as its type
+// (1 byte), value length (4 bytes, big-endian) and value. Readers skip the types they
+// do not know, so that new blocks can be added without breaking them. Sections without
+// blocks are the same as in version 2.
+pub const BLOCKS_VERSION: u8 = 3;
+
+const BLOCKS_FLAG: u8 = 0b1000_0000;
+
+// The entries of `Metadata`.
+const METADATA_BLOCK: u8 = 1;
+// The runs of ambiguous bases, one after the other (see `AmbiguousRun`).
+const AMBIGUOUS_BLOCK: u8 = 2;
+
+// The 20 standard amino acids, then X (any), B (D or N), Z (E or Q),
+// U (selenocysteine), O (pyrrolysine) and * (stop). The 5-bit code of a
+// residue is its index.
+pub const AMINO_ACIDS: &[u8; 26] = b"ACDEFGHIKLMNPQRSTVWYXBZUO*";
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Molecule {
+    // DNA or RNA, 2 bits per nucleotide.
+    Nucleotide,
+    // 5 bits per amino acid.
+    Protein,
+}
+
+impl Molecule {
+    fn from_byte(value: u8) -> Result<Self, BinaryFastaError> {
+        match value {
+            0 => Ok(Molecule::Nucleotide),
+            1 => Ok(Molecule::Protein),
+            _ => Err(BinaryFastaError::UnknownMolecule { value }),
+        }
+    }
+
+    // The molecule, and the flags of the section, out of the ones that exist in `version`.
+    fn from_section_byte(value: u8, version: u8) -> Result<(Self, u8), BinaryFastaError> {
+        let flags = if version >= BLOCKS_VERSION {
+            BLOCKS_FLAG
+        } else {
+            0
+        };
+        Ok((Molecule::from_byte(value & !flags)?, value & flags))
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Molecule::Nucleotide => 0,
+            Molecule::Protein => 1,
+        }
+    }
+
+    pub fn bits_per_residue(self) -> usize {
+        match self {
+            Molecule::Nucleotide => 2,
+            Molecule::Protein => 5,
+        }
+    }
+
+    // Number of bytes holding `length` packed residues. The last byte may not be full.
+    pub fn packed_length(self, length: usize) -> usize {
+        (length * self.bits_per_residue()).div_ceil(8)
+    }
+}
+
+// Reverses the order of the 4 nucleotides in a byte, and complements each of them.
+// With A = 00; C = 01; G = 10; T = 11 the complement of a nucleotide is its bitwise NOT.
+const fn reverse_complement_table() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let complement = !(byte as u8);
+        let mut reversed = 0u8;
+        let mut i = 0;
+        while i < 4 {
+            reversed = (reversed << 2) | ((complement >> (2 * i)) & 0b11);
+            i += 1;
+        }
+        table[byte] = reversed;
+        byte += 1;
+    }
+    table
+}
+
+const REVERSE_COMPLEMENT: [u8; 256] = reverse_complement_table();
+
+// The fields of a section that come before its packed sequence, as read by
+// `BinaryFastaSection::read_section_header`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SectionHeader {
+    pub descriptor: String,
+    // Signed like `BinaryFastaSection::sequence_length`.
+    pub sequence_length: i32,
+    pub molecule: Molecule,
+    pub metadata: Metadata,
+    pub ambiguous_runs: Vec<AmbiguousRun>,
+    // Position of the first byte of the packed sequence in the stream.
+    pub data_offset: u64,
+}
+
+impl SectionHeader {
+    // Number of nucleotides or amino acids in the sequence.
+    pub fn length(&self) -> usize {
+        self.sequence_length.unsigned_abs() as usize
+    }
+
+    // Number of bytes of the packed sequence.
+    pub fn packed_length(&self) -> usize {
+        self.molecule.packed_length(self.length())
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub struct BinaryFastaSection {
     pub descriptor: String,
     // Sequence of bytes holding the 2-bit nucleotide encoding
     // Where A = 00; C = 01; G = 10; T/U = 11
+    // or, for proteins, the 5-bit index of each residue in AMINO_ACIDS.
     pub sequence: Vec<u8>,
     // Need the exact number, because the last byte written may not be full.
     // The sign represents whether the original sequence was DNA (1) or RNA (0).
+    // Protein lengths are always positive.
     pub sequence_length: i32,
+    pub molecule: Molecule,
+    // Only kept in BASTA files.
+    pub metadata: Metadata,
+    // Bases without a 2-bit code, in order. Always empty for proteins.
+    pub ambiguous_runs: Vec<AmbiguousRun>,
 }
 
 impl BinaryFastaSection {
-    fn get_descriptor_byte_length(&self) -> u8 {
-        u8::try_from(self.descriptor.len()).expect("Descriptor is too long.")
+    // Lowest format version that can hold the section.
+    pub fn format_version(&self) -> u8 {
+        if self.has_blocks() {
+            BLOCKS_VERSION
+        } else {
+            MOLECULE_VERSION
+        }
+    }
+
+    fn has_blocks(&self) -> bool {
+        !self.metadata.is_empty() || !self.ambiguous_runs.is_empty()
+    }
+
+    // Descriptors longer than this are fine in FASTA, but cannot be written to BASTA.
+    fn get_descriptor_byte_length(&self) -> Result<u8, BinaryFastaError> {
+        u8::try_from(self.descriptor.len()).map_err(|_| BinaryFastaError::DescriptorTooLong {
+            descriptor: self.descriptor.clone(),
+        })
+    }
+
+    // Number of nucleotides or amino acids in the sequence.
+    pub fn length(&self) -> usize {
+        self.sequence_length.unsigned_abs() as usize
     }
 
-    pub fn from_fasta(fasta_section: FastaSection) -> Self {
-        // The sign bit signals whether the source data was DNA (+) or RNA (-)
-        let sign = if fasta_section.is_dna() { 1 } else { -1 };
-        let sequence_length = sign
-            * i32::try_from(fasta_section.sequence.chars().count()).expect("Sequence is too long.");
+    // The sequence name, which is the descriptor up to the first whitespace.
+    pub fn id(&self) -> &str {
+        self.descriptor.split_whitespace().next().unwrap_or("")
+    }
+
+    // Replaces the sequence name, keeping the rest of the descriptor.
+    pub fn set_id(&mut self, id: &str) {
+        let id_length = self
+            .descriptor
+            .find(char::is_whitespace)
+            .unwrap_or(self.descriptor.len());
+        self.descriptor.replace_range(..id_length, id);
+    }
+
+    // 2-bit code of the nucleotide at `position`, which is A (0) for ambiguous bases.
+    pub fn nucleotide_code(&self, position: usize) -> u8 {
+        (self.sequence[position / 4] >> (6 - 2 * (position % 4))) & 0b11
+    }
+
+    // The ambiguous base at `position`, if there is one.
+    pub fn ambiguous_base(&self, position: usize) -> Option<u8> {
+        let index = self
+            .ambiguous_runs
+            .partition_point(|run| run.end() <= position);
+        self.ambiguous_runs
+            .get(index)
+            .filter(|run| run.start() <= position)
+            .map(|run| run.base)
+    }
+
+    // 2-bit codes of the nucleotides, or None for ambiguous bases.
+    pub fn nucleotide_codes(&self) -> impl Iterator<Item = Option<u8>> + '_ {
+        let mut runs = self.ambiguous_runs.iter().peekable();
+        (0..self.length()).map(move |position| {
+            while runs.next_if(|run| run.end() <= position).is_some() {}
+            match runs.peek() {
+                Some(run) if run.start() <= position => None,
+                _ => Some(self.nucleotide_code(position)),
+            }
+        })
+    }
+
+    // 6-bit indexes of the codons starting at `offset`, read straight from the
+    // packed bytes, or None for codons with an ambiguous base. Bases left over at
+    // the end are ignored.
+    pub fn codons(&self, offset: usize) -> impl Iterator<Item = Option<u8>> + '_ {
+        self.nucleotide_codes()
+            .skip(offset)
+            .tuples()
+            .map(|(first, second, third)| Some((first? << 4) | (second? << 2) | third?))
+    }
+
+    // Protein section translated from the codons starting at `offset`.
+    pub fn translate(
+        &self,
+        translation: &Translation,
+        offset: usize,
+    ) -> Result<BinaryFastaSection, BinaryFastaError> {
+        if self.molecule == Molecule::Protein {
+            return Err(BinaryFastaError::NotNucleotide {
+                name: self.descriptor.clone(),
+            });
+        }
+
+        let protein = translation.translate(self.codons(offset));
+        Ok(BinaryFastaSection {
+            descriptor: self.descriptor.clone(),
+            sequence: BinaryFastaSection::translate_protein_to_binary(&protein)?,
+            sequence_length: i32::try_from(protein.len()).expect("Sequence is too long."),
+            molecule: Molecule::Protein,
+            metadata: self.metadata.clone(),
+            ambiguous_runs: Vec::new(),
+        })
+    }
+
+    // Residues `start..end` (0-based), copied directly from the packed bytes.
+    pub fn subsequence(&self, start: usize, end: usize) -> BinaryFastaSection {
+        assert!(start <= end && end <= self.length(), "Range out of bounds.");
+
+        let bits = self.molecule.bits_per_residue();
+        let length = end - start;
+        let sign = if self.sequence_length < 0 { -1 } else { 1 };
 
         BinaryFastaSection {
+            descriptor: self.descriptor.clone(),
+            sequence: slice_bits(&self.sequence, start * bits, length * bits),
+            sequence_length: sign * i32::try_from(length).expect("Sequence is too long."),
+            molecule: self.molecule,
+            metadata: self.metadata.clone(),
+            ambiguous_runs: ambiguous::clip(&self.ambiguous_runs, start, end),
+        }
+    }
+
+    // Works on the packed bytes: the bytes are reversed and every byte is looked up
+    // in a table, which leaves the padding bits of the last byte at the start.
+    pub fn reverse_complement(&self) -> Result<BinaryFastaSection, BinaryFastaError> {
+        if self.molecule == Molecule::Protein {
+            return Err(BinaryFastaError::NotNucleotide {
+                name: self.descriptor.clone(),
+            });
+        }
+
+        let length = self.length();
+        let reversed: Vec<u8> = self
+            .sequence
+            .iter()
+            .rev()
+            .map(|&byte| REVERSE_COMPLEMENT[byte as usize])
+            .collect();
+        let padding = 2 * (self.sequence.len() * 4 - length);
+        let mut sequence = slice_bits(&reversed, padding, 2 * length);
+
+        // Ambiguous bases were packed as A, and are now T.
+        let ambiguous_runs = ambiguous::reverse_complement(&self.ambiguous_runs, length);
+        for position in ambiguous_runs.iter().flat_map(|run| run.start()..run.end()) {
+            sequence[position / 4] &= !(0b11 << (6 - 2 * (position % 4)));
+        }
+
+        Ok(BinaryFastaSection {
+            descriptor: self.descriptor.clone(),
+            sequence,
+            sequence_length: self.sequence_length,
+            molecule: self.molecule,
+            metadata: self.metadata.clone(),
+            ambiguous_runs,
+        })
+    }
+
+    pub fn from_fasta(fasta_section: FastaSection) -> Result<Self, BinaryFastaError> {
+        let molecule = fasta_section.molecule()?;
+        let length =
+            i32::try_from(fasta_section.sequence.chars().count()).expect("Sequence is too long.");
+
+        let mut ambiguous_runs: Vec<AmbiguousRun> = Vec::new();
+        let (sequence, sequence_length) = match molecule {
+            Molecule::Nucleotide => {
+                for (position, base) in fasta_section.sequence.bytes().enumerate() {
+                    if ambiguous::is_ambiguous(base) {
+                        ambiguous::push_base(
+                            &mut ambiguous_runs,
+                            position,
+                            base.to_ascii_uppercase(),
+                        );
+                    }
+                }
+                // The sign bit signals whether the source data was DNA (+) or RNA (-)
+                let sign = if fasta_section.is_dna() { 1 } else { -1 };
+                (
+                    BinaryFastaSection::translate_to_binary(&fasta_section.sequence)?,
+                    sign * length,
+                )
+            }
+            Molecule::Protein => (
+                BinaryFastaSection::translate_protein_to_binary(&fasta_section.sequence)?,
+                length,
+            ),
+        };
+
+        Ok(BinaryFastaSection {
             descriptor: fasta_section.descriptor,
-            sequence: BinaryFastaSection::translate_to_binary(&fasta_section.sequence),
+            sequence,
             sequence_length,
-        }
+            molecule,
+            metadata: Metadata::new(),
+            ambiguous_runs,
+        })
     }
 
+    // `version` is the format version of the file the section is read from.
     pub fn from_bytes(
         byte_stream: &mut impl Iterator<Item = u8>,
+        version: u8,
     ) -> Result<BinaryFastaSection, BinaryFastaError> {
-        // Read descriptor length from 1st byte.
+        let (molecule, flags) = if version >= MOLECULE_VERSION {
+            Molecule::from_section_byte(
+                byte_stream.next().ok_or(BinaryFastaError::UnexpectedEof)?,
+                version,
+            )?
+        } else {
+            (Molecule::Nucleotide, 0)
+        };
+
+        // Read descriptor length.
         let descriptor_length = byte_stream.next().ok_or(BinaryFastaError::UnexpectedEof)?;
 
         // Next 4 bytes contain a signed 32-bit integer.
@@ -62,13 +384,25 @@ impl BinaryFastaSection {
 
         // The binary sequence length is stored as a negative value if the sequence is RNA.
         // Get the absolute value for the "sequence_bytes" logic ahead.
-        let positive_length = sequence_length.abs();
+        let positive_length = sequence_length.unsigned_abs() as usize;
 
-        // The sequence has 4 nucleotides per byte, so divide by 4, but get 1
-        // more byte if the length is not divisible by 4 (there is a final
-        // byte that is partially filled with nucleotide data.)
-        let sequence_bytes: usize =
-            ((positive_length / 4) + if positive_length % 4 != 0 { 1 } else { 0 }) as usize;
+        let (metadata, ambiguous_runs) = if flags & BLOCKS_FLAG != 0 {
+            let length: Vec<u8> = byte_stream.take(4).collect();
+            let [l0, l1, l2, l3] = length[..] else {
+                return Err(BinaryFastaError::UnexpectedEof);
+            };
+            let length = u32::from_be_bytes([l0, l1, l2, l3]) as usize;
+            let list: Vec<u8> = byte_stream.take(length).collect();
+            if list.len() != length {
+                return Err(BinaryFastaError::UnexpectedEof);
+            }
+            read_blocks(&list, molecule, positive_length, &descriptor)?
+        } else {
+            (Metadata::new(), Vec::new())
+        };
+
+        // The final byte may be partially filled with sequence data.
+        let sequence_bytes = molecule.packed_length(positive_length);
 
         let sequence: Vec<u8> = byte_stream.take(sequence_bytes).collect();
         if sequence.len() != sequence_bytes {
@@ -79,14 +413,102 @@ impl BinaryFastaSection {
             descriptor,
             sequence,
             sequence_length,
+            molecule,
+            metadata,
+            ambiguous_runs,
         })
     }
 
-    pub fn convert_to_bytes(&self) -> Vec<u8> {
+    // Reads the fields of the section starting at the current position of the reader,
+    // which is left at the start of the packed sequence, to be read with
+    // `read_section_data` or skipped with `skip_section`. Returns None if the reader
+    // is at the end of the stream.
+    pub fn read_section_header<R: Read + Seek>(
+        reader: &mut R,
+        version: u8,
+    ) -> Result<Option<SectionHeader>, BinaryFastaError> {
+        // A stream may only end between two sections.
+        let mut first = [0u8; 1];
+        if reader.read(&mut first)? == 0 {
+            return Ok(None);
+        }
+
+        let (molecule, flags, descriptor_length) = if version >= MOLECULE_VERSION {
+            let (molecule, flags) = Molecule::from_section_byte(first[0], version)?;
+            (molecule, flags, read_array::<1>(reader)?[0])
+        } else {
+            (Molecule::Nucleotide, 0, first[0])
+        };
+        let sequence_length = i32::from_be_bytes(read_array::<4>(reader)?);
+
+        let mut descriptor = vec![0u8; descriptor_length as usize];
+        read_exact(reader, &mut descriptor)?;
+        let descriptor =
+            String::from_utf8(descriptor).map_err(|_| BinaryFastaError::InvalidUtf8Descriptor)?;
+
+        let length = sequence_length.unsigned_abs() as usize;
+        let (metadata, ambiguous_runs) = if flags & BLOCKS_FLAG != 0 {
+            let list_length = u32::from_be_bytes(read_array::<4>(reader)?) as u64;
+            // Only the bytes that are there are allocated, whatever the length says.
+            let mut list = Vec::new();
+            reader.by_ref().take(list_length).read_to_end(&mut list)?;
+            if list.len() as u64 != list_length {
+                return Err(BinaryFastaError::UnexpectedEof);
+            }
+            read_blocks(&list, molecule, length, &descriptor)?
+        } else {
+            (Metadata::new(), Vec::new())
+        };
+
+        Ok(Some(SectionHeader {
+            descriptor,
+            sequence_length,
+            molecule,
+            metadata,
+            ambiguous_runs,
+            data_offset: reader.stream_position()?,
+        }))
+    }
+
+    // Moves the reader past the packed sequence of the section whose header was just
+    // read, without reading it.
+    pub fn skip_section<R: Seek>(
+        reader: &mut R,
+        header: &SectionHeader,
+    ) -> Result<(), BinaryFastaError> {
+        // Buffered readers keep their buffer when the end of the section is already in it.
+        reader.seek_relative(header.packed_length() as i64)?;
+        Ok(())
+    }
+
+    // Reads the packed sequence of the section whose header was just read.
+    pub fn read_section_data<R: Read>(
+        reader: &mut R,
+        header: SectionHeader,
+    ) -> Result<BinaryFastaSection, BinaryFastaError> {
+        let mut sequence = vec![0u8; header.packed_length()];
+        read_exact(reader, &mut sequence)?;
+
+        Ok(BinaryFastaSection {
+            descriptor: header.descriptor,
+            sequence,
+            sequence_length: header.sequence_length,
+            molecule: header.molecule,
+            metadata: header.metadata,
+            ambiguous_runs: header.ambiguous_runs,
+        })
+    }
+
+    pub fn convert_to_bytes(&self) -> Result<Vec<u8>, BinaryFastaError> {
         let mut bytes = Vec::<u8>::new();
 
-        // 1st byte: descriptor length
-        bytes.push(self.get_descriptor_byte_length());
+        // 1st byte: molecule type (0 = nucleotide, 1 = protein),
+        // with the high bit set if blocks follow the descriptor.
+        let flags = if self.has_blocks() { BLOCKS_FLAG } else { 0 };
+        bytes.push(self.molecule.to_byte() | flags);
+
+        // Next byte: descriptor length
+        bytes.push(self.get_descriptor_byte_length()?);
 
         // Next 4 bytes: 2-bit sequence length as i32
         // the sign represents whether the data was DNA or RNA
@@ -96,12 +518,31 @@ impl BinaryFastaSection {
         // Next bytes are the descriptor text.
         bytes.extend_from_slice(self.descriptor.as_bytes());
 
+        // Then the list of blocks, if any, after its 4-byte length.
+        if self.has_blocks() {
+            let mut list = Vec::new();
+            if !self.metadata.is_empty() {
+                push_block(&mut list, METADATA_BLOCK, &self.metadata.convert_to_bytes());
+            }
+            if !self.ambiguous_runs.is_empty() {
+                push_block(
+                    &mut list,
+                    AMBIGUOUS_BLOCK,
+                    &ambiguous::convert_to_bytes(&self.ambiguous_runs),
+                );
+            }
+            let length = u32::try_from(list.len()).expect("Blocks are too long.");
+            bytes.extend_from_slice(&length.to_be_bytes());
+            bytes.extend_from_slice(&list);
+        }
+
         // Final bytes are the sequence bits.
         bytes.extend_from_slice(&self.sequence);
-        bytes
+        Ok(bytes)
     }
 
-    pub fn translate_to_binary(sequence: &str) -> Vec<u8> {
+    // Ambiguous bases are packed as A, and kept in `ambiguous_runs` by `from_fasta`.
+    pub fn translate_to_binary(sequence: &str) -> Result<Vec<u8>, BinaryFastaError> {
         // Convert 4 utf-8 characters to 1 byte of binary data with 2-bits per nucleotide
         let mut results: Vec<u8> = Vec::new();
 
@@ -115,6 +556,9 @@ impl BinaryFastaSection {
                     'A' => {
                         // 00 (no bits to flip)
                     }
+                    c if c.is_ascii() && ambiguous::is_ambiguous(c as u8) => {
+                        // 00, like A
+                    }
                     'C' => {
                         // 01 (flip the second bit)
                         binary_data += 2u8.pow(7 - current_position - 1)
@@ -129,57 +573,180 @@ impl BinaryFastaSection {
                             + 2u8.pow(7 - current_position)
                             + 2u8.pow(7 - current_position - 1)
                     }
-                    _ => panic!("Invalid sequence character '{}'", c),
+                    _ => return Err(BinaryFastaError::InvalidSequenceCharacter { character: c }),
                 }
                 // Move to the next 2 bits
                 current_position += 2;
             }
             results.push(binary_data)
         }
-        results
+        Ok(results)
+    }
+
+    pub fn translate_protein_to_binary(sequence: &str) -> Result<Vec<u8>, BinaryFastaError> {
+        // 5 bits per residue, from left to right, so a residue may span 2 bytes.
+        let mut results = vec![0u8; Molecule::Protein.packed_length(sequence.len())];
+
+        for (i, c) in sequence.bytes().enumerate() {
+            let code = AMINO_ACIDS
+                .iter()
+                .position(|&residue| residue == c.to_ascii_uppercase())
+                .ok_or(BinaryFastaError::InvalidSequenceCharacter {
+                    character: c as char,
+                })? as u16;
+
+            let bit_position = i * 5;
+            let byte_index = bit_position / 8;
+            let bit_offset = bit_position % 8;
+
+            // Place the 5 bits in a 16-bit window starting at the current byte.
+            let window = code << (11 - bit_offset);
+            results[byte_index] |= (window >> 8) as u8;
+            if bit_offset > 3 {
+                results[byte_index + 1] |= window as u8;
+            }
+        }
+        Ok(results)
+    }
+}
+
+// A block of a section: type (1 byte), value length (4 bytes, big-endian), then value.
+fn push_block(list: &mut Vec<u8>, block_type: u8, value: &[u8]) {
+    let length = u32::try_from(value.len()).expect("Block is too long.");
+    list.push(block_type);
+    list.extend_from_slice(&length.to_be_bytes());
+    list.extend_from_slice(value);
+}
+
+// The metadata and the ambiguous runs in the list of blocks of a section. Blocks of
+// other types were added by a newer version, and are skipped.
+fn read_blocks(
+    mut list: &[u8],
+    molecule: Molecule,
+    length: usize,
+    descriptor: &str,
+) -> Result<(Metadata, Vec<AmbiguousRun>), BinaryFastaError> {
+    let mut metadata = Metadata::new();
+    let mut ambiguous_runs = Vec::new();
+    while !list.is_empty() {
+        let [block_type, l0, l1, l2, l3, ref rest @ ..] = *list else {
+            return Err(BinaryFastaError::InvalidSectionBlocks {
+                name: descriptor.to_string(),
+            });
+        };
+        let block_length = u32::from_be_bytes([l0, l1, l2, l3]) as usize;
+        if rest.len() < block_length {
+            return Err(BinaryFastaError::InvalidSectionBlocks {
+                name: descriptor.to_string(),
+            });
+        }
+        let (value, rest) = rest.split_at(block_length);
+
+        match block_type {
+            METADATA_BLOCK => metadata = Metadata::from_bytes(value)?,
+            // Only nucleotide sequences may have ambiguous runs.
+            AMBIGUOUS_BLOCK => {
+                ambiguous_runs = ambiguous::from_bytes(value, length)
+                    .filter(|_| molecule == Molecule::Nucleotide)
+                    .ok_or_else(|| BinaryFastaError::InvalidAmbiguousRuns {
+                        name: descriptor.to_string(),
+                    })?
+            }
+            _ => {}
+        }
+        list = rest;
+    }
+    Ok((metadata, ambiguous_runs))
+}
+
+// Copies `bit_length` bits starting at `bit_start` (counted from the most significant
+// bit of the first byte) into new bytes, leaving the unused bits of the last byte zero.
+fn slice_bits(bytes: &[u8], bit_start: usize, bit_length: usize) -> Vec<u8> {
+    let mut result: Vec<u8> = (0..bit_length.div_ceil(8))
+        .map(|i| {
+            let bit_position = bit_start + 8 * i;
+            let byte_index = bit_position / 8;
+            let high = bytes[byte_index] as u16;
+            let low = bytes.get(byte_index + 1).copied().unwrap_or(0) as u16;
+            (((high << 8) | low) >> (8 - bit_position % 8)) as u8
+        })
+        .collect();
+
+    let bits_in_last_byte = bit_length % 8;
+    if bits_in_last_byte != 0 {
+        if let Some(last) = result.last_mut() {
+            *last &= 0xFF << (8 - bits_in_last_byte);
+        }
+    }
+    result
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], BinaryFastaError> {
+    let mut arr = [0u8; N];
+    read_exact(reader, &mut arr)?;
+    Ok(arr)
+}
+
+fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), BinaryFastaError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => BinaryFastaError::UnexpectedEof,
+        _ => e.into(),
+    })
+}
+
+// Packs a FASTA record written as text, so tests can build sections in one line.
+#[cfg(test)]
+impl BinaryFastaSection {
+    pub fn from_text(descriptor: &str, sequence: &str) -> BinaryFastaSection {
+        BinaryFastaSection::from_fasta(FastaSection {
+            descriptor: String::from(descriptor),
+            sequence: String::from(sequence),
+        })
+        .unwrap()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::basta::metadata::MetadataEntry;
 
     #[test]
     fn test_ascii_to_binary_translations() {
-        assert_eq!(BinaryFastaSection::translate_to_binary(""), vec!());
+        assert_eq!(BinaryFastaSection::translate_to_binary("").unwrap(), vec!());
 
         assert_eq!(
-            BinaryFastaSection::translate_to_binary("a"),
+            BinaryFastaSection::translate_to_binary("a").unwrap(),
             vec!(0b0000_0000)
         );
         assert_eq!(
-            BinaryFastaSection::translate_to_binary("c"),
+            BinaryFastaSection::translate_to_binary("c").unwrap(),
             vec!(0b0100_0000)
         );
         assert_eq!(
-            BinaryFastaSection::translate_to_binary("g"),
+            BinaryFastaSection::translate_to_binary("g").unwrap(),
             vec!(0b1000_0000)
         );
         assert_eq!(
-            BinaryFastaSection::translate_to_binary("t"),
+            BinaryFastaSection::translate_to_binary("t").unwrap(),
             vec!(0b1100_0000)
         );
         assert_eq!(
-            BinaryFastaSection::translate_to_binary("u"),
+            BinaryFastaSection::translate_to_binary("u").unwrap(),
             vec!(0b1100_0000)
         );
 
         assert_eq!(
-            BinaryFastaSection::translate_to_binary("acgt"),
+            BinaryFastaSection::translate_to_binary("acgt").unwrap(),
             vec!(0b0001_1011)
         );
         assert_eq!(
-            BinaryFastaSection::translate_to_binary("TGCA"),
+            BinaryFastaSection::translate_to_binary("TGCA").unwrap(),
             vec!(0b1110_0100)
         );
 
         assert_eq!(
-            BinaryFastaSection::translate_to_binary("aaAAccCCggGGttTTuuUU"),
+            BinaryFastaSection::translate_to_binary("aaAAccCCggGGttTTuuUU").unwrap(),
             vec!(
                 0b0000_0000,
                 0b0101_0101,
@@ -203,7 +770,406 @@ mod tests {
             descriptor: String::from(descr1),
             sequence: vec![0b1111_1111, 0b1010_1010, 0b0101_0000, 0b0000_0101],
             sequence_length: 16i32,
+            molecule: Molecule::Nucleotide,
+            metadata: Metadata::new(),
+            ambiguous_runs: Vec::new(),
+        };
+        assert_eq!(
+            BinaryFastaSection::from_fasta(fasta_section).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_protein_to_binary_translations() {
+        assert_eq!(
+            BinaryFastaSection::translate_protein_to_binary("").unwrap(),
+            vec!()
+        );
+        // A = 0, C = 1, * = 25
+        assert_eq!(
+            BinaryFastaSection::translate_protein_to_binary("ac*").unwrap(),
+            vec!(0b0000_0000, 0b0111_0010)
+        );
+        assert_eq!(
+            BinaryFastaSection::translate_protein_to_binary("WWWWWWWW").unwrap(),
+            vec!(
+                0b1001_0100,
+                0b1010_0101,
+                0b0010_1001,
+                0b0100_1010,
+                0b0101_0010
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_characters_are_errors() {
+        assert!(matches!(
+            BinaryFastaSection::translate_to_binary("ACGX"),
+            Err(BinaryFastaError::InvalidSequenceCharacter { character: 'X' })
+        ));
+        assert!(matches!(
+            BinaryFastaSection::translate_protein_to_binary("MV-L"),
+            Err(BinaryFastaError::InvalidSequenceCharacter { character: '-' })
+        ));
+    }
+
+    #[test]
+    fn test_from_fasta_protein() {
+        let fasta_section = FastaSection {
+            descriptor: String::from("sp|P69905|HBA_HUMAN"),
+            sequence: String::from("MVLSPADKTNVKAAWGKVGAHAGEYGAEALERMFLSFPTTKTYFPHF*"),
         };
-        assert_eq!(BinaryFastaSection::from_fasta(fasta_section), expected);
+
+        let section = BinaryFastaSection::from_fasta(fasta_section).unwrap();
+        assert_eq!(section.molecule, Molecule::Protein);
+        assert_eq!(section.sequence_length, 48);
+        assert_eq!(section.sequence.len(), 30);
+    }
+
+    #[test]
+    fn test_from_fasta_ambiguous_nucleotides() {
+        let fasta_section = FastaSection {
+            descriptor: String::from("test 1"),
+            sequence: String::from("ACGTNNAcgRn"),
+        };
+
+        let section = BinaryFastaSection::from_fasta(fasta_section).unwrap();
+        assert_eq!(
+            section.sequence,
+            vec![0b0001_1011, 0b0000_0001, 0b1000_0000]
+        );
+        assert_eq!(
+            section.ambiguous_runs,
+            vec![
+                AmbiguousRun {
+                    start: 4,
+                    length: 2,
+                    base: b'N'
+                },
+                AmbiguousRun {
+                    start: 9,
+                    length: 1,
+                    base: b'R'
+                },
+                AmbiguousRun {
+                    start: 10,
+                    length: 1,
+                    base: b'N'
+                },
+            ]
+        );
+        assert_eq!(section.format_version(), BLOCKS_VERSION);
+        assert_eq!(FastaSection::from_basta(section).sequence, "ACGTNNACGRN");
+    }
+
+    #[test]
+    fn test_ambiguous_runs_round_trip() {
+        let mut annotated = BinaryFastaSection::from_text("chr1 test", "NNACGTRYACNN");
+        annotated
+            .metadata
+            .insert(MetadataEntry::parse("topology", "circular").unwrap())
+            .unwrap();
+        let ambiguous = BinaryFastaSection::from_text("chr1 test", "ACGTN");
+
+        let mut bytes = annotated.convert_to_bytes().unwrap();
+        assert_eq!(bytes[0], BLOCKS_FLAG);
+        bytes.extend(ambiguous.convert_to_bytes().unwrap());
+
+        let mut stream = bytes.clone().into_iter();
+        assert_eq!(
+            BinaryFastaSection::from_bytes(&mut stream, BLOCKS_VERSION).unwrap(),
+            annotated
+        );
+        assert_eq!(
+            BinaryFastaSection::from_bytes(&mut stream, BLOCKS_VERSION).unwrap(),
+            ambiguous
+        );
+
+        let mut reader = io::Cursor::new(bytes);
+        let header = BinaryFastaSection::read_section_header(&mut reader, BLOCKS_VERSION)
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.ambiguous_runs, annotated.ambiguous_runs);
+        assert_eq!(
+            BinaryFastaSection::read_section_data(&mut reader, header).unwrap(),
+            annotated
+        );
+    }
+
+    #[test]
+    fn test_read_invalid_ambiguous_runs() {
+        // A run past the end of the sequence.
+        let mut outside = BinaryFastaSection::from_text("chr1 test", "ACGTN");
+        outside.ambiguous_runs[0].start = 5;
+        let bytes = outside.convert_to_bytes().unwrap();
+
+        assert!(matches!(
+            BinaryFastaSection::from_bytes(&mut bytes.clone().into_iter(), BLOCKS_VERSION),
+            Err(BinaryFastaError::InvalidAmbiguousRuns { .. })
+        ));
+        assert!(matches!(
+            BinaryFastaSection::read_section_header(&mut io::Cursor::new(bytes), BLOCKS_VERSION),
+            Err(BinaryFastaError::InvalidAmbiguousRuns { .. })
+        ));
+    }
+
+    #[test]
+    fn test_skip_unknown_blocks() {
+        let section = BinaryFastaSection::from_text("chr1 test", "ACGTN");
+        let bytes = section.convert_to_bytes().unwrap();
+        let list_start = 6 + 9;
+
+        // A block of a type added by a newer version, before the known one.
+        let mut unknown = Vec::new();
+        push_block(&mut unknown, 0x7F, b"from the future");
+        let list_length = u32::from_be_bytes(bytes[list_start..list_start + 4].try_into().unwrap());
+        let mut newer = bytes[..list_start].to_vec();
+        newer.extend_from_slice(&(list_length + unknown.len() as u32).to_be_bytes());
+        newer.extend_from_slice(&unknown);
+        newer.extend_from_slice(&bytes[list_start + 4..]);
+        newer.extend_from_slice(&bytes);
+
+        let mut stream = newer.clone().into_iter();
+        for _ in 0..2 {
+            assert_eq!(
+                BinaryFastaSection::from_bytes(&mut stream, BLOCKS_VERSION).unwrap(),
+                section
+            );
+        }
+
+        let mut reader = io::Cursor::new(newer);
+        let header = BinaryFastaSection::read_section_header(&mut reader, BLOCKS_VERSION)
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.data_offset, (bytes.len() + unknown.len() - 2) as u64);
+        assert_eq!(
+            BinaryFastaSection::read_section_data(&mut reader, header).unwrap(),
+            section
+        );
+
+        // A block longer than the rest of the list.
+        let mut overflowing = bytes.clone();
+        overflowing[list_start + 4 + 4] += 1;
+        assert!(matches!(
+            BinaryFastaSection::from_bytes(&mut overflowing.into_iter(), BLOCKS_VERSION),
+            Err(BinaryFastaError::InvalidSectionBlocks { .. })
+        ));
+    }
+
+    #[test]
+    fn test_protein_bytes_round_trip() {
+        let section = BinaryFastaSection::from_text("protein", "MKQLEDKVEELLSKNYHLENEVARLKKLV");
+
+        let bytes = section.convert_to_bytes().unwrap();
+        assert_eq!(bytes[0], 1);
+
+        let parsed =
+            BinaryFastaSection::from_bytes(&mut bytes.into_iter(), MOLECULE_VERSION).unwrap();
+        assert_eq!(parsed, section);
+    }
+
+    #[test]
+    fn test_reverse_complement_table() {
+        // ACGT -> ACGT, AACG -> CGTT
+        assert_eq!(REVERSE_COMPLEMENT[0b0001_1011], 0b0001_1011);
+        assert_eq!(REVERSE_COMPLEMENT[0b0000_0110], 0b0110_1111);
+    }
+
+    #[test]
+    fn test_reverse_complement() {
+        for (sequence, expected) in [
+            ("", ""),
+            ("A", "T"),
+            ("ACG", "CGT"),
+            ("AACCGGTT", "AACCGGTT"),
+            ("GATTACAGG", "CCTGTAATC"),
+            ("acguu", "AACGU"),
+            ("ANNCGRT", "AYCGNNT"),
+        ] {
+            let reversed = BinaryFastaSection::from_text("chr1 test", sequence)
+                .reverse_complement()
+                .unwrap();
+            assert_eq!(
+                reversed,
+                BinaryFastaSection::from_text("chr1 test", expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_reverse_complement_rejects_protein() {
+        assert!(matches!(
+            BinaryFastaSection::from_text("chr1 test", "MVLSPADK").reverse_complement(),
+            Err(BinaryFastaError::NotNucleotide { .. })
+        ));
+    }
+
+    #[test]
+    fn test_subsequence() {
+        let sequence = "GATNNNAGGRAT";
+        for (start, end) in [(0, 12), (1, 4), (3, 10), (4, 5), (5, 5), (11, 12)] {
+            assert_eq!(
+                BinaryFastaSection::from_text("chr1 test", sequence).subsequence(start, end),
+                BinaryFastaSection::from_text("chr1 test", &sequence[start..end])
+            );
+        }
+
+        let protein = "MVLSPADKTNVKAAW";
+        assert_eq!(
+            BinaryFastaSection::from_text("chr1 test", protein).subsequence(3, 11),
+            BinaryFastaSection::from_text("chr1 test", &protein[3..11])
+        );
+    }
+
+    #[test]
+    fn test_id() {
+        assert_eq!(
+            BinaryFastaSection::from_text("chr1 test", "ACGT").id(),
+            "chr1"
+        );
+    }
+
+    #[test]
+    fn test_set_id() {
+        let mut renamed = BinaryFastaSection::from_text("chr1 test", "ACGT");
+        renamed.descriptor = String::from("1 dna:chromosome");
+        renamed.set_id("chr1");
+        assert_eq!(renamed.descriptor, "chr1 dna:chromosome");
+
+        renamed.descriptor = String::from("chrM");
+        renamed.set_id("MT");
+        assert_eq!(renamed.descriptor, "MT");
+    }
+
+    #[test]
+    fn test_descriptor_too_long() {
+        let mut long = BinaryFastaSection::from_text("chr1 test", "ACGT");
+        long.descriptor = "x".repeat(255);
+        assert!(long.convert_to_bytes().is_ok());
+
+        long.set_id(&"x".repeat(256));
+        assert!(matches!(
+            long.convert_to_bytes(),
+            Err(BinaryFastaError::DescriptorTooLong { descriptor }) if descriptor.len() == 256
+        ));
+    }
+
+    #[test]
+    fn test_read_header_and_skip_section() {
+        let protein = BinaryFastaSection::from_text("protein", "MKQLEDKVEELLSK");
+        let dna = BinaryFastaSection::from_text("chr1 test", "ACGTACGTAC");
+
+        let mut bytes = protein.convert_to_bytes().unwrap();
+        bytes.extend(dna.convert_to_bytes().unwrap());
+        let mut reader = io::Cursor::new(bytes);
+
+        let header = BinaryFastaSection::read_section_header(&mut reader, MOLECULE_VERSION)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            header,
+            SectionHeader {
+                descriptor: String::from("protein"),
+                sequence_length: 14,
+                molecule: Molecule::Protein,
+                metadata: Metadata::new(),
+                ambiguous_runs: Vec::new(),
+                data_offset: 13,
+            }
+        );
+        assert_eq!(header.packed_length(), 9);
+        BinaryFastaSection::skip_section(&mut reader, &header).unwrap();
+
+        let header = BinaryFastaSection::read_section_header(&mut reader, MOLECULE_VERSION)
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.descriptor, "chr1 test");
+        assert_eq!(header.data_offset, 22 + 15);
+        assert_eq!(
+            BinaryFastaSection::read_section_data(&mut reader, header).unwrap(),
+            dna
+        );
+
+        assert_eq!(
+            BinaryFastaSection::read_section_header(&mut reader, MOLECULE_VERSION).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_legacy_section_header() {
+        // Sections had no molecule byte before version 2.
+        let mut bytes = vec![4u8];
+        bytes.extend((-6i32).to_be_bytes());
+        bytes.extend(b"test");
+        bytes.extend([0b0001_1011, 0b0110_0000]);
+        let mut reader = io::Cursor::new(bytes);
+
+        let header = BinaryFastaSection::read_section_header(&mut reader, 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.molecule, Molecule::Nucleotide);
+        assert_eq!((header.length(), header.data_offset), (6, 9));
+    }
+
+    #[test]
+    fn test_read_truncated_section_header() {
+        let bytes = BinaryFastaSection::from_text("chr1 test", "ACGT")
+            .convert_to_bytes()
+            .unwrap();
+        let mut reader = io::Cursor::new(&bytes[..4]);
+
+        assert!(matches!(
+            BinaryFastaSection::read_section_header(&mut reader, MOLECULE_VERSION),
+            Err(BinaryFastaError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let mut annotated = BinaryFastaSection::from_text("chr1 test", "ACGTACGTAC");
+        for (key, value) in [("accession", "NC_012920.1"), ("topology", "circular")] {
+            annotated
+                .metadata
+                .insert(MetadataEntry::parse(key, value).unwrap())
+                .unwrap();
+        }
+        let plain = BinaryFastaSection::from_text("chr1 test", "ACGT");
+
+        let mut bytes = annotated.convert_to_bytes().unwrap();
+        assert_eq!(bytes[0], BLOCKS_FLAG);
+        // Without metadata, a section is written as in version 2.
+        assert_eq!(plain.convert_to_bytes().unwrap()[0], 0);
+        bytes.extend(plain.convert_to_bytes().unwrap());
+
+        let mut stream = bytes.clone().into_iter();
+        assert_eq!(
+            BinaryFastaSection::from_bytes(&mut stream, BLOCKS_VERSION).unwrap(),
+            annotated
+        );
+        assert_eq!(
+            BinaryFastaSection::from_bytes(&mut stream, BLOCKS_VERSION).unwrap(),
+            plain
+        );
+
+        let mut reader = io::Cursor::new(bytes);
+        let header = BinaryFastaSection::read_section_header(&mut reader, BLOCKS_VERSION)
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.metadata, annotated.metadata);
+        assert_eq!(header.data_offset, 6 + 9 + 4 + 5 + 18);
+        assert_eq!(
+            BinaryFastaSection::read_section_data(&mut reader, header).unwrap(),
+            annotated
+        );
+
+        // Version 2 files do not have the flag.
+        let mut stream = annotated.convert_to_bytes().unwrap().into_iter();
+        assert!(matches!(
+            BinaryFastaSection::from_bytes(&mut stream, MOLECULE_VERSION),
+            Err(BinaryFastaError::UnknownMolecule { value: 0x80 })
+        ));
     }
 }
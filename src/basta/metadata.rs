@@ -0,0 +1,393 @@
+use std::fmt;
+
+use crate::errors::BinaryFastaError;
+
+// Entries of a section are stored one after the other as tag (1 byte), value length
+// (2 bytes, big-endian) and value, in a block of at most this many bytes.
+pub const MAX_METADATA_LENGTH: usize = u16::MAX as usize;
+
+const ACCESSION_TAG: u8 = 1;
+const TAXONOMY_ID_TAG: u8 = 2;
+const TOPOLOGY_TAG: u8 = 3;
+const ASSEMBLY_TAG: u8 = 4;
+const CUSTOM_TAG: u8 = 5;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Topology {
+    Linear,
+    Circular,
+}
+
+impl Topology {
+    fn from_byte(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Topology::Linear),
+            1 => Some(Topology::Circular),
+            _ => None,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Topology::Linear => 0,
+            Topology::Circular => 1,
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Topology::Linear => write!(f, "linear"),
+            Topology::Circular => write!(f, "circular"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum MetadataEntry {
+    // Accession of the sequence in the database it comes from, UTF-8.
+    Accession(String),
+    // NCBI taxonomy ID, 4 bytes big-endian.
+    TaxonomyId(u32),
+    // 1 byte, 0 for linear and 1 for circular.
+    Topology(Topology),
+    // Version of the assembly the sequence belongs to, UTF-8.
+    Assembly(String),
+    // Any other key and value: key length (1 byte), key, then value, UTF-8.
+    Custom { key: String, value: String },
+    // Entries with a tag this version does not know, written by a newer one. They are
+    // kept as they are, so copying a section does not lose them.
+    Unknown { tag: u8, value: Vec<u8> },
+}
+
+impl MetadataEntry {
+    // Parses a value given on the command line or in a table. Keys other than
+    // accession, taxid, topology and assembly make custom entries.
+    pub fn parse(key: &str, value: &str) -> Result<Self, BinaryFastaError> {
+        let invalid = || BinaryFastaError::InvalidMetadataValue {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        // Keys and values are written to tab-separated tables.
+        if value.contains(['\t', '\n', '\r']) {
+            return Err(invalid());
+        }
+
+        let entry = match key {
+            "accession" => MetadataEntry::Accession(value.to_string()),
+            "taxid" => MetadataEntry::TaxonomyId(value.parse().map_err(|_| invalid())?),
+            "topology" => MetadataEntry::Topology(match value {
+                "linear" => Topology::Linear,
+                "circular" => Topology::Circular,
+                _ => return Err(invalid()),
+            }),
+            "assembly" => MetadataEntry::Assembly(value.to_string()),
+            _ if key.is_empty()
+                || key.len() > u8::MAX as usize
+                || key.contains(char::is_whitespace) =>
+            {
+                return Err(invalid())
+            }
+            _ => MetadataEntry::Custom {
+                key: key.to_string(),
+                value: value.to_string(),
+            },
+        };
+        Ok(entry)
+    }
+
+    pub fn key(&self) -> String {
+        match self {
+            MetadataEntry::Accession(_) => String::from("accession"),
+            MetadataEntry::TaxonomyId(_) => String::from("taxid"),
+            MetadataEntry::Topology(_) => String::from("topology"),
+            MetadataEntry::Assembly(_) => String::from("assembly"),
+            MetadataEntry::Custom { key, .. } => key.clone(),
+            MetadataEntry::Unknown { tag, .. } => format!("tag_{}", tag),
+        }
+    }
+
+    // Unknown values are shown as hexadecimal bytes.
+    pub fn value(&self) -> String {
+        match self {
+            MetadataEntry::Accession(value) | MetadataEntry::Assembly(value) => value.clone(),
+            MetadataEntry::TaxonomyId(id) => id.to_string(),
+            MetadataEntry::Topology(topology) => topology.to_string(),
+            MetadataEntry::Custom { value, .. } => value.clone(),
+            MetadataEntry::Unknown { value, .. } => {
+                value.iter().map(|byte| format!("{:02x}", byte)).collect()
+            }
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            MetadataEntry::Accession(_) => ACCESSION_TAG,
+            MetadataEntry::TaxonomyId(_) => TAXONOMY_ID_TAG,
+            MetadataEntry::Topology(_) => TOPOLOGY_TAG,
+            MetadataEntry::Assembly(_) => ASSEMBLY_TAG,
+            MetadataEntry::Custom { .. } => CUSTOM_TAG,
+            MetadataEntry::Unknown { tag, .. } => *tag,
+        }
+    }
+
+    // Whether the entries have the same key, without building it.
+    fn same_key(&self, other: &MetadataEntry) -> bool {
+        match (self, other) {
+            (MetadataEntry::Custom { key, .. }, MetadataEntry::Custom { key: other, .. }) => {
+                key == other
+            }
+            _ => self.tag() == other.tag(),
+        }
+    }
+
+    // Tag, value length and value.
+    fn encoded_length(&self) -> usize {
+        let value_length = match self {
+            MetadataEntry::Accession(value) | MetadataEntry::Assembly(value) => value.len(),
+            MetadataEntry::TaxonomyId(_) => 4,
+            MetadataEntry::Topology(_) => 1,
+            MetadataEntry::Custom { key, value } => 1 + key.len() + value.len(),
+            MetadataEntry::Unknown { value, .. } => value.len(),
+        };
+        3 + value_length
+    }
+
+    fn value_bytes(&self) -> Vec<u8> {
+        match self {
+            MetadataEntry::Accession(value) | MetadataEntry::Assembly(value) => {
+                value.as_bytes().to_vec()
+            }
+            MetadataEntry::TaxonomyId(id) => id.to_be_bytes().to_vec(),
+            MetadataEntry::Topology(topology) => vec![topology.to_byte()],
+            MetadataEntry::Custom { key, value } => {
+                let mut bytes = vec![key.len() as u8];
+                bytes.extend_from_slice(key.as_bytes());
+                bytes.extend_from_slice(value.as_bytes());
+                bytes
+            }
+            MetadataEntry::Unknown { value, .. } => value.clone(),
+        }
+    }
+
+    fn from_tag_and_value(tag: u8, value: &[u8]) -> Result<Self, BinaryFastaError> {
+        let invalid = || BinaryFastaError::InvalidMetadata { tag };
+        let text = |bytes: &[u8]| String::from_utf8(bytes.to_vec()).map_err(|_| invalid());
+
+        let entry = match tag {
+            ACCESSION_TAG => MetadataEntry::Accession(text(value)?),
+            TAXONOMY_ID_TAG => MetadataEntry::TaxonomyId(u32::from_be_bytes(
+                value.try_into().map_err(|_| invalid())?,
+            )),
+            TOPOLOGY_TAG => match value {
+                [byte] => MetadataEntry::Topology(Topology::from_byte(*byte).ok_or_else(invalid)?),
+                _ => return Err(invalid()),
+            },
+            ASSEMBLY_TAG => MetadataEntry::Assembly(text(value)?),
+            CUSTOM_TAG => {
+                let (&key_length, rest) = value.split_first().ok_or_else(invalid)?;
+                if rest.len() < key_length as usize {
+                    return Err(invalid());
+                }
+                let (key, value) = rest.split_at(key_length as usize);
+                MetadataEntry::Custom {
+                    key: text(key)?,
+                    value: text(value)?,
+                }
+            }
+            _ => MetadataEntry::Unknown {
+                tag,
+                value: value.to_vec(),
+            },
+        };
+        Ok(entry)
+    }
+}
+
+// Metadata attached to a section, beside its descriptor.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Metadata {
+    entries: Vec<MetadataEntry>,
+    // Number of bytes the entries take once encoded.
+    length: usize,
+}
+
+impl Metadata {
+    pub fn new() -> Self {
+        Metadata::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[MetadataEntry] {
+        &self.entries
+    }
+
+    // Replaces the entry with the same key, if there is one.
+    pub fn insert(&mut self, entry: MetadataEntry) -> Result<(), BinaryFastaError> {
+        let existing = self.entries.iter().position(|e| e.same_key(&entry));
+        let replaced_length = existing.map_or(0, |i| self.entries[i].encoded_length());
+
+        let length = self.length - replaced_length + entry.encoded_length();
+        if length > MAX_METADATA_LENGTH {
+            return Err(BinaryFastaError::MetadataTooLarge { length });
+        }
+        match existing {
+            Some(i) => self.entries[i] = entry,
+            None => self.entries.push(entry),
+        }
+        self.length = length;
+        Ok(())
+    }
+
+    // The block of entries, without its length.
+    pub fn convert_to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.length);
+        for entry in &self.entries {
+            let value = entry.value_bytes();
+            bytes.push(entry.tag());
+            bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(&value);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, BinaryFastaError> {
+        let mut metadata = Metadata::new();
+        while let [tag, high, low, rest @ ..] = bytes {
+            let length = u16::from_be_bytes([*high, *low]) as usize;
+            if rest.len() < length {
+                return Err(BinaryFastaError::InvalidMetadata { tag: *tag });
+            }
+            let (value, rest) = rest.split_at(length);
+            // Entries are kept as they are, even if a key is repeated.
+            metadata.length += 3 + length;
+            metadata
+                .entries
+                .push(MetadataEntry::from_tag_and_value(*tag, value)?);
+            bytes = rest;
+        }
+        // Bytes left over are the start of an entry that was cut.
+        if let Some(&tag) = bytes.first() {
+            return Err(BinaryFastaError::InvalidMetadata { tag });
+        }
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Metadata {
+        let mut metadata = Metadata::new();
+        for (key, value) in [
+            ("accession", "NC_012920.1"),
+            ("taxid", "9606"),
+            ("topology", "circular"),
+            ("assembly", "GRCh38.p14"),
+            ("source", "RefSeq"),
+        ] {
+            metadata
+                .insert(MetadataEntry::parse(key, value).unwrap())
+                .unwrap();
+        }
+        metadata
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let metadata = example();
+        let bytes = metadata.convert_to_bytes();
+
+        assert_eq!(&bytes[..3], &[ACCESSION_TAG, 0, 11]);
+        assert_eq!(Metadata::from_bytes(&bytes).unwrap(), metadata);
+        assert_eq!(
+            metadata.entries()[2],
+            MetadataEntry::Topology(Topology::Circular)
+        );
+        assert_eq!(Metadata::from_bytes(&[]).unwrap(), Metadata::new());
+    }
+
+    #[test]
+    fn test_insert_replaces_same_key() {
+        let mut metadata = example();
+        metadata
+            .insert(MetadataEntry::parse("taxid", "10090").unwrap())
+            .unwrap();
+        metadata
+            .insert(MetadataEntry::parse("source", "GenBank").unwrap())
+            .unwrap();
+
+        assert_eq!(metadata.entries().len(), 5);
+        assert_eq!(metadata.length, metadata.convert_to_bytes().len());
+        assert_eq!(metadata.entries()[1], MetadataEntry::TaxonomyId(10090));
+        assert_eq!(metadata.entries()[4].value(), "GenBank");
+    }
+
+    #[test]
+    fn test_unknown_entries_are_kept() {
+        let mut bytes = vec![200, 0, 2, 0xAB, 0xCD];
+        bytes.extend(example().convert_to_bytes());
+
+        let metadata = Metadata::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            metadata.entries()[0],
+            MetadataEntry::Unknown {
+                tag: 200,
+                value: vec![0xAB, 0xCD]
+            }
+        );
+        assert_eq!(metadata.entries()[0].key(), "tag_200");
+        assert_eq!(metadata.entries()[0].value(), "abcd");
+        assert_eq!(metadata.convert_to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_invalid_metadata() {
+        // Cut entry, then a taxonomy ID of 3 bytes, then a topology of 2.
+        for bytes in [
+            &[ACCESSION_TAG, 0, 5, b'N'][..],
+            &[ACCESSION_TAG][..],
+            &[TAXONOMY_ID_TAG, 0, 3, 1, 2, 3][..],
+            &[TOPOLOGY_TAG, 0, 1, 2][..],
+        ] {
+            assert!(matches!(
+                Metadata::from_bytes(bytes),
+                Err(BinaryFastaError::InvalidMetadata { .. })
+            ));
+        }
+
+        assert!(MetadataEntry::parse("taxid", "human").is_err());
+        assert!(MetadataEntry::parse("topology", "round").is_err());
+        assert!(MetadataEntry::parse("my key", "value").is_err());
+        assert!(MetadataEntry::parse("note", "two\tcolumns").is_err());
+    }
+
+    #[test]
+    fn test_too_large() {
+        let mut metadata = Metadata::new();
+        let value = "x".repeat(MAX_METADATA_LENGTH);
+
+        assert!(matches!(
+            metadata.insert(MetadataEntry::parse("note", &value).unwrap()),
+            Err(BinaryFastaError::MetadataTooLarge { .. })
+        ));
+        assert!(metadata.is_empty());
+
+        // Replacing an entry frees its bytes.
+        let half = "x".repeat(MAX_METADATA_LENGTH / 2);
+        let note = MetadataEntry::parse("note", &half).unwrap();
+        metadata.insert(note.clone()).unwrap();
+        metadata.insert(note).unwrap();
+        assert!(matches!(
+            metadata.insert(MetadataEntry::parse("other", &half).unwrap()),
+            Err(BinaryFastaError::MetadataTooLarge { .. })
+        ));
+        assert_eq!(metadata.entries().len(), 1);
+        assert_eq!(metadata.length, metadata.convert_to_bytes().len());
+    }
+}
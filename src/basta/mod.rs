@@ -1,2 +1,5 @@
+pub mod ambiguous;
 pub mod binary_fasta_data;
+pub mod binary_fasta_header;
 pub mod binary_fasta_section;
+pub mod metadata;
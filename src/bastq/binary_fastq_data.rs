@@ -0,0 +1,119 @@
+use std::fs::File;
+use std::io::{self, prelude::*};
+use std::path::Path;
+
+use crate::bastq::binary_fastq_section::BinaryFastqSection;
+use crate::errors::BinaryFastaError;
+use crate::fastq::fastq_section::FastqSection;
+use crate::output_file::OutputFile;
+
+// BASTQ files start with a header like BASTA files (magic, version, flags),
+// with their own magic so the two can be told apart. The version follows the
+// BASTA version, since every BASTQ section embeds a BASTA section.
+pub const MAGIC: &[u8; 4] = b"BSTQ";
+pub const FORMAT_VERSION: u8 = 2;
+const HEADER_LENGTH: usize = 6;
+
+pub fn is_bastq(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub fn from_fastq<I>(
+    fastq_data: I,
+) -> impl Iterator<Item = Result<BinaryFastqSection, BinaryFastaError>>
+where
+    I: Iterator<Item = Result<FastqSection, BinaryFastaError>>,
+{
+    fastq_data.map(|res| res.and_then(BinaryFastqSection::from_fastq))
+}
+
+pub fn write<I>(iter: I, file_path: &Path, overwrite: bool) -> Result<(), BinaryFastaError>
+where
+    I: Iterator<Item = Result<BinaryFastqSection, BinaryFastaError>>,
+{
+    let mut writer = OutputFile::create(file_path, overwrite)?;
+
+    // Flags are reserved, currently always 0.
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[FORMAT_VERSION, 0])?;
+
+    for section in iter {
+        writer.write_all(&section?.convert_to_bytes()?)?;
+    }
+
+    writer.commit()
+}
+
+pub fn read(
+    file_path: &Path,
+) -> Result<impl Iterator<Item = Result<BinaryFastqSection, BinaryFastaError>>, BinaryFastaError> {
+    let file = File::open(file_path)?;
+    let mut reader = io::BufReader::new(file);
+
+    let mut header = [0u8; HEADER_LENGTH];
+    reader
+        .read_exact(&mut header)
+        .map_err(|_| BinaryFastaError::UnexpectedEof)?;
+    if !is_bastq(&header) {
+        return Err(BinaryFastaError::MalformedBastqHeader {
+            path: file_path.to_path_buf(),
+        });
+    }
+    let version = header[4];
+    if version > FORMAT_VERSION {
+        return Err(BinaryFastaError::UnsupportedVersion { version });
+    }
+
+    let mut bytes_iter = reader
+        .bytes()
+        .map(|res| res.expect("I/O error while reading bytes"))
+        .peekable();
+
+    Ok(std::iter::from_fn(move || {
+        if bytes_iter.peek().is_some() {
+            Some(BinaryFastqSection::from_bytes(&mut bytes_iter, version))
+        } else {
+            None
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reads.bastq");
+
+        let fastq_sections = vec![
+            Ok(FastqSection::new("read 1", "ACGTNNAC", "IIII!!II")),
+            Ok(FastqSection::new("read 2", "GGGA", "#II#")),
+        ];
+        write(from_fastq(fastq_sections.into_iter()), &path, false).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(b"BSTQ"));
+
+        let sections: Vec<_> = read(&path)
+            .unwrap()
+            .map(|res| FastqSection::from_bastq(res.unwrap()))
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                FastqSection::new("read 1", "ACGTNNAC", "IIII!!II"),
+                FastqSection::new("read 2", "GGGA", "#II#"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_rejects_other_files() {
+        assert!(matches!(
+            read(Path::new("tests/data/small_dna.fasta")),
+            Err(BinaryFastaError::MalformedBastqHeader { .. })
+        ));
+    }
+}
@@ -0,0 +1,363 @@
+use crate::{
+    basta::binary_fasta_section::{BinaryFastaSection, Molecule},
+    errors::BinaryFastaError,
+    fasta::fasta_section::FastaSection,
+    fastq::fastq_section::FastqSection,
+};
+
+// How the quality string of a read is stored. Whichever is smaller is written.
+const RAW_QUALITY: u8 = 0;
+const RUN_LENGTH_QUALITY: u8 = 1;
+
+#[derive(Debug, PartialEq)]
+pub struct BinaryFastqSection {
+    // Descriptor and bases, packed exactly like a BASTA section.
+    // N bases cannot be represented in 2 bits, so they are stored as A.
+    pub bases: BinaryFastaSection,
+    // (start, length) of every run of N bases in the sequence.
+    pub n_runs: Vec<(u32, u32)>,
+    // Phred+33 quality characters, one per base.
+    pub quality: Vec<u8>,
+}
+
+impl BinaryFastqSection {
+    pub fn from_fastq(fastq_section: FastqSection) -> Result<Self, BinaryFastaError> {
+        let (sequence, n_runs) = Self::extract_n_runs(&fastq_section.sequence)?;
+
+        let bases = BinaryFastaSection::from_fasta(FastaSection {
+            descriptor: fastq_section.descriptor,
+            sequence,
+        })?;
+
+        Ok(BinaryFastqSection {
+            bases,
+            n_runs,
+            quality: fastq_section.quality.into_bytes(),
+        })
+    }
+
+    // Replaces N bases with A (so the sequence can be packed), and returns where they were.
+    fn extract_n_runs(sequence: &str) -> Result<(String, Vec<(u32, u32)>), BinaryFastaError> {
+        let mut cleaned = String::with_capacity(sequence.len());
+        let mut n_runs: Vec<(u32, u32)> = Vec::new();
+
+        for (i, c) in sequence.chars().enumerate() {
+            match c {
+                'A' | 'C' | 'G' | 'T' | 'U' | 'a' | 'c' | 'g' | 't' | 'u' => cleaned.push(c),
+                'N' | 'n' => {
+                    let position = u32::try_from(i).expect("Sequence is too long.");
+                    match n_runs.last_mut() {
+                        Some((start, length)) if *start + *length == position => *length += 1,
+                        _ => n_runs.push((position, 1)),
+                    }
+                    cleaned.push('A');
+                }
+                _ => return Err(BinaryFastaError::InvalidSequenceCharacter { character: c }),
+            }
+        }
+        Ok((cleaned, n_runs))
+    }
+
+    // Runs of identical scores are common with binned qualities, where they are
+    // stored as the character and the run length (as a varint). Unbinned scores
+    // change at almost every base, so they are stored as they are.
+    fn encode_quality(quality: &[u8]) -> Vec<u8> {
+        let mut runs = vec![RUN_LENGTH_QUALITY];
+        for chunk in quality.chunk_by(|a, b| a == b) {
+            runs.push(chunk[0]);
+            push_varint(&mut runs, chunk.len() as u32);
+            if runs.len() > quality.len() {
+                break;
+            }
+        }
+        if runs.len() <= quality.len() {
+            return runs;
+        }
+
+        let mut raw = Vec::with_capacity(1 + quality.len());
+        raw.push(RAW_QUALITY);
+        raw.extend_from_slice(quality);
+        raw
+    }
+
+    // Reads the quality of a read of `length` bases, or returns None if it is malformed.
+    fn decode_quality(
+        byte_stream: &mut impl Iterator<Item = u8>,
+        length: usize,
+    ) -> Result<Option<Vec<u8>>, BinaryFastaError> {
+        let encoding = byte_stream.next().ok_or(BinaryFastaError::UnexpectedEof)?;
+        let mut quality = Vec::with_capacity(length);
+        match encoding {
+            RAW_QUALITY => {
+                for _ in 0..length {
+                    quality.push(byte_stream.next().ok_or(BinaryFastaError::UnexpectedEof)?);
+                }
+            }
+            RUN_LENGTH_QUALITY => {
+                while quality.len() < length {
+                    let value = byte_stream.next().ok_or(BinaryFastaError::UnexpectedEof)?;
+                    let count = read_varint(byte_stream)?;
+                    match count.map(|count| count as usize) {
+                        Some(count) if count > 0 && count <= length - quality.len() => {
+                            quality.resize(quality.len() + count, value)
+                        }
+                        _ => return Ok(None),
+                    }
+                }
+            }
+            _ => return Ok(None),
+        }
+        // Phred+33 characters are printable ASCII.
+        Ok(quality
+            .iter()
+            .all(|score| (b'!'..=b'~').contains(score))
+            .then_some(quality))
+    }
+
+    // `version` is the format version of the file. A BASTQ section starts exactly
+    // like a BASTA section of the same format version.
+    pub fn from_bytes(
+        byte_stream: &mut impl Iterator<Item = u8>,
+        version: u8,
+    ) -> Result<BinaryFastqSection, BinaryFastaError> {
+        let bases = BinaryFastaSection::from_bytes(byte_stream, version)?;
+        let length = bases.sequence_length.unsigned_abs() as usize;
+        let invalid = || BinaryFastaError::InvalidBastqRead {
+            name: bases.id().to_string(),
+        };
+        if bases.molecule != Molecule::Nucleotide {
+            return Err(invalid());
+        }
+
+        // The N runs must be in order, not overlap and fit in the sequence.
+        let n_run_count = read_u32(byte_stream)?;
+        if n_run_count as usize > length {
+            return Err(invalid());
+        }
+        let mut n_runs = Vec::with_capacity(n_run_count as usize);
+        let mut end = 0;
+        for _ in 0..n_run_count {
+            let (start, run_length) = (read_u32(byte_stream)?, read_u32(byte_stream)?);
+            if run_length == 0
+                || (start as usize) < end
+                || start as usize + run_length as usize > length
+            {
+                return Err(invalid());
+            }
+            end = start as usize + run_length as usize;
+            n_runs.push((start, run_length));
+        }
+
+        let quality = Self::decode_quality(byte_stream, length)?.ok_or_else(invalid)?;
+
+        Ok(BinaryFastqSection {
+            bases,
+            n_runs,
+            quality,
+        })
+    }
+
+    pub fn convert_to_bytes(&self) -> Result<Vec<u8>, BinaryFastaError> {
+        // Descriptor and packed bases, laid out like a BASTA section.
+        let mut bytes = self.bases.convert_to_bytes()?;
+
+        // Next 4 bytes: number of N runs, followed by the (start, length) of each
+        // run as big-endian u32s.
+        bytes.extend_from_slice(&(self.n_runs.len() as u32).to_be_bytes());
+        for (start, length) in &self.n_runs {
+            bytes.extend_from_slice(&start.to_be_bytes());
+            bytes.extend_from_slice(&length.to_be_bytes());
+        }
+
+        // Then the quality: 1 byte for the encoding, followed by either the Phred+33
+        // characters, or runs of a character and its repeat count (as a varint) that
+        // add up to the sequence length.
+        bytes.extend(Self::encode_quality(&self.quality));
+        Ok(bytes)
+    }
+}
+
+fn read_u32(byte_stream: &mut impl Iterator<Item = u8>) -> Result<u32, BinaryFastaError> {
+    let mut arr = [0u8; 4];
+    for byte in arr.iter_mut() {
+        *byte = byte_stream.next().ok_or(BinaryFastaError::UnexpectedEof)?;
+    }
+    Ok(u32::from_be_bytes(arr))
+}
+
+// LEB128: 7 bits per byte, least significant first, with the top bit set on every
+// byte but the last.
+fn push_varint(bytes: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+// Returns None if the varint does not fit in a u32.
+fn read_varint(
+    byte_stream: &mut impl Iterator<Item = u8>,
+) -> Result<Option<u32>, BinaryFastaError> {
+    let mut value = 0u32;
+    for shift in (0..32).step_by(7) {
+        let byte = byte_stream.next().ok_or(BinaryFastaError::UnexpectedEof)?;
+        value |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((shift < 28 || byte < 0x10).then_some(value));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{basta::metadata::Metadata, bastq::binary_fastq_data::FORMAT_VERSION};
+
+    #[test]
+    fn test_from_fastq() {
+        let fastq_section = FastqSection::new("read 1", "ACNNGTN", "IIII##!");
+
+        let expected = BinaryFastqSection {
+            bases: BinaryFastaSection {
+                descriptor: String::from("read 1"),
+                sequence: vec![0b0001_0000, 0b1011_0000],
+                sequence_length: 7i32,
+                molecule: Molecule::Nucleotide,
+                metadata: Metadata::new(),
+                ambiguous_runs: Vec::new(),
+            },
+            n_runs: vec![(2, 2), (6, 1)],
+            quality: b"IIII##!".to_vec(),
+        };
+        assert_eq!(
+            BinaryFastqSection::from_fastq(fastq_section).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_long_quality_runs() {
+        let quality = "I".repeat(300);
+        // 300 is 0b10_0101100 as a varint.
+        assert_eq!(
+            BinaryFastqSection::encode_quality(quality.as_bytes()),
+            vec![RUN_LENGTH_QUALITY, b'I', 0b1010_1100, 0b10]
+        );
+    }
+
+    // Pseudo-random Phred+33 qualities of a read, which drift from the high 30s
+    // down to the 20s towards its end. Binned qualities keep 4 of the scores, like
+    // newer Illumina instruments.
+    fn qualities(length: usize, binned: bool) -> Vec<u8> {
+        let mut state = 12345u32;
+        (0..length)
+            .map(|position| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                let score = 38 - (position * 12 / length) as u32 - (state >> 16) % 8;
+                let score = match score {
+                    _ if !binned => score,
+                    0..=9 => 2,
+                    10..=19 => 12,
+                    20..=29 => 23,
+                    _ => 37,
+                };
+                b'!' + score as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_quality_encoding_size() {
+        // Unbinned scores take 1 byte each, plus the encoding.
+        let unbinned = qualities(150, false);
+        let encoded = BinaryFastqSection::encode_quality(&unbinned);
+        assert_eq!(encoded.len(), unbinned.len() + 1);
+        assert_eq!(encoded[0], RAW_QUALITY);
+
+        // Binned scores are mostly runs, which take less than half the space.
+        let binned = qualities(150, true);
+        let encoded = BinaryFastqSection::encode_quality(&binned);
+        assert_eq!(encoded[0], RUN_LENGTH_QUALITY);
+        assert!(encoded.len() < binned.len() / 2, "{}", encoded.len());
+
+        for quality in [unbinned, binned, Vec::new()] {
+            let encoded = BinaryFastqSection::encode_quality(&quality);
+            assert_eq!(
+                BinaryFastqSection::decode_quality(&mut encoded.into_iter(), quality.len())
+                    .unwrap(),
+                Some(quality)
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_character_is_rejected() {
+        let fastq_section = FastqSection::new("read 1", "ACRT", "IIII");
+        assert!(matches!(
+            BinaryFastqSection::from_fastq(fastq_section),
+            Err(BinaryFastaError::InvalidSequenceCharacter { character: 'R' })
+        ));
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let fastq_section = FastqSection::new("read 1", "NNACGUUN", "!!IIII#I");
+        let section = BinaryFastqSection::from_fastq(fastq_section).unwrap();
+
+        let bytes = section.convert_to_bytes().unwrap();
+        let parsed =
+            BinaryFastqSection::from_bytes(&mut bytes.into_iter(), FORMAT_VERSION).unwrap();
+        assert_eq!(parsed, section);
+
+        let fastq_section = FastqSection::from_bastq(parsed);
+        assert_eq!(
+            fastq_section,
+            FastqSection::new("read 1", "NNACGUUN", "!!IIII#I")
+        );
+    }
+
+    #[test]
+    fn test_malformed_reads_are_rejected() {
+        let fastq_section = FastqSection::new("read 1", "ACNNGTN", "IIII##!");
+        let section = BinaryFastqSection::from_fastq(fastq_section).unwrap();
+        let bases = section.bases.convert_to_bytes().unwrap();
+
+        let malformed = |n_runs: &[(u32, u32)], quality: &[u8]| {
+            let mut bytes = bases.clone();
+            bytes.extend_from_slice(&(n_runs.len() as u32).to_be_bytes());
+            for (start, length) in n_runs {
+                bytes.extend_from_slice(&start.to_be_bytes());
+                bytes.extend_from_slice(&length.to_be_bytes());
+            }
+            bytes.extend_from_slice(quality);
+            BinaryFastqSection::from_bytes(&mut bytes.into_iter(), FORMAT_VERSION)
+        };
+
+        assert!(malformed(&[(2, 2), (6, 1)], b"\x00IIII##!").is_ok());
+        assert!(malformed(&[(2, 2)], b"\x01I\x07").is_ok());
+        for (n_runs, quality) in [
+            // N runs past the end, overlapping or empty.
+            (&[(6, 2)][..], &b"\x00IIII##!"[..]),
+            (&[(2, 2), (3, 1)][..], &b"\x00IIII##!"[..]),
+            (&[(2, 0)][..], &b"\x00IIII##!"[..]),
+            // Quality runs longer than the sequence, or empty.
+            (&[][..], &b"\x01I\x08"[..]),
+            (&[][..], &b"\x01I\x00I\x07"[..]),
+            // Unknown encoding, and a quality that is not printable.
+            (&[][..], &b"\x02IIII##!"[..]),
+            (&[][..], &b"\x00IIII##\n"[..]),
+        ] {
+            assert!(matches!(
+                malformed(n_runs, quality),
+                Err(BinaryFastaError::InvalidBastqRead { .. })
+            ));
+        }
+        // Truncated quality.
+        assert!(matches!(
+            malformed(&[], b"\x00IIII"),
+            Err(BinaryFastaError::UnexpectedEof)
+        ));
+    }
+}
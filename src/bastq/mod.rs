@@ -0,0 +1,2 @@
+pub mod binary_fastq_data;
+pub mod binary_fastq_section;
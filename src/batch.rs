@@ -0,0 +1,366 @@
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    basta::binary_fasta_data,
+    bastq::binary_fastq_data,
+    errors::BinaryFastaError,
+    fasta::fasta_data,
+    fastq::fastq_data,
+    nucleotide_file::{Direction, FileFormat, NucleotideFile},
+    twobit::two_bit_data,
+};
+
+// A file to convert, along with the directory (relative to the directory
+// passed on the command line) it was found in when walking recursively.
+#[derive(Debug, PartialEq)]
+pub struct BatchInput {
+    pub file_path: PathBuf,
+    pub relative_dir: PathBuf,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct BatchSummary {
+    pub converted: usize,
+    pub failed: usize,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
+
+impl fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Converted {} of {} files ({} bytes in, {} bytes out), {} failed.",
+            self.converted,
+            self.converted + self.failed,
+            self.bytes_in,
+            self.bytes_out,
+            self.failed
+        )
+    }
+}
+
+fn is_glob_pattern(s: &str) -> bool {
+    s.contains(['*', '?', '['])
+}
+
+// Expands the inputs given on the command line into the list of files to convert.
+// Inputs can be files, directories (with `recursive`), or glob patterns the shell
+// did not expand (e.g. because they were quoted). When walking directories, only
+// files with an extension that can be converted in `direction` (or in any direction,
+// if None) are picked up.
+pub fn collect_inputs(
+    inputs: &[String],
+    recursive: bool,
+    direction: Option<Direction>,
+) -> Result<Vec<BatchInput>, BinaryFastaError> {
+    let mut files = Vec::new();
+
+    for input in inputs {
+        let path = Path::new(input);
+
+        if path.is_dir() {
+            if !recursive {
+                return Err(BinaryFastaError::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "'{}' is a directory. Use --recursive to convert the files inside it.",
+                        path.display()
+                    ),
+                )));
+            }
+            walk_dir(path, Path::new(""), direction, &mut files)?;
+        } else if path.exists() {
+            files.push(BatchInput {
+                file_path: path.to_path_buf(),
+                relative_dir: PathBuf::new(),
+            });
+        } else if is_glob_pattern(input) {
+            let matches = expand_glob(input)?;
+            if matches.is_empty() {
+                return Err(BinaryFastaError::Io(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No files match the pattern '{}'.", input),
+                )));
+            }
+            files.extend(matches);
+        } else {
+            return Err(BinaryFastaError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("File '{}' not found.", path.display()),
+            )));
+        }
+    }
+    Ok(files)
+}
+
+fn expand_glob(pattern: &str) -> Result<Vec<BatchInput>, BinaryFastaError> {
+    let paths = glob::glob(pattern).map_err(|e| {
+        BinaryFastaError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid pattern '{}': {}", pattern, e),
+        ))
+    })?;
+
+    let mut files = Vec::new();
+    for path_res in paths {
+        let file_path = path_res.map_err(|e| BinaryFastaError::Io(e.into()))?;
+        if file_path.is_file() {
+            files.push(BatchInput {
+                file_path,
+                relative_dir: PathBuf::new(),
+            });
+        }
+    }
+    Ok(files)
+}
+
+// Collects every FASTA/BASTA file below `dir`, in a stable (sorted) order.
+fn walk_dir(
+    dir: &Path,
+    relative_dir: &Path,
+    direction: Option<Direction>,
+    files: &mut Vec<BatchInput>,
+) -> Result<(), BinaryFastaError> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<PathBuf>, io::Error>>()?;
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() {
+            let name = entry.file_name().unwrap_or_default();
+            walk_dir(&entry, &relative_dir.join(name), direction, files)?;
+        } else if has_extension_for(&entry, direction) {
+            files.push(BatchInput {
+                file_path: entry,
+                relative_dir: relative_dir.to_path_buf(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn has_extension_for(file_path: &Path, direction: Option<Direction>) -> bool {
+    match NucleotideFile::new(file_path) {
+        Ok(file) => direction.is_none_or(|direction| direction == file.format.direction()),
+        Err(_) => false,
+    }
+}
+
+// Uses the direction given on the command line, or detects it from the file.
+pub fn resolve_input(
+    file_path: &Path,
+    direction: Option<Direction>,
+) -> Result<NucleotideFile, BinaryFastaError> {
+    match direction {
+        Some(direction) => NucleotideFile::for_direction(file_path, direction),
+        None => NucleotideFile::detect(file_path),
+    }
+}
+
+// The converted file is written next to the input, unless an output directory is
+// given, in which case the directory structure found while walking is mirrored there.
+pub fn output_path(
+    input: &BatchInput,
+    input_file: &NucleotideFile,
+    output_dir: Option<&Path>,
+) -> PathBuf {
+    let switched = input_file.switch_extension().file_path;
+    match output_dir {
+        Some(dir) => {
+            let file_name = switched.file_name().unwrap_or_default();
+            dir.join(&input.relative_dir).join(file_name)
+        }
+        None => switched,
+    }
+}
+
+// Fails if two inputs would be converted to the same file, which happens when files with
+// the same name from different directories are converted into one output directory.
+// Inputs that cannot be read are left for `run` to report.
+pub fn check_output_collisions(
+    inputs: &[BatchInput],
+    direction: Option<Direction>,
+    output_dir: Option<&Path>,
+) -> Result<(), BinaryFastaError> {
+    let mut outputs: HashMap<PathBuf, &Path> = HashMap::new();
+    for input in inputs {
+        let Ok(input_file) = resolve_input(&input.file_path, direction) else {
+            continue;
+        };
+        let output = output_path(input, &input_file, output_dir);
+        if let Some(first) = outputs.get(&output) {
+            return Err(BinaryFastaError::OutputCollision {
+                first: first.to_path_buf(),
+                second: input.file_path.clone(),
+                output,
+            });
+        }
+        outputs.insert(output, &input.file_path);
+    }
+    Ok(())
+}
+
+pub fn create_parent_dir(file_path: &Path) -> Result<(), BinaryFastaError> {
+    if let Some(parent) = file_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn convert(
+    input_file: &NucleotideFile,
+    output_path: &Path,
+    overwrite: bool,
+) -> Result<(), BinaryFastaError> {
+    let input_path = &input_file.file_path;
+
+    match (input_file.format, input_file.output_format(output_path)) {
+        (FileFormat::Fasta, FileFormat::Basta) => {
+            let read_fasta_iter = fasta_data::read(input_path)?;
+            let binary_iter = binary_fasta_data::from_fasta(read_fasta_iter);
+            binary_fasta_data::write(binary_iter, output_path, overwrite)?;
+        }
+        (FileFormat::Basta, FileFormat::Fasta) => {
+            let read_basta_iter = binary_fasta_data::read(input_path)?;
+            let fasta_iter = fasta_data::from_basta(read_basta_iter);
+            fasta_data::write(fasta_iter, output_path, overwrite)?;
+        }
+        (FileFormat::Fastq, FileFormat::Bastq) => {
+            let read_fastq_iter = fastq_data::read(input_path)?;
+            let binary_iter = binary_fastq_data::from_fastq(read_fastq_iter);
+            binary_fastq_data::write(binary_iter, output_path, overwrite)?;
+        }
+        (FileFormat::Bastq, FileFormat::Fastq) => {
+            let read_bastq_iter = binary_fastq_data::read(input_path)?;
+            let fastq_iter = fastq_data::from_bastq(read_bastq_iter);
+            fastq_data::write(fastq_iter, output_path, overwrite)?;
+        }
+        (FileFormat::Fasta, FileFormat::TwoBit) => {
+            let read_fasta_iter = fasta_data::read(input_path)?;
+            let two_bit_iter = two_bit_data::from_fasta(read_fasta_iter);
+            two_bit_data::write(two_bit_iter, output_path, overwrite)?;
+        }
+        (FileFormat::Basta, FileFormat::TwoBit) => {
+            let read_basta_iter = binary_fasta_data::read(input_path)?;
+            let two_bit_iter = two_bit_data::from_basta(read_basta_iter);
+            two_bit_data::write(two_bit_iter, output_path, overwrite)?;
+        }
+        (FileFormat::TwoBit, FileFormat::Fasta) => {
+            let read_two_bit_iter = two_bit_data::read(input_path)?;
+            let fasta_iter = fasta_data::from_two_bit(read_two_bit_iter);
+            fasta_data::write(fasta_iter, output_path, overwrite)?;
+        }
+        (FileFormat::TwoBit, FileFormat::Basta) => {
+            let read_two_bit_iter = two_bit_data::read(input_path)?;
+            let binary_iter = binary_fasta_data::from_two_bit(read_two_bit_iter);
+            binary_fasta_data::write(binary_iter, output_path, overwrite)?;
+        }
+        (from, to) => unreachable!("{} cannot be converted to {}", from, to),
+    }
+    Ok(())
+}
+
+fn convert_one(
+    input: &BatchInput,
+    direction: Option<Direction>,
+    output_dir: Option<&Path>,
+    overwrite: bool,
+) -> Result<(u64, u64), BinaryFastaError> {
+    let input_file = resolve_input(&input.file_path, direction)?;
+    let output_path = output_path(input, &input_file, output_dir);
+
+    create_parent_dir(&output_path)?;
+    convert(&input_file, &output_path, overwrite)?;
+
+    let bytes_in = fs::metadata(&input_file.file_path)?.len();
+    let bytes_out = fs::metadata(&output_path)?.len();
+    Ok((bytes_in, bytes_out))
+}
+
+// Converts every input, reporting (but continuing past) files that fail.
+pub fn run(
+    inputs: &[BatchInput],
+    direction: Option<Direction>,
+    output_dir: Option<&Path>,
+    overwrite: bool,
+) -> BatchSummary {
+    let mut summary = BatchSummary::default();
+
+    for input in inputs {
+        match convert_one(input, direction, output_dir, overwrite) {
+            Ok((bytes_in, bytes_out)) => {
+                summary.converted += 1;
+                summary.bytes_in += bytes_in;
+                summary.bytes_out += bytes_out;
+            }
+            Err(e) => {
+                summary.failed += 1;
+                eprintln!("Failed to convert '{}': {}", input.file_path.display(), e);
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_path_next_to_input() {
+        let input = BatchInput {
+            file_path: PathBuf::from("/data/genes/gene1.fa"),
+            relative_dir: PathBuf::new(),
+        };
+        let input_file = NucleotideFile::new(&input.file_path).unwrap();
+
+        assert_eq!(
+            output_path(&input, &input_file, None),
+            PathBuf::from("/data/genes/gene1.ba")
+        );
+    }
+
+    #[test]
+    fn test_output_path_mirrors_relative_dir() {
+        let input = BatchInput {
+            file_path: PathBuf::from("/data/genes/chr1/gene1.fasta"),
+            relative_dir: PathBuf::from("chr1"),
+        };
+        let input_file = NucleotideFile::new(&input.file_path).unwrap();
+
+        assert_eq!(
+            output_path(&input, &input_file, Some(Path::new("/out"))),
+            PathBuf::from("/out/chr1/gene1.basta")
+        );
+    }
+
+    #[test]
+    fn test_is_glob_pattern() {
+        assert!(is_glob_pattern("*.fa"));
+        assert!(is_glob_pattern("gene?.fasta"));
+        assert!(is_glob_pattern("gene[12].fa"));
+        assert!(!is_glob_pattern("gene1.fa"));
+    }
+
+    #[test]
+    fn test_summary_display() {
+        let summary = BatchSummary {
+            converted: 2,
+            failed: 1,
+            bytes_in: 100,
+            bytes_out: 30,
+        };
+        assert_eq!(
+            summary.to_string(),
+            "Converted 2 of 3 files (100 bytes in, 30 bytes out), 1 failed."
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use std::path::Path;
+
+use binary_fasta::{annotate::Annotator, errors::BinaryFastaError, records};
+
+use crate::parser::AnnotateArgs;
+
+pub fn run(args: &AnnotateArgs) -> Result<(), BinaryFastaError> {
+    let mut annotator = Annotator {
+        clear: args.clear,
+        entries: args.entries.clone(),
+        names: args.names.iter().cloned().collect(),
+        ..Annotator::default()
+    };
+    if let Some(table) = &args.table {
+        annotator.read_table(Path::new(table))?;
+    }
+
+    let sections = records::read(Path::new(&args.input))?.map(move |res| {
+        res.and_then(|mut section| {
+            annotator.annotate(&mut section)?;
+            Ok(section)
+        })
+    });
+
+    // Only BASTA output keeps the metadata.
+    let output = args.output.output.as_deref().map(Path::new);
+    records::write(sections, output, args.output.force)
+}
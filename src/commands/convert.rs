@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use binary_fasta::{batch, errors::BinaryFastaError, nucleotide_file::Direction};
+
+use crate::parser::OutputArgs;
+
+// Converts the inputs in the given direction, or in the direction detected
+// per file if it is None.
+pub fn run(
+    inputs: &[String],
+    options: &OutputArgs,
+    direction: Option<Direction>,
+) -> Result<(), BinaryFastaError> {
+    let files = batch::collect_inputs(inputs, options.recursive, direction)?;
+    let output_dir = options.output_dir.as_deref().map(Path::new);
+
+    // A single file given explicitly is converted directly, and any error is returned as-is.
+    let single_file = inputs.len() == 1 && Path::new(&inputs[0]).is_file();
+
+    if single_file {
+        let input_file = batch::resolve_input(&files[0].file_path, direction)?;
+
+        let output_path = match &options.output {
+            Some(output_str) => Path::new(output_str).to_path_buf(),
+            None => batch::output_path(&files[0], &input_file, output_dir),
+        };
+
+        batch::create_parent_dir(&output_path)?;
+        return batch::convert(&input_file, &output_path, options.force);
+    }
+
+    if options.output.is_some() {
+        return Err(BinaryFastaError::OutputWithMultipleInputs {
+            inputs: files.len(),
+        });
+    }
+
+    batch::check_output_collisions(&files, direction, output_dir)?;
+    let summary = batch::run(&files, direction, output_dir, options.force);
+    println!("{}", summary);
+
+    if summary.failed > 0 {
+        return Err(BinaryFastaError::BatchFailed {
+            failed: summary.failed,
+            total: files.len(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::{io::Write, path::Path};
+
+use binary_fasta::{
+    dedup::{Deduplicator, Duplicate},
+    errors::BinaryFastaError,
+    output_file::OutputFile,
+    records,
+};
+
+use crate::parser::DedupArgs;
+
+pub fn run(args: &DedupArgs) -> Result<(), BinaryFastaError> {
+    // Created first, so an existing map file stops the run before anything is written.
+    let map = match &args.map {
+        Some(map) => Some(OutputFile::create(Path::new(map), args.output.force)?),
+        None => None,
+    };
+
+    let mut deduplicator = Deduplicator::new(args.by_descriptor);
+    let mut duplicates: Vec<Duplicate> = Vec::new();
+
+    let sections = records::read(Path::new(&args.input))?.filter_map(|res| match res {
+        Ok(section) => match deduplicator.check(&section) {
+            Some(duplicate) => {
+                duplicates.push(duplicate);
+                None
+            }
+            None => Some(Ok(section)),
+        },
+        Err(e) => Some(Err(e)),
+    });
+
+    let output = args.output.output.as_deref().map(Path::new);
+    records::write(sections, output, args.output.force)?;
+
+    if let Some(mut map) = map {
+        write_map(&duplicates, &mut map)?;
+        map.commit()?;
+    }
+    Ok(())
+}
+
+// One line per removed sequence, in input order, with the descriptor of the sequence
+// kept in its place and whether they share a sequence or an ID.
+fn write_map(duplicates: &[Duplicate], writer: &mut impl Write) -> Result<(), BinaryFastaError> {
+    writeln!(writer, "DROPPED\tKEPT\tREASON")?;
+    for duplicate in duplicates {
+        writeln!(
+            writer,
+            "{}\t{}\t{}",
+            duplicate.dropped,
+            duplicate.kept,
+            duplicate.reason.name()
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use std::path::Path;
+
+use binary_fasta::{detect, errors::BinaryFastaError};
+
+// Prints the format of each file, as determined by its content.
+pub fn run(inputs: &[String]) -> Result<(), BinaryFastaError> {
+    for input in inputs {
+        let format = detect::detect_format(Path::new(input))?;
+        println!("{}: {}", input, format);
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use binary_fasta::{
+    basta::binary_fasta_section::{BinaryFastaSection, Molecule},
+    diff::{self, Difference},
+    errors::BinaryFastaError,
+    output_file::OutputFile,
+    records,
+};
+
+use crate::parser::DiffArgs;
+
+// Returns whether the files hold the same sequences.
+pub fn run(args: &DiffArgs) -> Result<bool, BinaryFastaError> {
+    if args.quiet {
+        return write_diff(args, &mut io::sink());
+    }
+
+    match &args.output {
+        Some(output) => {
+            let mut writer = OutputFile::create(Path::new(output), args.force)?;
+            let same = write_diff(args, &mut writer)?;
+            writer.commit()?;
+            Ok(same)
+        }
+        None => {
+            let mut writer = BufWriter::new(io::stdout().lock());
+            let same = write_diff(args, &mut writer)?;
+            writer.flush()?;
+            Ok(same)
+        }
+    }
+}
+
+// Sections are matched by ID, whatever their order. One line per difference, in the
+// order of the first file, then the sections only in the second file, then a summary.
+fn write_diff(args: &DiffArgs, writer: &mut impl Write) -> Result<bool, BinaryFastaError> {
+    let limit = (!args.all_mismatches).then_some(args.mismatches);
+
+    // The second file is held in memory, packed.
+    let mut second_ids: Vec<String> = Vec::new();
+    let mut second: HashMap<String, BinaryFastaSection> = HashMap::new();
+    for res in records::read(Path::new(&args.second))? {
+        let section = res?;
+        let id = section.id().to_string();
+        if second.contains_key(&id) {
+            return Err(BinaryFastaError::DuplicateSequence { name: id });
+        }
+        second_ids.push(id.clone());
+        second.insert(id, section);
+    }
+
+    writeln!(writer, "# first: {}", args.first)?;
+    writeln!(writer, "# second: {}", args.second)?;
+
+    let mut first_ids = HashSet::new();
+    let mut identical = 0;
+    let mut different = 0;
+    let mut only_in_first = 0;
+    for res in records::read(Path::new(&args.first))? {
+        let section = res?;
+        let id = section.id();
+        if !first_ids.insert(id.to_string()) {
+            return Err(BinaryFastaError::DuplicateSequence {
+                name: id.to_string(),
+            });
+        }
+
+        let Some(other) = second.get(id) else {
+            writeln!(writer, "only_in_first\t{}", id)?;
+            only_in_first += 1;
+            continue;
+        };
+        let differences = diff::compare(&section, other, limit);
+        if differences.is_empty() {
+            identical += 1;
+        } else {
+            different += 1;
+        }
+        for difference in differences {
+            write_difference(id, &difference, writer)?;
+        }
+    }
+
+    let mut only_in_second = 0;
+    for id in second_ids.iter().filter(|id| !first_ids.contains(*id)) {
+        writeln!(writer, "only_in_second\t{}", id)?;
+        only_in_second += 1;
+    }
+
+    writeln!(writer, "# identical: {}", identical)?;
+    writeln!(writer, "# different: {}", different)?;
+    writeln!(writer, "# only in first: {}", only_in_first)?;
+    writeln!(writer, "# only in second: {}", only_in_second)?;
+    Ok(different == 0 && only_in_first == 0 && only_in_second == 0)
+}
+
+// Mismatch positions are 1-based.
+fn write_difference(
+    id: &str,
+    difference: &Difference,
+    writer: &mut impl Write,
+) -> Result<(), BinaryFastaError> {
+    match difference {
+        Difference::Descriptor { first, second } => {
+            writeln!(writer, "descriptor\t{}\t{}\t{}", id, first, second)?
+        }
+        Difference::Molecule { first, second } => writeln!(
+            writer,
+            "molecule\t{}\t{}\t{}",
+            id,
+            molecule_name(*first),
+            molecule_name(*second)
+        )?,
+        Difference::Length { first, second } => {
+            writeln!(writer, "length\t{}\t{}\t{}", id, first, second)?
+        }
+        Difference::Mismatches { count, shown } => {
+            writeln!(writer, "mismatches\t{}\t{}", id, count)?;
+            for mismatch in shown {
+                writeln!(
+                    writer,
+                    "mismatch\t{}\t{}\t{}\t{}",
+                    id,
+                    mismatch.position + 1,
+                    mismatch.first,
+                    mismatch.second
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn molecule_name(molecule: Molecule) -> &'static str {
+    match molecule {
+        Molecule::Nucleotide => "nucleotide",
+        Molecule::Protein => "protein",
+    }
+}
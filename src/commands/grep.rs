@@ -0,0 +1,47 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use binary_fasta::{errors::BinaryFastaError, motif, output_file::OutputFile, records};
+
+use crate::parser::GrepArgs;
+
+pub fn run(args: &GrepArgs) -> Result<(), BinaryFastaError> {
+    match &args.output {
+        Some(output) => {
+            let mut writer = OutputFile::create(Path::new(output), args.force)?;
+            write_matches(args, &mut writer)?;
+            writer.commit()?;
+        }
+        None => {
+            let mut writer = BufWriter::new(io::stdout().lock());
+            write_matches(args, &mut writer)?;
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn write_matches(args: &GrepArgs, writer: &mut impl Write) -> Result<(), BinaryFastaError> {
+    for input in &args.inputs {
+        for res in records::read(Path::new(input))? {
+            let section = res?;
+            let matches = motif::find_matches(
+                &section,
+                &args.patterns,
+                args.both_strands,
+                args.max_mismatches,
+            )?;
+            for m in matches {
+                m.write_bed(
+                    writer,
+                    section.id(),
+                    &args.patterns[m.pattern],
+                    args.max_mismatches > 0,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
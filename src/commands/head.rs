@@ -0,0 +1,13 @@
+use std::path::Path;
+
+use binary_fasta::{errors::BinaryFastaError, records};
+
+use crate::parser::HeadTailArgs;
+
+pub fn run(args: &HeadTailArgs) -> Result<(), BinaryFastaError> {
+    // Sections are read one at a time, so the rest of the file is never read.
+    let sections = records::read(Path::new(&args.input))?.take(args.count);
+
+    let output = args.output.output.as_deref().map(Path::new);
+    records::write(sections, output, args.output.force)
+}
@@ -0,0 +1,114 @@
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use binary_fasta::{
+    basta::{
+        binary_fasta_data::{self, SectionEntry},
+        binary_fasta_section::Molecule,
+    },
+    errors::BinaryFastaError,
+    fasta::fasta_section::LINE_WIDTH,
+    nucleotide_file::{FileFormat, NucleotideFile},
+};
+
+use crate::parser::InfoArgs;
+
+// Prints the header of each BASTA file, a table of its sections, and totals. Only the
+// section headers are read, every sequence is skipped.
+pub fn run(args: &InfoArgs) -> Result<(), BinaryFastaError> {
+    let mut writer = BufWriter::new(io::stdout().lock());
+    for input in &args.inputs {
+        write_info(Path::new(input), args.summary, &mut writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_info(path: &Path, summary: bool, writer: &mut impl Write) -> Result<(), BinaryFastaError> {
+    let format = NucleotideFile::detect(path)?.format;
+    if format != FileFormat::Basta {
+        return Err(BinaryFastaError::NotBasta {
+            path: path.to_path_buf(),
+            format,
+        });
+    }
+
+    let (header, entries) = binary_fasta_data::list_sections(path)?;
+    writeln!(writer, "# file: {}", path.display())?;
+    writeln!(writer, "# format version: {}", header.version)?;
+    writeln!(writer, "# flags: {:#04x}", header.flags)?;
+    if !summary {
+        writeln!(
+            writer,
+            "index\tdescriptor\tlength\tmolecule\toffset\tpacked_bytes"
+        )?;
+    }
+
+    let mut sections = 0;
+    let mut nucleotides = 0;
+    let mut amino_acids = 0;
+    let mut fasta_size = 0;
+    for (index, entry) in entries.enumerate() {
+        let entry = entry?;
+        let section = &entry.header;
+
+        sections += 1;
+        match section.molecule {
+            Molecule::Nucleotide => nucleotides += section.length(),
+            Molecule::Protein => amino_acids += section.length(),
+        }
+        // '>', descriptor and newline, then the sequence with a newline after every line
+        // of LINE_WIDTH characters and after the last one, as decode writes it.
+        fasta_size += section.descriptor.len()
+            + section.length()
+            + 2
+            + section.length().div_ceil(LINE_WIDTH).max(1);
+
+        if !summary {
+            write_entry(writer, index + 1, &entry)?;
+        }
+    }
+
+    let file_size = fs::metadata(path)?.len();
+    writeln!(writer, "# sections: {}", sections)?;
+    writeln!(writer, "# total bases: {}", nucleotides)?;
+    if amino_acids > 0 {
+        writeln!(writer, "# total amino acids: {}", amino_acids)?;
+    }
+    writeln!(writer, "# file size: {} bytes", file_size)?;
+    writeln!(writer, "# estimated FASTA size: {} bytes", fasta_size)?;
+    writeln!(
+        writer,
+        "# compression ratio: {:.2}",
+        fasta_size as f64 / file_size as f64
+    )?;
+    Ok(())
+}
+
+fn write_entry(
+    writer: &mut impl Write,
+    index: usize,
+    entry: &SectionEntry,
+) -> Result<(), BinaryFastaError> {
+    let section = &entry.header;
+    let molecule = match section.molecule {
+        Molecule::Protein => "protein",
+        Molecule::Nucleotide if section.sequence_length < 0 => "RNA",
+        Molecule::Nucleotide => "DNA",
+    };
+
+    writeln!(
+        writer,
+        "{}\t{}\t{}\t{}\t{}\t{}",
+        index,
+        section.descriptor,
+        section.length(),
+        molecule,
+        entry.offset,
+        section.packed_length()
+    )?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use binary_fasta::{
+    errors::BinaryFastaError,
+    kmer::{self, CanonicalKmers},
+    output_file::OutputFile,
+    records,
+};
+
+use crate::parser::KmersArgs;
+
+pub fn run(args: &KmersArgs) -> Result<(), BinaryFastaError> {
+    let counts = count_kmers(args)?;
+
+    match &args.output {
+        Some(output) => {
+            let mut writer = OutputFile::create(Path::new(output), args.force)?;
+            write_counts(args, &counts, &mut writer)?;
+            writer.commit()?;
+        }
+        None => {
+            let mut writer = BufWriter::new(io::stdout().lock());
+            write_counts(args, &counts, &mut writer)?;
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+// Counts are summed over every sequence of every input.
+fn count_kmers(args: &KmersArgs) -> Result<HashMap<u64, u64>, BinaryFastaError> {
+    let mut counts: HashMap<u64, u64> = HashMap::new();
+    for input in &args.inputs {
+        for res in records::read(Path::new(input))? {
+            let section = res?;
+            for kmer in CanonicalKmers::new(&section, args.k)? {
+                *counts.entry(kmer).or_insert(0) += 1;
+            }
+        }
+    }
+    Ok(counts)
+}
+
+// The count table lists k-mers in alphabetical order. The histogram has one line per
+// number of occurrences, with the number of distinct k-mers seen that many times.
+// K-mers seen fewer than `min_count` times are left out of both.
+fn write_counts(
+    args: &KmersArgs,
+    counts: &HashMap<u64, u64>,
+    writer: &mut impl Write,
+) -> Result<(), BinaryFastaError> {
+    let kept = counts.iter().filter(|(_, &count)| count >= args.min_count);
+
+    if args.histogram {
+        let mut histogram: BTreeMap<u64, u64> = BTreeMap::new();
+        for (_, &count) in kept {
+            *histogram.entry(count).or_insert(0) += 1;
+        }
+        for (count, kmers) in histogram {
+            writeln!(writer, "{}\t{}", count, kmers)?;
+        }
+    } else {
+        let mut kmers: Vec<(&u64, &u64)> = kept.collect();
+        kmers.sort_unstable();
+        for (&kmer, count) in kmers {
+            writeln!(writer, "{}\t{}", kmer::kmer_to_string(kmer, args.k), count)?;
+        }
+    }
+    Ok(())
+}
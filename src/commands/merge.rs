@@ -0,0 +1,13 @@
+use std::path::{Path, PathBuf};
+
+use binary_fasta::{errors::BinaryFastaError, merge, records};
+
+use crate::parser::MergeArgs;
+
+pub fn run(args: &MergeArgs) -> Result<(), BinaryFastaError> {
+    let inputs: Vec<PathBuf> = args.inputs.iter().map(PathBuf::from).collect();
+    let sections = merge::merge(inputs, args.duplicates);
+
+    let output = args.output.output.as_deref().map(Path::new);
+    records::write(sections, output, args.output.force)
+}
@@ -0,0 +1,39 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use binary_fasta::{
+    annotate::TABLE_HEADER,
+    basta::binary_fasta_data,
+    errors::BinaryFastaError,
+    nucleotide_file::{FileFormat, NucleotideFile},
+};
+
+use crate::parser::MetadataArgs;
+
+// Prints one line per metadata entry, as a table that annotate --table can read back.
+// Only the section headers are read, every sequence is skipped.
+pub fn run(args: &MetadataArgs) -> Result<(), BinaryFastaError> {
+    let path = Path::new(&args.input);
+    let format = NucleotideFile::detect(path)?.format;
+    if format != FileFormat::Basta {
+        return Err(BinaryFastaError::NotBasta {
+            path: path.to_path_buf(),
+            format,
+        });
+    }
+
+    let (_, entries) = binary_fasta_data::list_sections(path)?;
+    let mut writer = BufWriter::new(io::stdout().lock());
+    writeln!(writer, "{}", TABLE_HEADER)?;
+    for entry in entries {
+        let section = entry?.header;
+        let id = section.descriptor.split_whitespace().next().unwrap_or("");
+        for metadata in section.metadata.entries() {
+            writeln!(writer, "{}\t{}\t{}", id, metadata.key(), metadata.value())?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,20 @@
+pub mod annotate;
+pub mod convert;
+pub mod dedup;
+pub mod detect;
+pub mod diff;
+pub mod grep;
+pub mod head;
+pub mod info;
+pub mod kmers;
+pub mod merge;
+pub mod metadata;
+pub mod orfs;
+pub mod rename;
+pub mod revcomp;
+pub mod sample;
+pub mod sort;
+pub mod split;
+pub mod subset;
+pub mod tail;
+pub mod translate;
@@ -0,0 +1,90 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use binary_fasta::{
+    basta::binary_fasta_section::BinaryFastaSection,
+    errors::BinaryFastaError,
+    genetic_code::GeneticCode,
+    orf::{self, OrfFormat, OrfOptions},
+    output_file::OutputFile,
+    records::{self, SectionOutput},
+};
+
+use crate::parser::OrfsArgs;
+
+pub fn run(args: &OrfsArgs) -> Result<(), BinaryFastaError> {
+    let code = GeneticCode::from_id(args.table)
+        .ok_or(BinaryFastaError::UnknownGeneticCode { id: args.table })?;
+    let options = OrfOptions::new(code, &args.start_codons, args.min_length)?;
+
+    // Both outputs are created before anything is written, and only committed once
+    // both were written, so a failure leaves neither behind.
+    let mut output = args
+        .output
+        .as_ref()
+        .map(|output| OutputFile::create(Path::new(output), args.force))
+        .transpose()?;
+    let mut proteins_output = args
+        .proteins
+        .as_ref()
+        .map(|proteins| SectionOutput::create(Path::new(proteins), args.force))
+        .transpose()?;
+
+    // Proteins are only kept when they are asked for.
+    let mut proteins: Option<Vec<BinaryFastaSection>> =
+        proteins_output.as_ref().map(|_| Vec::new());
+
+    match output.as_mut() {
+        Some(writer) => write_orfs(args, &options, writer, &mut proteins)?,
+        None => {
+            let mut writer = BufWriter::new(io::stdout().lock());
+            write_orfs(args, &options, &mut writer, &mut proteins)?;
+            writer.flush()?;
+        }
+    }
+    if let (Some(proteins_output), Some(proteins)) = (proteins_output.as_mut(), proteins) {
+        proteins_output.write(proteins.into_iter().map(Ok))?;
+    }
+
+    if let Some(writer) = output {
+        writer.commit()?;
+    }
+    if let Some(proteins_output) = proteins_output {
+        proteins_output.commit()?;
+    }
+    Ok(())
+}
+
+// ORFs are named after their sequence and numbered in order of position: chr1_orf1, chr1_orf2...
+fn write_orfs(
+    args: &OrfsArgs,
+    options: &OrfOptions,
+    writer: &mut impl Write,
+    proteins: &mut Option<Vec<BinaryFastaSection>>,
+) -> Result<(), BinaryFastaError> {
+    if args.format == OrfFormat::Gff {
+        writeln!(writer, "##gff-version 3")?;
+    }
+
+    for res in records::read(Path::new(&args.input))? {
+        let section = res?;
+        let seq_id = section.id().to_string();
+
+        for (i, orf) in orf::find_orfs(&section, options)?.iter().enumerate() {
+            let name = format!("{}_orf{}", seq_id, i + 1);
+            match args.format {
+                OrfFormat::Bed => orf.write_bed(writer, &seq_id, &name)?,
+                OrfFormat::Gff => orf.write_gff(writer, &seq_id, &name)?,
+            }
+
+            if let Some(proteins) = proteins.as_mut() {
+                let mut protein = orf.protein(&section, options.code)?;
+                protein.descriptor = name;
+                proteins.push(protein);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use std::path::Path;
+
+use binary_fasta::{errors::BinaryFastaError, records, rename::Renamer};
+
+use crate::parser::RenameArgs;
+
+pub fn run(args: &RenameArgs) -> Result<(), BinaryFastaError> {
+    let renamer = match (&args.map, &args.regex, &args.replacement, &args.template) {
+        (Some(map), _, _, _) => Renamer::read_map(Path::new(map))?,
+        (None, Some(regex), Some(replacement), _) => Renamer::Regex {
+            regex: regex.clone(),
+            replacement: replacement.clone(),
+        },
+        (None, None, _, Some(template)) => Renamer::Template(template.clone()),
+        // Ruled out by the argument parser.
+        _ => unreachable!("rename needs --map, --regex with --replace or --template"),
+    };
+
+    let sections = records::read(Path::new(&args.input))?.map(move |res| {
+        res.and_then(|mut section| {
+            renamer.rename(&mut section)?;
+            Ok(section)
+        })
+    });
+
+    let output = args.output.output.as_deref().map(Path::new);
+    records::write(sections, output, args.output.force)
+}
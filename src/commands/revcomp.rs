@@ -0,0 +1,17 @@
+use std::path::Path;
+
+use binary_fasta::{errors::BinaryFastaError, records, region};
+
+use crate::parser::RevcompArgs;
+
+pub fn run(args: &RevcompArgs) -> Result<(), BinaryFastaError> {
+    let output = args.output.output.as_deref().map(Path::new);
+
+    let mut sections = records::read(Path::new(&args.input))?;
+    if !args.regions.is_empty() {
+        sections = Box::new(region::extract(sections, args.regions.clone()));
+    }
+
+    let reversed = sections.map(|res| res.and_then(|section| section.reverse_complement()));
+    records::write(reversed, output, args.output.force)
+}
@@ -0,0 +1,43 @@
+use std::{collections::HashSet, path::Path};
+
+use binary_fasta::{
+    basta::binary_fasta_data,
+    errors::BinaryFastaError,
+    records::{self, Sections},
+    sample::{self, Rng},
+};
+
+use crate::parser::SampleArgs;
+
+// Sampled sections keep their order in the input.
+pub fn run(args: &SampleArgs) -> Result<(), BinaryFastaError> {
+    let input = Path::new(&args.input);
+    let mut rng = args.seed.map_or_else(Rng::from_time, Rng::new);
+
+    let sections: Sections = match (args.count, args.fraction) {
+        (_, Some(fraction)) => records::read_selected(input, move |_| rng.next_f64() < fraction)?,
+        (Some(count), None) if records::is_basta(input)? => {
+            // Sample from the section headers, then only read the sampled sections.
+            let (_, entries) = binary_fasta_data::list_sections(input)?;
+            let sampled: HashSet<usize> = sample::reservoir(entries, count, &mut rng)?
+                .into_iter()
+                .map(|(index, _)| index)
+                .collect();
+
+            let mut index = 0;
+            records::read_selected(input, move |_| {
+                index += 1;
+                sampled.contains(&(index - 1))
+            })?
+        }
+        (Some(count), None) => {
+            let sampled = sample::reservoir(records::read(input)?, count, &mut rng)?;
+            Box::new(sampled.into_iter().map(|(_, section)| Ok(section)))
+        }
+        // clap requires either a count or a fraction.
+        (None, None) => unreachable!(),
+    };
+
+    let output = args.output.output.as_deref().map(Path::new);
+    records::write(sections, output, args.output.force)
+}
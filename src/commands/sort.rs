@@ -0,0 +1,34 @@
+use std::{env, path::Path};
+
+use binary_fasta::{
+    errors::BinaryFastaError,
+    records,
+    sort::{self, Order},
+};
+
+use crate::parser::SortArgs;
+
+pub fn run(args: &SortArgs) -> Result<(), BinaryFastaError> {
+    let input = Path::new(&args.input);
+    let order = Order {
+        key: args.by,
+        reverse: args.reverse,
+    };
+
+    // BASTA files sorted by name or length only keep their section headers in memory.
+    let sections = if order.uses_headers_only() && records::is_basta(input)? {
+        sort::sort_basta_by_headers(input, order)?
+    } else if let Some(max_memory) = args.max_memory {
+        let temp_dir = args
+            .temp_dir
+            .as_ref()
+            .map_or_else(env::temp_dir, |dir| dir.into());
+        sort::sort_external(records::read(input)?, order, max_memory, &temp_dir)?
+    } else {
+        let sorted = sort::sort_in_memory(records::read(input)?, order)?;
+        Box::new(sorted.into_iter().map(Ok))
+    };
+
+    let output = args.output.output.as_deref().map(Path::new);
+    records::write(sections, output, args.output.force)
+}
@@ -0,0 +1,44 @@
+use std::{collections::HashSet, path::Path};
+
+use binary_fasta::{
+    errors::BinaryFastaError,
+    records,
+    split::{self, SplitBy, SplitFormat},
+};
+
+use crate::parser::SplitArgs;
+
+// Prints the path of every file written, so they can be passed on to other jobs.
+pub fn run(args: &SplitArgs) -> Result<(), BinaryFastaError> {
+    let input = Path::new(&args.input);
+    let by = match (args.sequences, args.bases) {
+        (Some(count), _) => SplitBy::Count(count as usize),
+        (_, Some(bases)) => SplitBy::Bases(bases as usize),
+        (None, None) => SplitBy::Sequence,
+    };
+    let format = match args.format {
+        Some(format) => format,
+        None if records::is_basta(input)? => SplitFormat::Basta,
+        None => SplitFormat::Fasta,
+    };
+    let prefix = match &args.prefix {
+        Some(prefix) => prefix.clone(),
+        None => input.file_stem().map_or(String::from("split"), |stem| {
+            stem.to_string_lossy().into_owned()
+        }),
+    };
+
+    let mut used_names: HashSet<String> = HashSet::new();
+    for (i, chunk) in split::chunks(records::read(input)?, by).enumerate() {
+        let chunk = chunk?;
+        let name = match by {
+            SplitBy::Sequence => split::file_name(chunk[0].id(), &mut used_names),
+            SplitBy::Count(_) | SplitBy::Bases(_) => format!("{}.{:03}", prefix, i + 1),
+        };
+        let path = Path::new(&args.output_dir).join(format!("{}.{}", name, format.extension()));
+
+        records::write(chunk.into_iter().map(Ok), Some(&path), args.force)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use binary_fasta::{
+    errors::BinaryFastaError,
+    records,
+    subset::{self, Selection},
+};
+
+use crate::parser::SubsetArgs;
+
+pub fn run(args: &SubsetArgs) -> Result<(), BinaryFastaError> {
+    let mut ids = args.names.clone();
+    if let Some(id_list) = &args.ids {
+        ids.extend(subset::read_id_list(Path::new(id_list))?);
+    }
+    let selection = Selection::new(ids, args.regex.clone(), args.fields.clone());
+
+    let sections = records::read_selected(Path::new(&args.input), move |descriptor| {
+        selection.matches(descriptor)
+    })?;
+
+    let output = args.output.output.as_deref().map(Path::new);
+    records::write(sections, output, args.output.force)
+}
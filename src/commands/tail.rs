@@ -0,0 +1,39 @@
+use std::{collections::VecDeque, path::Path};
+
+use binary_fasta::{
+    basta::binary_fasta_data, errors::BinaryFastaError, records, records::Sections,
+};
+
+use crate::parser::HeadTailArgs;
+
+pub fn run(args: &HeadTailArgs) -> Result<(), BinaryFastaError> {
+    let input = Path::new(&args.input);
+
+    let sections: Sections = if records::is_basta(input)? {
+        // Count the sections from their headers, then only read the last ones.
+        let (_, mut entries) = binary_fasta_data::list_sections(input)?;
+        let total: usize = entries.try_fold(0, |total, entry| entry.map(|_| total + 1))?;
+        let first = total.saturating_sub(args.count);
+
+        let mut index = 0;
+        records::read_selected(input, move |_| {
+            index += 1;
+            index > first
+        })?
+    } else {
+        let mut last = VecDeque::with_capacity(args.count);
+        for res in records::read(input)? {
+            let section = res?;
+            if last.len() == args.count {
+                last.pop_front();
+            }
+            if args.count > 0 {
+                last.push_back(section);
+            }
+        }
+        Box::new(last.into_iter().map(Ok))
+    };
+
+    let output = args.output.output.as_deref().map(Path::new);
+    records::write(sections, output, args.output.force)
+}
@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use binary_fasta::{
+    errors::BinaryFastaError,
+    genetic_code::GeneticCode,
+    records,
+    translate::{self, Frame, Translation},
+};
+
+use crate::parser::TranslateArgs;
+
+pub fn run(args: &TranslateArgs) -> Result<(), BinaryFastaError> {
+    let code = GeneticCode::from_id(args.table)
+        .ok_or(BinaryFastaError::UnknownGeneticCode { id: args.table })?;
+    let translation = Translation {
+        code,
+        stop_codons: args.stop_codons,
+        start_as_met: args.start_as_met,
+    };
+
+    let frames: Vec<Frame> = if args.six_frames {
+        Frame::ALL.to_vec()
+    } else if args.frames.is_empty() {
+        vec![Frame::default()]
+    } else {
+        args.frames.clone()
+    };
+    // With a single frame, the protein keeps the descriptor of its sequence.
+    let name_frames = frames.len() > 1;
+
+    let sections = records::read(Path::new(&args.input))?;
+    let proteins = sections.flat_map(move |res| {
+        let section = match res {
+            Ok(section) => section,
+            Err(e) => return vec![Err(e)],
+        };
+        frames
+            .iter()
+            .map(|&frame| {
+                let mut protein = translate::translate_section(&section, frame, &translation)?;
+                if name_frames {
+                    protein.descriptor = translate::frame_descriptor(&section.descriptor, frame);
+                }
+                Ok(protein)
+            })
+            .collect()
+    });
+
+    let output = args.output.output.as_deref().map(Path::new);
+    records::write(proteins, output, args.output.force)
+}
@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash, Hasher, RandomState},
+};
+
+use crate::basta::binary_fasta_section::BinaryFastaSection;
+
+// Why a section was dropped.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Reason {
+    // Its sequence is the same as the kept one.
+    Sequence,
+    // Its ID is the same as the kept one (with `by_descriptor` only).
+    Descriptor,
+}
+
+impl Reason {
+    pub fn name(self) -> &'static str {
+        match self {
+            Reason::Sequence => "sequence",
+            Reason::Descriptor => "descriptor",
+        }
+    }
+}
+
+// A section that was dropped, and the descriptor of the section kept instead.
+#[derive(Debug, PartialEq)]
+pub struct Duplicate {
+    pub dropped: String,
+    pub kept: String,
+    pub reason: Reason,
+}
+
+// A 128-bit digest of the packed bytes and ambiguous runs of a sequence, so nothing is
+// decoded and only the digest is kept. The sign of the length keeps DNA and RNA apart,
+// and the molecule nucleotides and amino acids.
+//
+// The digest is two SipHash values with different random keys. Sequences are not
+// compared once their digests match: of n distinct sequences, two have the same digest
+// with a chance of at most n^2 / 2^129, less than 1 in 10^20 for a billion sequences.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+struct SequenceKey(u64, u64);
+
+impl SequenceKey {
+    fn new(section: &BinaryFastaSection, keys: &[RandomState; 2]) -> Self {
+        SequenceKey(Self::hash(section, &keys[0]), Self::hash(section, &keys[1]))
+    }
+
+    fn hash(section: &BinaryFastaSection, key: &RandomState) -> u64 {
+        let mut hasher = key.build_hasher();
+        section.sequence_length.hash(&mut hasher);
+        section.molecule.hash(&mut hasher);
+
+        // The padding bits of the last byte are not part of the sequence.
+        let bits = section.length() * section.molecule.bits_per_residue();
+        let padding = section.sequence.len() * 8 - bits;
+        match (section.sequence.split_last(), padding) {
+            (Some((last, bytes)), 1..=7) => {
+                hasher.write(bytes);
+                hasher.write_u8(last & (0xFFu8 << padding));
+            }
+            _ => hasher.write(&section.sequence),
+        }
+
+        section.ambiguous_runs.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+// Keeps the first section of every distinct sequence and, with `by_descriptor`, of every
+// sequence ID. Only the digests of the kept sequences are held in memory.
+pub struct Deduplicator {
+    by_descriptor: bool,
+    keys: [RandomState; 2],
+    sequences: HashMap<SequenceKey, String>,
+    ids: HashMap<String, String>,
+}
+
+impl Deduplicator {
+    pub fn new(by_descriptor: bool) -> Self {
+        Deduplicator {
+            by_descriptor,
+            keys: [RandomState::new(), RandomState::new()],
+            sequences: HashMap::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    // Returns the section it duplicates, or None if `section` is kept.
+    pub fn check(&mut self, section: &BinaryFastaSection) -> Option<Duplicate> {
+        let key = SequenceKey::new(section, &self.keys);
+        let duplicate = if let Some(kept) = self.sequences.get(&key) {
+            Some((kept, Reason::Sequence))
+        } else if self.by_descriptor {
+            self.ids
+                .get(section.id())
+                .map(|kept| (kept, Reason::Descriptor))
+        } else {
+            None
+        };
+
+        if let Some((kept, reason)) = duplicate {
+            return Some(Duplicate {
+                dropped: section.descriptor.clone(),
+                kept: kept.clone(),
+                reason,
+            });
+        }
+
+        if self.by_descriptor {
+            self.ids
+                .insert(section.id().to_string(), section.descriptor.clone());
+        }
+        self.sequences.insert(key, section.descriptor.clone());
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_sequences() {
+        let mut dedup = Deduplicator::new(false);
+
+        assert_eq!(
+            dedup.check(&BinaryFastaSection::from_text("a", "ACGTA")),
+            None
+        );
+        assert_eq!(
+            dedup.check(&BinaryFastaSection::from_text("b", "ACGT")),
+            None
+        );
+        assert_eq!(
+            dedup.check(&BinaryFastaSection::from_text("c copy", "acgta")),
+            Some(Duplicate {
+                dropped: String::from("c copy"),
+                kept: String::from("a"),
+                reason: Reason::Sequence,
+            })
+        );
+        // Same bases, but RNA.
+        assert_eq!(
+            dedup.check(&BinaryFastaSection::from_text("d", "ACGUA")),
+            None
+        );
+        // Same ID, different sequence.
+        assert_eq!(
+            dedup.check(&BinaryFastaSection::from_text("a", "TTTT")),
+            None
+        );
+        // N is packed as A, but is not the same base.
+        assert_eq!(
+            dedup.check(&BinaryFastaSection::from_text("e", "ACGTN")),
+            None
+        );
+        assert!(dedup
+            .check(&BinaryFastaSection::from_text("f", "acgtn"))
+            .is_some());
+    }
+
+    #[test]
+    fn test_padding_bits_are_ignored() {
+        let mut dedup = Deduplicator::new(false);
+        let original = BinaryFastaSection::from_text("a", "ACG");
+        let mut padded = BinaryFastaSection::from_text("b", "ACG");
+        padded.sequence[0] |= 0b11;
+
+        assert_eq!(dedup.check(&original), None);
+        assert!(dedup.check(&padded).is_some());
+    }
+
+    #[test]
+    fn test_by_descriptor() {
+        let mut dedup = Deduplicator::new(true);
+
+        assert_eq!(
+            dedup.check(&BinaryFastaSection::from_text("a first", "ACGT")),
+            None
+        );
+        assert_eq!(
+            dedup.check(&BinaryFastaSection::from_text("a second", "TTTT")),
+            Some(Duplicate {
+                dropped: String::from("a second"),
+                kept: String::from("a first"),
+                reason: Reason::Descriptor,
+            })
+        );
+        assert_eq!(
+            dedup
+                .check(&BinaryFastaSection::from_text("b", "ACGT"))
+                .map(|d| d.reason),
+            Some(Reason::Sequence)
+        );
+    }
+}
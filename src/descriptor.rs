@@ -0,0 +1,220 @@
+use std::sync::LazyLock;
+
+use itertools::Itertools;
+use regex::Regex;
+
+// An attribute key, either bare after whitespace ("OS=Homo sapiens") or at the start of
+// brackets ("[organism=Homo sapiens]").
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[(?P<bracketed>[A-Za-z_][\w.-]*)=|(?:^|\s)(?P<bare>[A-Za-z_][\w.-]*)=")
+        .expect("Invalid attribute regex.")
+});
+
+// NCBI database tags that are followed by an accession ("ref|NP_000549.1|").
+const NCBI_DATABASES: [&str; 5] = ["ref", "gb", "emb", "dbj", "lcl"];
+
+// The parts of a FASTA descriptor.
+#[derive(Debug, PartialEq)]
+pub struct Descriptor<'a> {
+    // Up to the first whitespace. Like samtools, sequences are looked up by it.
+    pub id: &'a str,
+    // Free text after the ID, up to the first attribute, and after bracketed attributes,
+    // joined by single spaces.
+    pub description: String,
+    // key=value pairs, in order. Bare values run until the next attribute, so they may
+    // contain spaces, as in UniProt's "OS=Homo sapiens OX=9606". Bracketed values, as
+    // in NCBI submissions, run until the closing bracket.
+    pub attributes: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Descriptor<'a> {
+    pub fn parse(descriptor: &'a str) -> Self {
+        let descriptor = descriptor.trim();
+        let id_length = descriptor
+            .find(char::is_whitespace)
+            .unwrap_or(descriptor.len());
+        let (id, rest) = descriptor.split_at(id_length);
+        let rest = rest.trim_start();
+
+        let mut attributes = Vec::new();
+        let mut text = Vec::new();
+        let mut text_start = 0;
+        let mut next = ATTRIBUTE.captures(rest);
+        while let Some(captures) = next {
+            let start = captures.get(0).expect("Whole match.");
+            text.push(rest[text_start..start.start()].trim());
+
+            let value_start = start.end();
+            if let Some(key) = captures.name("bracketed") {
+                let value_end = rest[value_start..]
+                    .find(']')
+                    .map_or(rest.len(), |i| value_start + i);
+                attributes.push((key.as_str(), rest[value_start..value_end].trim()));
+                text_start = (value_end + 1).min(rest.len());
+                next = ATTRIBUTE.captures_at(rest, text_start);
+            } else {
+                // Bare values take all the text up to the next attribute.
+                let key = captures.name("bare").expect("One of the keys matches.");
+                next = ATTRIBUTE.captures_at(rest, value_start);
+                let value_end = next
+                    .as_ref()
+                    .map_or(rest.len(), |c| c.get(0).expect("Whole match.").start());
+                attributes.push((key.as_str(), rest[value_start..value_end].trim()));
+                text_start = value_end;
+            }
+        }
+        text.push(rest[text_start..].trim());
+
+        Descriptor {
+            id,
+            description: text.into_iter().filter(|t| !t.is_empty()).join(" "),
+            attributes,
+        }
+    }
+
+    // Parts of an ID separated by '|', as in NCBI ("gi|4504347|ref|NP_000549.1|") and
+    // UniProt ("sp|P69905|HBA_HUMAN") descriptors. Other IDs are a single field.
+    pub fn id_fields(&self) -> Vec<&'a str> {
+        self.id.split('|').collect()
+    }
+
+    // The accession of NCBI and UniProt IDs.
+    pub fn accession(&self) -> Option<&'a str> {
+        let fields = self.id_fields();
+        let accession = match fields[..] {
+            ["sp" | "tr", accession, ..] => accession,
+            ["gi", _, _, accession, ..] => accession,
+            [database, accession, ..] if NCBI_DATABASES.contains(&database) => accession,
+            _ => return None,
+        };
+        Some(accession).filter(|accession| !accession.is_empty())
+    }
+
+    // The UniProt entry name, HBA_HUMAN in "sp|P69905|HBA_HUMAN".
+    pub fn entry_name(&self) -> Option<&'a str> {
+        match self.id_fields()[..] {
+            ["sp" | "tr", _, name, ..] if !name.is_empty() => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn attribute(&self, key: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| *value)
+    }
+
+    // A part of the descriptor by name: "id", "description", "accession", "entry_name",
+    // or else the key of an attribute.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        match name {
+            "id" => Some(self.id),
+            "description" => Some(self.description.as_str()).filter(|d| !d.is_empty()),
+            "accession" => self.accession(),
+            "entry_name" => self.entry_name(),
+            key => self.attribute(key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_descriptor() {
+        let descriptor = Descriptor::parse("chr1 dna:chromosome chromosome:GRCh38:1");
+        assert_eq!(descriptor.id, "chr1");
+        assert_eq!(descriptor.description, "dna:chromosome chromosome:GRCh38:1");
+        assert!(descriptor.attributes.is_empty());
+        assert_eq!(descriptor.accession(), None);
+
+        let descriptor = Descriptor::parse("chrM");
+        assert_eq!(descriptor.id, "chrM");
+        assert_eq!(descriptor.field("description"), None);
+    }
+
+    #[test]
+    fn test_uniprot_descriptor() {
+        let descriptor = Descriptor::parse(
+            "sp|P69905|HBA_HUMAN Hemoglobin subunit alpha OS=Homo sapiens OX=9606 GN=HBA1 PE=1 SV=2",
+        );
+        assert_eq!(descriptor.description, "Hemoglobin subunit alpha");
+        assert_eq!(
+            descriptor.attributes,
+            vec![
+                ("OS", "Homo sapiens"),
+                ("OX", "9606"),
+                ("GN", "HBA1"),
+                ("PE", "1"),
+                ("SV", "2")
+            ]
+        );
+        assert_eq!(descriptor.field("accession"), Some("P69905"));
+        assert_eq!(descriptor.field("entry_name"), Some("HBA_HUMAN"));
+        assert_eq!(descriptor.field("OS"), Some("Homo sapiens"));
+        assert_eq!(descriptor.field("missing"), None);
+    }
+
+    #[test]
+    fn test_ncbi_descriptors() {
+        let descriptor = Descriptor::parse("gi|4504347|ref|NP_000549.1| hemoglobin subunit alpha");
+        assert_eq!(
+            descriptor.id_fields(),
+            vec!["gi", "4504347", "ref", "NP_000549.1", ""]
+        );
+        assert_eq!(descriptor.accession(), Some("NP_000549.1"));
+        assert_eq!(descriptor.entry_name(), None);
+
+        let descriptor = Descriptor::parse(
+            "ref|NC_045512.2| Severe acute respiratory syndrome coronavirus 2 [organism=SARS-CoV-2] [topology=linear]",
+        );
+        assert_eq!(descriptor.accession(), Some("NC_045512.2"));
+        assert_eq!(
+            descriptor.description,
+            "Severe acute respiratory syndrome coronavirus 2"
+        );
+        assert_eq!(
+            descriptor.attributes,
+            vec![("organism", "SARS-CoV-2"), ("topology", "linear")]
+        );
+    }
+
+    #[test]
+    fn test_attributes() {
+        let descriptor = Descriptor::parse("contig_1 length=5120 depth=31.5x circular=true");
+        assert_eq!(descriptor.description, "");
+        assert_eq!(descriptor.field("length"), Some("5120"));
+        assert_eq!(descriptor.field("circular"), Some("true"));
+
+        // Brackets may hold '=' and spaces, and follow each other directly.
+        let descriptor = Descriptor::parse("seq1 [note=a b=c][gene=HBA1] rest");
+        assert_eq!(
+            descriptor.attributes,
+            vec![("note", "a b=c"), ("gene", "HBA1")]
+        );
+        assert_eq!(descriptor.field("b"), None);
+        assert_eq!(descriptor.description, "rest");
+    }
+
+    #[test]
+    fn test_text_around_bracketed_attributes() {
+        let descriptor = Descriptor::parse(
+            "NM_000558.5 [organism=Homo sapiens] hemoglobin subunit alpha 1 [gene=HBA1] mRNA",
+        );
+        assert_eq!(
+            descriptor.field("description"),
+            Some("hemoglobin subunit alpha 1 mRNA")
+        );
+        assert_eq!(
+            descriptor.attributes,
+            vec![("organism", "Homo sapiens"), ("gene", "HBA1")]
+        );
+
+        // Text after a bare attribute is part of its value.
+        let descriptor = Descriptor::parse("seq1 before [gene=HBA1] after length=5 kb");
+        assert_eq!(descriptor.description, "before after");
+        assert_eq!(descriptor.field("length"), Some("5 kb"));
+    }
+}
@@ -0,0 +1,129 @@
+use std::{fmt, fs::File, io::Read, path::Path};
+
+use crate::{
+    basta::binary_fasta_header::BinaryFastaHeader, bastq::binary_fastq_data,
+    errors::BinaryFastaError,
+};
+
+// Number of bytes needed to recognize any of the formats below.
+const SNIFF_LENGTH: u64 = 4;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// The UCSC .2bit signature is 0x1A412743, written in the byte order of the
+// machine that created the file.
+const TWO_BIT_MAGIC_BE: [u8; 4] = [0x1a, 0x41, 0x27, 0x43];
+const TWO_BIT_MAGIC_LE: [u8; 4] = [0x43, 0x27, 0x41, 0x1a];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DetectedFormat {
+    Fasta,
+    Fastq,
+    Gzip,
+    Basta,
+    Bastq,
+    TwoBit,
+    Empty,
+    // Anything else, including BASTA files written before the header existed.
+    Unknown,
+}
+
+impl fmt::Display for DetectedFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DetectedFormat::Fasta => "FASTA",
+            DetectedFormat::Fastq => "FASTQ",
+            DetectedFormat::Gzip => "gzip-compressed data",
+            DetectedFormat::Basta => "BASTA",
+            DetectedFormat::Bastq => "BASTQ",
+            DetectedFormat::TwoBit => "UCSC 2bit",
+            DetectedFormat::Empty => "an empty file",
+            DetectedFormat::Unknown => "an unknown format",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Identifies the format from the first bytes of a file.
+pub fn detect_bytes(start: &[u8]) -> DetectedFormat {
+    if start.is_empty() {
+        DetectedFormat::Empty
+    } else if start[0] == b'>' {
+        DetectedFormat::Fasta
+    } else if start[0] == b'@' {
+        DetectedFormat::Fastq
+    } else if start.starts_with(&GZIP_MAGIC) {
+        DetectedFormat::Gzip
+    } else if BinaryFastaHeader::is_basta(start) {
+        DetectedFormat::Basta
+    } else if binary_fastq_data::is_bastq(start) {
+        DetectedFormat::Bastq
+    } else if start.starts_with(&TWO_BIT_MAGIC_BE) || start.starts_with(&TWO_BIT_MAGIC_LE) {
+        DetectedFormat::TwoBit
+    } else {
+        DetectedFormat::Unknown
+    }
+}
+
+pub fn detect_format(file_path: &Path) -> Result<DetectedFormat, BinaryFastaError> {
+    let mut start = Vec::new();
+    File::open(file_path)?
+        .take(SNIFF_LENGTH)
+        .read_to_end(&mut start)?;
+    Ok(detect_bytes(&start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_text_formats() {
+        assert_eq!(detect_bytes(b">chr1\nACGT"), DetectedFormat::Fasta);
+        assert_eq!(
+            detect_bytes(b"@read1\nACGT\n+\nIIII"),
+            DetectedFormat::Fastq
+        );
+    }
+
+    #[test]
+    fn test_detect_binary_formats() {
+        assert_eq!(
+            detect_bytes(&[0x1f, 0x8b, 0x08, 0x00]),
+            DetectedFormat::Gzip
+        );
+        assert_eq!(detect_bytes(b"BSTA\x01\x00"), DetectedFormat::Basta);
+        assert_eq!(detect_bytes(b"BSTQ\x01\x00"), DetectedFormat::Bastq);
+        assert_eq!(
+            detect_bytes(&[0x43, 0x27, 0x41, 0x1a]),
+            DetectedFormat::TwoBit
+        );
+        assert_eq!(
+            detect_bytes(&[0x1a, 0x41, 0x27, 0x43]),
+            DetectedFormat::TwoBit
+        );
+    }
+
+    #[test]
+    fn test_detect_empty_and_unknown() {
+        assert_eq!(detect_bytes(&[]), DetectedFormat::Empty);
+        // A legacy BASTA file starts with the descriptor length.
+        assert_eq!(detect_bytes(&[6, 0, 0, 0]), DetectedFormat::Unknown);
+        assert_eq!(
+            detect_bytes(b"This is not a fasta file"),
+            DetectedFormat::Unknown
+        );
+    }
+
+    #[test]
+    fn test_detect_format_from_file() {
+        assert_eq!(
+            detect_format(Path::new("tests/data/small_dna.fasta")).unwrap(),
+            DetectedFormat::Fasta
+        );
+        assert_eq!(
+            detect_format(Path::new("tests/data/malformed.fasta")).unwrap(),
+            DetectedFormat::Unknown
+        );
+    }
+}
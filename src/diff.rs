@@ -0,0 +1,254 @@
+use itertools::Itertools;
+
+use crate::basta::binary_fasta_section::{BinaryFastaSection, Molecule, AMINO_ACIDS};
+
+// How two sections with the same ID differ. DNA and RNA with the same bases are
+// not a difference.
+#[derive(Debug, PartialEq)]
+pub enum Difference {
+    Descriptor { first: String, second: String },
+    Molecule { first: Molecule, second: Molecule },
+    Length { first: usize, second: usize },
+    // Residues are only compared between sequences of the same molecule and length.
+    Mismatches { count: usize, shown: Vec<Mismatch> },
+}
+
+// A residue that differs, at a 0-based position.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Mismatch {
+    pub position: usize,
+    pub first: char,
+    pub second: char,
+}
+
+// Lists the differences between two sections, with at most `limit` mismatches (all of
+// them if None), but counting every one.
+pub fn compare(
+    first: &BinaryFastaSection,
+    second: &BinaryFastaSection,
+    limit: Option<usize>,
+) -> Vec<Difference> {
+    let mut differences = Vec::new();
+
+    if first.descriptor != second.descriptor {
+        differences.push(Difference::Descriptor {
+            first: first.descriptor.clone(),
+            second: second.descriptor.clone(),
+        });
+    }
+    if first.molecule != second.molecule {
+        differences.push(Difference::Molecule {
+            first: first.molecule,
+            second: second.molecule,
+        });
+    } else if first.length() != second.length() {
+        differences.push(Difference::Length {
+            first: first.length(),
+            second: second.length(),
+        });
+    } else if first.sequence != second.sequence || first.ambiguous_runs != second.ambiguous_runs {
+        let mut count = 0;
+        let mut shown = Vec::new();
+        for mismatch in mismatches(first, second) {
+            if limit.is_none_or(|limit| shown.len() < limit) {
+                shown.push(mismatch);
+            }
+            count += 1;
+        }
+        // Sequences may only differ in the padding bits of their last byte.
+        if count > 0 {
+            differences.push(Difference::Mismatches { count, shown });
+        }
+    }
+    differences
+}
+
+// Compares the packed bytes, and only looks at the residues of the bytes that differ,
+// and at the ambiguous bases (which are packed as A) of either section.
+// Both sections must have the same molecule and length.
+fn mismatches<'a>(
+    first: &'a BinaryFastaSection,
+    second: &'a BinaryFastaSection,
+) -> impl Iterator<Item = Mismatch> + 'a {
+    let bits = first.molecule.bits_per_residue();
+    let length = first.length();
+    let ambiguous = |section: &'a BinaryFastaSection| {
+        section
+            .ambiguous_runs
+            .iter()
+            .flat_map(|run| run.start()..run.end())
+    };
+
+    first
+        .sequence
+        .iter()
+        .zip(&second.sequence)
+        .positions(|(a, b)| a != b)
+        // A residue may span two bytes that both differ.
+        .flat_map(move |byte| (8 * byte / bits)..=((8 * byte + 7) / bits))
+        .merge(ambiguous(first).merge(ambiguous(second)))
+        .dedup()
+        .filter(move |&position| position < length)
+        .filter_map(move |position| {
+            let (a, b) = (residue(first, position), residue(second, position));
+            (a != b).then_some(Mismatch {
+                position,
+                first: a,
+                second: b,
+            })
+        })
+}
+
+fn residue_code(section: &BinaryFastaSection, position: usize) -> u8 {
+    let bits = section.molecule.bits_per_residue();
+    (position * bits..(position + 1) * bits).fold(0, |code, bit| {
+        (code << 1) | ((section.sequence[bit / 8] >> (7 - bit % 8)) & 1)
+    })
+}
+
+fn residue(section: &BinaryFastaSection, position: usize) -> char {
+    if let Some(base) = section.ambiguous_base(position) {
+        return base as char;
+    }
+    let code = residue_code(section, position);
+    match section.molecule {
+        // RNA sequences have a negative length.
+        Molecule::Nucleotide if code == 0b11 && section.sequence_length < 0 => 'U',
+        Molecule::Nucleotide => b"ACGT"[code as usize] as char,
+        Molecule::Protein => AMINO_ACIDS[code as usize] as char,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mismatch(position: usize, first: char, second: char) -> Mismatch {
+        Mismatch {
+            position,
+            first,
+            second,
+        }
+    }
+
+    #[test]
+    fn test_identical() {
+        let first = BinaryFastaSection::from_text("chr1", "ACGTACGTA");
+        assert!(compare(
+            &first,
+            &BinaryFastaSection::from_text("chr1", "acgtacgta"),
+            None
+        )
+        .is_empty());
+        // Same bases as RNA.
+        assert!(compare(
+            &first,
+            &BinaryFastaSection::from_text("chr1", "ACGUACGUA"),
+            None
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_nucleotide_mismatches() {
+        let first = BinaryFastaSection::from_text("chr1", "ACGTACGTA");
+        let second = BinaryFastaSection::from_text("chr1", "ACGAACGTC");
+
+        assert_eq!(
+            compare(&first, &second, None),
+            vec![Difference::Mismatches {
+                count: 2,
+                shown: vec![mismatch(3, 'T', 'A'), mismatch(8, 'A', 'C')]
+            }]
+        );
+        assert_eq!(
+            compare(&first, &second, Some(1)),
+            vec![Difference::Mismatches {
+                count: 2,
+                shown: vec![mismatch(3, 'T', 'A')]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_mismatches() {
+        let first = BinaryFastaSection::from_text("chr1", "ACNNACGTA");
+        let second = BinaryFastaSection::from_text("chr1", "ANNAACGTR");
+
+        assert_eq!(
+            compare(&first, &second, None),
+            vec![Difference::Mismatches {
+                count: 3,
+                shown: vec![
+                    mismatch(1, 'C', 'N'),
+                    mismatch(3, 'N', 'A'),
+                    mismatch(8, 'A', 'R')
+                ]
+            }]
+        );
+        assert!(compare(
+            &first,
+            &BinaryFastaSection::from_text("chr1", "acnnacgta"),
+            None
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_protein_mismatches() {
+        // Residues 1 and 2 span the first and second bytes.
+        let first = BinaryFastaSection::from_text("p", "MKVLSE");
+        let second = BinaryFastaSection::from_text("p", "MRWLSQ");
+
+        assert_eq!(
+            compare(&first, &second, None),
+            vec![Difference::Mismatches {
+                count: 3,
+                shown: vec![
+                    mismatch(1, 'K', 'R'),
+                    mismatch(2, 'V', 'W'),
+                    mismatch(5, 'E', 'Q')
+                ]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_other_differences() {
+        let first = BinaryFastaSection::from_text("chr1 old", "ACGT");
+
+        assert_eq!(
+            compare(
+                &first,
+                &BinaryFastaSection::from_text("chr1 new", "ACGTA"),
+                None
+            ),
+            vec![
+                Difference::Descriptor {
+                    first: String::from("chr1 old"),
+                    second: String::from("chr1 new")
+                },
+                Difference::Length {
+                    first: 4,
+                    second: 5
+                }
+            ]
+        );
+        assert_eq!(
+            compare(
+                &first,
+                &BinaryFastaSection::from_text("chr1 old", "PEPE"),
+                None
+            ),
+            vec![Difference::Molecule {
+                first: Molecule::Nucleotide,
+                second: Molecule::Protein
+            }]
+        );
+
+        let mut padded = BinaryFastaSection::from_text("chr1 old", "ACG");
+        let unpadded = BinaryFastaSection::from_text("chr1 old", "ACG");
+        padded.sequence[0] |= 0b11;
+        assert!(compare(&unpadded, &padded, None).is_empty());
+    }
+}
@@ -3,13 +3,144 @@ use std::fmt;
 use std::io;
 use std::path::PathBuf;
 
+use crate::{
+    basta::metadata::MAX_METADATA_LENGTH,
+    detect::DetectedFormat,
+    genetic_code::GeneticCode,
+    kmer::MAX_K,
+    motif::MAX_PATTERN_LENGTH,
+    nucleotide_file::{Direction, FileFormat},
+};
+
 #[derive(Debug)]
 pub enum BinaryFastaError {
     UnexpectedEof,
     InvalidUtf8Descriptor,
+    DescriptorTooLong {
+        descriptor: String,
+    },
     Io(io::Error), // Wraps general IO errors
-    InvalidFileExtension { path: PathBuf },
-    MalformedFastaHeader { path: PathBuf },
+    InvalidFileExtension {
+        path: PathBuf,
+    },
+    MalformedFastaHeader {
+        path: PathBuf,
+    },
+    OutputWithMultipleInputs {
+        inputs: usize,
+    },
+    OutputCollision {
+        first: PathBuf,
+        second: PathBuf,
+        output: PathBuf,
+    },
+    BatchFailed {
+        failed: usize,
+        total: usize,
+    },
+    OutputExists {
+        path: PathBuf,
+    },
+    UnsupportedVersion {
+        version: u8,
+    },
+    MalformedFastqRecord {
+        path: PathBuf,
+        line: usize,
+    },
+    MalformedBastqHeader {
+        path: PathBuf,
+    },
+    InvalidBastqRead {
+        name: String,
+    },
+    MalformedTwoBitHeader {
+        path: PathBuf,
+    },
+    InvalidTwoBitBlocks {
+        name: String,
+    },
+    TwoBitNameTooLong {
+        name: String,
+    },
+    InvalidSequenceCharacter {
+        character: char,
+    },
+    UnknownMolecule {
+        value: u8,
+    },
+    NotNucleotide {
+        name: String,
+    },
+    UnsupportedInput {
+        path: PathBuf,
+        format: FileFormat,
+    },
+    UnsupportedOutputExtension {
+        path: PathBuf,
+    },
+    NotBasta {
+        path: PathBuf,
+        format: FileFormat,
+    },
+    InvalidRegion {
+        region: String,
+    },
+    SequenceNotFound {
+        name: String,
+    },
+    DuplicateSequence {
+        name: String,
+    },
+    InvalidRenameMap {
+        path: PathBuf,
+        line: usize,
+    },
+    MissingDescriptorField {
+        field: String,
+        descriptor: String,
+    },
+    InvalidMetadata {
+        tag: u8,
+    },
+    InvalidAmbiguousRuns {
+        name: String,
+    },
+    InvalidSectionBlocks {
+        name: String,
+    },
+    InvalidMetadataValue {
+        key: String,
+        value: String,
+    },
+    MetadataTooLarge {
+        length: usize,
+    },
+    InvalidMetadataTable {
+        path: PathBuf,
+        line: usize,
+    },
+    UnknownGeneticCode {
+        id: u8,
+    },
+    InvalidCodon {
+        codon: String,
+    },
+    InvalidKmerSize {
+        k: usize,
+    },
+    InvalidPattern {
+        pattern: String,
+    },
+    UnsupportedFormat {
+        path: PathBuf,
+        found: DetectedFormat,
+    },
+    UnexpectedFormat {
+        path: PathBuf,
+        direction: Direction,
+        found: DetectedFormat,
+    },
 }
 
 impl fmt::Display for BinaryFastaError {
@@ -19,10 +150,17 @@ impl fmt::Display for BinaryFastaError {
             BinaryFastaError::InvalidUtf8Descriptor => {
                 write!(f, "descriptor contains invalid UTF-8.")
             }
+            BinaryFastaError::DescriptorTooLong { descriptor } => write!(
+                f,
+                "descriptor '{}' is {} bytes long, but BASTA sections hold at most {}.",
+                descriptor,
+                descriptor.len(),
+                u8::MAX
+            ),
             BinaryFastaError::Io(e) => write!(f, "I/O error: {}", e),
             BinaryFastaError::InvalidFileExtension { path } => write!(
                 f,
-                "expected a FASTA (.fa/.fasta) or BASTA (.ba/.basta) file, but found '{}'.",
+                "could not tell whether '{}' is a FASTA or BASTA file. Use the encode or decode subcommand.",
                 path.display()
             ),
             BinaryFastaError::MalformedFastaHeader { path } => {
@@ -32,6 +170,189 @@ impl fmt::Display for BinaryFastaError {
                     path.display()
                 )
             }
+            BinaryFastaError::OutputWithMultipleInputs { inputs } => write!(
+                f,
+                "--output can only be used with a single input file, but {} were given. Use --output-dir instead.",
+                inputs
+            ),
+            BinaryFastaError::OutputCollision {
+                first,
+                second,
+                output,
+            } => write!(
+                f,
+                "'{}' and '{}' would both be converted to '{}'. Convert them into different output directories.",
+                first.display(),
+                second.display(),
+                output.display()
+            ),
+            BinaryFastaError::BatchFailed { failed, total } => {
+                write!(f, "{} of {} files failed to convert.", failed, total)
+            }
+            BinaryFastaError::OutputExists { path } => write!(
+                f,
+                "'{}' already exists. Use --force to overwrite it.",
+                path.display()
+            ),
+            BinaryFastaError::UnsupportedVersion { version } => write!(
+                f,
+                "BASTA format version {} is newer than this program supports. Please upgrade.",
+                version
+            ),
+            BinaryFastaError::MalformedFastqRecord { path, line } => write!(
+                f,
+                "'{}' is not a valid FASTQ file. The record ending at line {} is malformed.",
+                path.display(),
+                line
+            ),
+            BinaryFastaError::MalformedBastqHeader { path } => write!(
+                f,
+                "BASTQ file must start with the 'BSTQ' header. '{}' has a malformed header.",
+                path.display()
+            ),
+            BinaryFastaError::InvalidBastqRead { name } => write!(
+                f,
+                "the N runs or quality of read '{}' do not match its sequence.",
+                name
+            ),
+            BinaryFastaError::MalformedTwoBitHeader { path } => write!(
+                f,
+                "'{}' does not start with the UCSC 2bit signature.",
+                path.display()
+            ),
+            BinaryFastaError::InvalidTwoBitBlocks { name } => write!(
+                f,
+                "the N or mask blocks of '{}' are out of order or outside the sequence.",
+                name
+            ),
+            BinaryFastaError::TwoBitNameTooLong { name } => write!(
+                f,
+                "sequence name '{}' is {} bytes long, but 2bit names hold at most {}.",
+                name,
+                name.len(),
+                u8::MAX
+            ),
+            BinaryFastaError::InvalidSequenceCharacter { character } => {
+                write!(f, "invalid sequence character '{}'.", character)
+            }
+            BinaryFastaError::UnknownMolecule { value } => {
+                write!(f, "unknown molecule type {} in BASTA section.", value)
+            }
+            BinaryFastaError::NotNucleotide { name } => write!(
+                f,
+                "'{}' is a protein sequence, but a nucleotide sequence is required.",
+                name
+            ),
+            BinaryFastaError::UnsupportedInput { path, format } => write!(
+                f,
+                "'{}' is a {} file, but only FASTA, BASTA and 2bit files can be read here.",
+                path.display(),
+                format
+            ),
+            BinaryFastaError::NotBasta { path, format } => write!(
+                f,
+                "'{}' is a {} file, but only BASTA files can be listed.",
+                path.display(),
+                format
+            ),
+            BinaryFastaError::UnsupportedOutputExtension { path } => write!(
+                f,
+                "cannot tell the output format of '{}' from its extension. Use a FASTA, BASTA or 2bit extension.",
+                path.display()
+            ),
+            BinaryFastaError::InvalidRegion { region } => write!(
+                f,
+                "invalid region '{}'. Expected NAME, or NAME:START-END with 1-based inclusive coordinates.",
+                region
+            ),
+            BinaryFastaError::SequenceNotFound { name } => {
+                write!(f, "no sequence named '{}' was found.", name)
+            }
+            BinaryFastaError::MissingDescriptorField { field, descriptor } => write!(
+                f,
+                "descriptor '{}' has no field '{}'.",
+                descriptor, field
+            ),
+            BinaryFastaError::InvalidRenameMap { path, line } => write!(
+                f,
+                "line {} of '{}' is not an old and a new name separated by whitespace.",
+                line,
+                path.display()
+            ),
+            BinaryFastaError::DuplicateSequence { name } => write!(
+                f,
+                "more than one sequence is named '{}'.",
+                name
+            ),
+            BinaryFastaError::InvalidMetadata { tag } => {
+                write!(f, "metadata entry with tag {} is malformed.", tag)
+            }
+            BinaryFastaError::InvalidAmbiguousRuns { name } => write!(
+                f,
+                "the ambiguous base runs of '{}' are malformed or outside the sequence.",
+                name
+            ),
+            BinaryFastaError::InvalidSectionBlocks { name } => write!(
+                f,
+                "the blocks of '{}' do not fit in the length of their list.",
+                name
+            ),
+            BinaryFastaError::InvalidMetadataValue { key, value } => write!(
+                f,
+                "invalid metadata '{}={}'. taxid must be a number, topology linear or circular, and other keys a single word. Values cannot contain tabs or line breaks.",
+                key, value
+            ),
+            BinaryFastaError::MetadataTooLarge { length } => write!(
+                f,
+                "metadata of a sequence would take {} bytes, more than the {} allowed.",
+                length, MAX_METADATA_LENGTH
+            ),
+            BinaryFastaError::InvalidMetadataTable { path, line } => write!(
+                f,
+                "line {} of '{}' is not a sequence name, a key and a value separated by tabs.",
+                line,
+                path.display()
+            ),
+            BinaryFastaError::UnknownGeneticCode { id } => write!(
+                f,
+                "unknown genetic code {}. Supported NCBI tables are {}.",
+                id,
+                GeneticCode::ids().iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", ")
+            ),
+            BinaryFastaError::InvalidCodon { codon } => write!(
+                f,
+                "'{}' is not a codon. Codons are 3 of the bases A, C, G and T (or U).",
+                codon
+            ),
+            BinaryFastaError::InvalidKmerSize { k } => write!(
+                f,
+                "cannot count k-mers of length {}. k must be between 1 and {}.",
+                k,
+                MAX_K
+            ),
+            BinaryFastaError::InvalidPattern { pattern } => write!(
+                f,
+                "'{}' is not a nucleotide pattern. Patterns are 1 to {} IUPAC bases (A, C, G, T, U, R, Y, S, W, K, M, B, D, H, V, N).",
+                pattern,
+                MAX_PATTERN_LENGTH
+            ),
+            BinaryFastaError::UnsupportedFormat { path, found } => write!(
+                f,
+                "'{}' looks like {}, which cannot be converted. Expected a FASTA, FASTQ, BASTA, BASTQ or 2bit file.",
+                path.display(),
+                found
+            ),
+            BinaryFastaError::UnexpectedFormat {
+                path,
+                direction,
+                found,
+            } => write!(
+                f,
+                "cannot {} '{}', it looks like {}.",
+                direction,
+                path.display(),
+                found
+            ),
         }
     }
 }
@@ -1,12 +1,13 @@
 use std::{
     fs::File,
-    io::{self, BufRead, BufWriter, Read, Write},
+    io::{self, BufRead, Read, Write},
     path::Path,
 };
 
 use crate::{
     basta::binary_fasta_section::BinaryFastaSection, errors::BinaryFastaError,
-    fasta::fasta_section::FastaSection,
+    fasta::fasta_section::FastaSection, output_file::OutputFile,
+    twobit::two_bit_section::TwoBitSection,
 };
 
 pub fn from_basta<I>(
@@ -18,6 +19,15 @@ where
     binary_fasta_data.map(|res| res.map(FastaSection::from_basta))
 }
 
+pub fn from_two_bit<I>(
+    two_bit_data: I,
+) -> impl Iterator<Item = Result<FastaSection, BinaryFastaError>>
+where
+    I: Iterator<Item = Result<TwoBitSection, BinaryFastaError>>,
+{
+    two_bit_data.map(|res| res.map(|section| section.to_fasta()))
+}
+
 pub fn read(
     file_path: &Path,
 ) -> Result<impl Iterator<Item = Result<FastaSection, BinaryFastaError>>, BinaryFastaError> {
@@ -81,27 +91,36 @@ fn validate_fasta(file_path: &Path) -> Result<(), BinaryFastaError> {
     }
 }
 
-pub fn write<I>(iter: I, file_path: &Path) -> Result<(), BinaryFastaError>
+pub fn write<I>(iter: I, file_path: &Path, overwrite: bool) -> Result<(), BinaryFastaError>
 where
     I: Iterator<Item = Result<FastaSection, BinaryFastaError>>,
 {
-    let file = File::create(file_path)?;
-    let mut writer = BufWriter::new(file);
+    let mut writer = OutputFile::create(file_path, overwrite)?;
+    write_to(iter, &mut writer)?;
 
+    // Flush any bytes left in the buffer after the last section is written,
+    // and move the file into place.
+    writer.commit()
+}
+
+pub fn write_to<I, W>(iter: I, writer: &mut W) -> Result<(), BinaryFastaError>
+where
+    I: Iterator<Item = Result<FastaSection, BinaryFastaError>>,
+    W: Write,
+{
     for section_res in iter {
         let section = section_res?;
         let section_bytes: Vec<u8> = section.convert_to_bytes();
         writer.write_all(&section_bytes)?;
         writer.write_all(b"\n")?;
     }
-    // Flush any bytes left in the buffer after the last section is written
-    writer.flush()?;
     Ok(())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::basta::{binary_fasta_section::Molecule, metadata::Metadata};
 
     #[test]
     fn test_is_dna() {
@@ -128,11 +147,17 @@ mod test {
                 descriptor: String::from(descr1),
                 sequence: vec![0b0000_0000, 0b0101_0101, 0b1010_1010, 0b1111_1111],
                 sequence_length: -16i32, // Length is negative because sign bit signals DNA (+) or RNA (-)
+                molecule: Molecule::Nucleotide,
+                metadata: Metadata::new(),
+                ambiguous_runs: Vec::new(),
             }),
             Ok(BinaryFastaSection {
                 descriptor: String::from(descr2),
                 sequence: vec![0b0001_1011, 0b0110_0000],
                 sequence_length: -6i32, // Length is negative because sign bit signals DNA (+) or RNA (-)
+                molecule: Molecule::Nucleotide,
+                metadata: Metadata::new(),
+                ambiguous_runs: Vec::new(),
             }),
         ]
         .into_iter();
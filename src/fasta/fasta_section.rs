@@ -1,6 +1,15 @@
 use itertools::Itertools;
 
-use crate::basta::binary_fasta_section::BinaryFastaSection;
+use crate::{
+    basta::{
+        ambiguous::AMBIGUOUS_NUCLEOTIDES,
+        binary_fasta_section::{BinaryFastaSection, Molecule, AMINO_ACIDS},
+    },
+    errors::BinaryFastaError,
+};
+
+// Number of sequence characters on each line written by `convert_to_bytes`.
+pub const LINE_WIDTH: usize = 50;
 
 #[derive(Debug, PartialEq)]
 pub struct FastaSection {
@@ -21,8 +30,24 @@ impl FastaSection {
     }
 
     pub fn from_basta(basta_section: BinaryFastaSection) -> Self {
-        let char_sequence =
-            Self::translate_from_binary(&basta_section.sequence, basta_section.sequence_length);
+        let char_sequence = match basta_section.molecule {
+            Molecule::Nucleotide => {
+                let mut bases = Self::translate_from_binary(
+                    &basta_section.sequence,
+                    basta_section.sequence_length,
+                )
+                .into_bytes();
+                for run in &basta_section.ambiguous_runs {
+                    bases[run.start()..run.end()].fill(run.base);
+                }
+                // Only ASCII characters were written into the sequence.
+                String::from_utf8(bases).expect("Sequence is not ASCII.")
+            }
+            Molecule::Protein => Self::translate_protein_from_binary(
+                &basta_section.sequence,
+                basta_section.sequence_length.unsigned_abs() as usize,
+            ),
+        };
 
         FastaSection {
             descriptor: basta_section.descriptor.clone(),
@@ -43,6 +68,34 @@ impl FastaSection {
         true
     }
 
+    // A sequence of nucleotide codes (A, C, G, T, U and the IUPAC ambiguity codes) is a
+    // nucleotide sequence if at least half of it is A, C, G, T, U or N, which tells it
+    // apart from a peptide such as "MKWV". Anything else must be made of amino acid letters.
+    pub fn molecule(&self) -> Result<Molecule, BinaryFastaError> {
+        let mut only_nucleotide_codes = true;
+        let mut common_bases = 0;
+
+        for c in self.sequence.chars() {
+            let upper = c.to_ascii_uppercase();
+            if matches!(upper, 'A' | 'C' | 'G' | 'T' | 'U' | 'N') {
+                common_bases += 1;
+                continue;
+            }
+            if !upper.is_ascii() || !AMINO_ACIDS.contains(&(upper as u8)) {
+                return Err(BinaryFastaError::InvalidSequenceCharacter { character: c });
+            }
+            if !AMBIGUOUS_NUCLEOTIDES.contains(&(upper as u8)) {
+                only_nucleotide_codes = false;
+            }
+        }
+
+        if only_nucleotide_codes && 2 * common_bases >= self.sequence.len() {
+            Ok(Molecule::Nucleotide)
+        } else {
+            Ok(Molecule::Protein)
+        }
+    }
+
     pub fn translate_from_binary(bytes: &Vec<u8>, length: i32) -> String {
         let mut result = String::new();
         let mut chars_stored = 0;
@@ -78,6 +131,24 @@ impl FastaSection {
         result
     }
 
+    pub fn translate_protein_from_binary(bytes: &[u8], length: usize) -> String {
+        (0..length)
+            .map(|i| {
+                let bit_position = i * 5;
+                let byte_index = bit_position / 8;
+                let bit_offset = bit_position % 8;
+
+                // A residue may span 2 bytes, so read a 16-bit window.
+                let high = bytes[byte_index] as u16;
+                let low = bytes.get(byte_index + 1).copied().unwrap_or(0) as u16;
+                let code = (((high << 8) | low) >> (11 - bit_offset)) & 0b1_1111;
+
+                // Codes past the end of the alphabet only appear in corrupted files.
+                AMINO_ACIDS.get(code as usize).copied().unwrap_or(b'X') as char
+            })
+            .collect()
+    }
+
     fn is_bit_set(byte: &u8, bit_index: u8) -> bool {
         // Indexed from left to right, with the most-significant-bit as 0.
         if bit_index == 7 {
@@ -120,15 +191,13 @@ impl FastaSection {
         let sequence_bytes = self.sequence.clone();
         let sequence_bytes_iterator = &mut sequence_bytes.as_bytes().iter().peekable();
 
-        let chars_per_line = 50;
-
         while sequence_bytes_iterator.peek().is_some() {
-            let chunk = sequence_bytes_iterator.take(chars_per_line).collect_vec();
+            let chunk = sequence_bytes_iterator.take(LINE_WIDTH).collect_vec();
             let chunk_length = chunk.len();
             result.extend(chunk);
             // If the chunk is full of characters (and there are more characters to come)
             // add a newline character.
-            if chunk_length == chars_per_line && sequence_bytes_iterator.peek().is_some() {
+            if chunk_length == LINE_WIDTH && sequence_bytes_iterator.peek().is_some() {
                 result.push(b'\n')
             }
         }
@@ -138,6 +207,8 @@ impl FastaSection {
 
 #[cfg(test)]
 mod test {
+    use crate::basta::binary_fasta_section::Molecule;
+    use crate::errors::BinaryFastaError;
     use crate::fasta::fasta_section::FastaSection;
 
     #[test]
@@ -163,4 +234,37 @@ mod test {
             expected
         );
     }
+
+    #[test]
+    fn translate_from_binary_protein() {
+        let bytes = vec![0b0000_0000, 0b0111_0010];
+
+        assert_eq!(
+            FastaSection::translate_protein_from_binary(&bytes, 3),
+            String::from("AC*")
+        );
+    }
+
+    #[test]
+    fn detect_molecule() {
+        let molecule = |sequence: &str| {
+            FastaSection {
+                descriptor: String::from("test"),
+                sequence: String::from(sequence),
+            }
+            .molecule()
+        };
+
+        assert_eq!(molecule("").unwrap(), Molecule::Nucleotide);
+        assert_eq!(molecule("acgtACGU").unwrap(), Molecule::Nucleotide);
+        assert_eq!(molecule("MVLSPADKTNVKAAW").unwrap(), Molecule::Protein);
+        assert_eq!(molecule("mkwvtfisll*").unwrap(), Molecule::Protein);
+        assert_eq!(molecule("ACGTNRY").unwrap(), Molecule::Nucleotide);
+        assert_eq!(molecule("NNNNNNNN").unwrap(), Molecule::Nucleotide);
+        assert_eq!(molecule("MKWVDHRY").unwrap(), Molecule::Protein);
+        assert!(matches!(
+            molecule("MVL-SP"),
+            Err(BinaryFastaError::InvalidSequenceCharacter { character: '-' })
+        ));
+    }
 }
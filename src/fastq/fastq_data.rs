@@ -0,0 +1,141 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use crate::{
+    bastq::binary_fastq_section::BinaryFastqSection, errors::BinaryFastaError,
+    fastq::fastq_section::FastqSection, output_file::OutputFile,
+};
+
+pub fn from_bastq<I>(
+    binary_fastq_data: I,
+) -> impl Iterator<Item = Result<FastqSection, BinaryFastaError>>
+where
+    I: Iterator<Item = Result<BinaryFastqSection, BinaryFastaError>>,
+{
+    binary_fastq_data.map(|res| res.map(FastqSection::from_bastq))
+}
+
+// Reads the four line records of a FASTQ file (header, sequence, separator, quality).
+pub fn read(
+    file_path: &Path,
+) -> Result<impl Iterator<Item = Result<FastqSection, BinaryFastaError>>, BinaryFastaError> {
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+    let mut lines = reader.lines();
+    let path = file_path.to_path_buf();
+    let mut line_number = 0;
+
+    Ok(std::iter::from_fn(move || {
+        let mut record: Vec<String> = Vec::with_capacity(4);
+
+        while record.len() < 4 {
+            match lines.next() {
+                Some(Ok(mut line)) => {
+                    line_number += 1;
+                    // lines() strips '\n' but not '\r' from files created on PC
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                    // Tolerate blank lines between records (e.g. at the end of the file).
+                    if record.is_empty() && line.is_empty() {
+                        continue;
+                    }
+                    record.push(line);
+                }
+                Some(Err(e)) => return Some(Err(e.into())),
+                None if record.is_empty() => return None,
+                None => return Some(Err(malformed(&path, line_number))),
+            }
+        }
+
+        let (header, sequence, separator, quality) =
+            (&record[0], &record[1], &record[2], &record[3]);
+
+        if !header.starts_with('@') {
+            return Some(Err(malformed(&path, line_number - 3)));
+        }
+        if !separator.starts_with('+') {
+            return Some(Err(malformed(&path, line_number - 1)));
+        }
+        if quality.len() != sequence.len() {
+            return Some(Err(malformed(&path, line_number)));
+        }
+
+        Some(Ok(FastqSection::new(header, sequence, quality)))
+    }))
+}
+
+fn malformed(path: &Path, line: usize) -> BinaryFastaError {
+    BinaryFastaError::MalformedFastqRecord {
+        path: path.to_path_buf(),
+        line,
+    }
+}
+
+pub fn write<I>(iter: I, file_path: &Path, overwrite: bool) -> Result<(), BinaryFastaError>
+where
+    I: Iterator<Item = Result<FastqSection, BinaryFastaError>>,
+{
+    let mut writer = OutputFile::create(file_path, overwrite)?;
+
+    for section_res in iter {
+        writer.write_all(&section_res?.convert_to_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    writer.commit()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reads.fastq");
+        std::fs::write(
+            &path,
+            "@read 1\r\nACGT\r\n+read 1\r\nIIII\r\n@read 2\nNNAC\n+\n!!#I\n\n",
+        )
+        .unwrap();
+
+        let sections: Vec<_> = read(&path).unwrap().map(Result::unwrap).collect();
+        assert_eq!(
+            sections,
+            vec![
+                FastqSection::new("read 1", "ACGT", "IIII"),
+                FastqSection::new("read 2", "NNAC", "!!#I"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_truncated_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reads.fastq");
+        std::fs::write(&path, "@read 1\nACGT\n+\nIIII\n@read 2\nACGT\n").unwrap();
+
+        let results: Vec<_> = read(&path).unwrap().collect();
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1],
+            Err(BinaryFastaError::MalformedFastqRecord { line: 6, .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_quality_length_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reads.fastq");
+        std::fs::write(&path, "@read 1\nACGT\n+\nIII\n").unwrap();
+
+        let results: Vec<_> = read(&path).unwrap().collect();
+        assert!(matches!(
+            results[0],
+            Err(BinaryFastaError::MalformedFastqRecord { line: 4, .. })
+        ));
+    }
+}
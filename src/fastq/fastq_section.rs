@@ -0,0 +1,72 @@
+use crate::{bastq::binary_fastq_section::BinaryFastqSection, fasta::fasta_section::FastaSection};
+
+#[derive(Debug, PartialEq)]
+pub struct FastqSection {
+    pub descriptor: String,
+    pub sequence: String,
+    // One Phred+33 encoded character per base.
+    pub quality: String,
+}
+
+impl FastqSection {
+    pub fn new(descriptor: &str, sequence: &str, quality: &str) -> FastqSection {
+        FastqSection {
+            descriptor: descriptor.trim_start_matches('@').to_string(),
+            sequence: sequence.to_string(),
+            quality: quality.to_string(),
+        }
+    }
+
+    pub fn from_bastq(bastq_section: BinaryFastqSection) -> Self {
+        // N bases are stored as A in the packed sequence, so put them back.
+        let mut sequence = FastaSection::translate_from_binary(
+            &bastq_section.bases.sequence,
+            bastq_section.bases.sequence_length,
+        )
+        .into_bytes();
+        for &(start, length) in &bastq_section.n_runs {
+            let start = start as usize;
+            sequence[start..start + length as usize].fill(b'N');
+        }
+
+        FastqSection {
+            descriptor: bastq_section.bases.descriptor,
+            // Only ASCII characters were written into the sequence.
+            sequence: String::from_utf8(sequence).expect("Sequence is not ASCII."),
+            // Qualities are checked to be printable ASCII when they are read.
+            quality: String::from_utf8(bastq_section.quality).expect("Quality is not ASCII."),
+        }
+    }
+
+    pub fn convert_to_bytes(&self) -> Vec<u8> {
+        let mut result: Vec<u8> = Vec::new();
+        result.push(b'@');
+        result.extend(self.descriptor.as_bytes());
+        result.push(b'\n');
+        result.extend(self.sequence.as_bytes());
+        // The descriptor is not repeated on the separator line, which is optional.
+        result.extend(b"\n+\n");
+        result.extend(self.quality.as_bytes());
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_new_trims_at_sign() {
+        let section = FastqSection::new("@read 1", "ACGT", "IIII");
+        assert_eq!(section.descriptor, "read 1");
+    }
+
+    #[test]
+    fn test_convert_to_bytes() {
+        let section = FastqSection::new("@read 1", "ACGN", "II#!");
+        assert_eq!(
+            section.convert_to_bytes(),
+            b"@read 1\nACGN\n+\nII#!".to_vec()
+        );
+    }
+}
@@ -0,0 +1,2 @@
+pub mod fastq_data;
+pub mod fastq_section;
@@ -0,0 +1,303 @@
+// NCBI genetic code tables (https://www.ncbi.nlm.nih.gov/Taxonomy/Utils/wprintgc.cgi).
+// They are written below in the NCBI order, where the bases of a codon run through
+// T, C, A, G, first base slowest. Lookups use the BASTA order instead, so a codon's
+// index is its 3 packed 2-bit codes read as a single 6-bit number.
+//
+// Tables 27, 28 and 31 are left out: some of their codons are a stop or an amino acid
+// depending on where they are in the gene, which a codon table cannot express.
+
+pub const DEFAULT_TABLE: u8 = 1;
+
+#[derive(Debug, PartialEq)]
+pub struct GeneticCode {
+    pub id: u8,
+    pub name: &'static str,
+    // Amino acid (or '*' for a stop) of every codon, by BASTA codon index.
+    amino_acids: [u8; 64],
+    // Whether every codon can start translation, by BASTA codon index.
+    starts: [bool; 64],
+}
+
+// Position in the NCBI order of the base with the given BASTA code (A, C, G, T).
+const NCBI_POSITION: [usize; 4] = [2, 1, 3, 0];
+
+const fn ncbi_index(codon: usize) -> usize {
+    16 * NCBI_POSITION[codon >> 4]
+        + 4 * NCBI_POSITION[(codon >> 2) & 0b11]
+        + NCBI_POSITION[codon & 0b11]
+}
+
+const fn genetic_code(
+    id: u8,
+    name: &'static str,
+    amino_acids: &[u8; 64],
+    starts: &[u8; 64],
+) -> GeneticCode {
+    let mut code = GeneticCode {
+        id,
+        name,
+        amino_acids: [0; 64],
+        starts: [false; 64],
+    };
+    let mut codon = 0;
+    while codon < 64 {
+        code.amino_acids[codon] = amino_acids[ncbi_index(codon)];
+        code.starts[codon] = starts[ncbi_index(codon)] == b'M';
+        codon += 1;
+    }
+    code
+}
+
+pub const GENETIC_CODES: [GeneticCode; 23] = [
+    genetic_code(
+        1,
+        "Standard",
+        b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"---M---------------M---------------M----------------------------",
+    ),
+    genetic_code(
+        2,
+        "Vertebrate Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        b"--------------------------------MMMM---------------M------------",
+    ),
+    genetic_code(
+        3,
+        "Yeast Mitochondrial",
+        b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------------------------------MM---------------M------------",
+    ),
+    genetic_code(
+        4,
+        "Mold, Protozoan, and Coelenterate Mitochondrial and Mycoplasma/Spiroplasma",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--MM---------------M------------MMMM---------------M------------",
+    ),
+    genetic_code(
+        5,
+        "Invertebrate Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        b"---M----------------------------MMMM---------------M------------",
+    ),
+    genetic_code(
+        6,
+        "Ciliate, Dasycladacean and Hexamita Nuclear",
+        b"FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"-----------------------------------M----------------------------",
+    ),
+    genetic_code(
+        9,
+        "Echinoderm and Flatworm Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        b"-----------------------------------M---------------M------------",
+    ),
+    genetic_code(
+        10,
+        "Euplotid Nuclear",
+        b"FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"-----------------------------------M----------------------------",
+    ),
+    genetic_code(
+        11,
+        "Bacterial, Archaeal and Plant Plastid",
+        b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"---M---------------M------------MMMM---------------M------------",
+    ),
+    genetic_code(
+        12,
+        "Alternative Yeast Nuclear",
+        b"FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"-------------------M---------------M----------------------------",
+    ),
+    genetic_code(
+        13,
+        "Ascidian Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+        b"---M------------------------------MM---------------M------------",
+    ),
+    genetic_code(
+        14,
+        "Alternative Flatworm Mitochondrial",
+        b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        b"-----------------------------------M----------------------------",
+    ),
+    genetic_code(
+        16,
+        "Chlorophycean Mitochondrial",
+        b"FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"-----------------------------------M----------------------------",
+    ),
+    genetic_code(
+        21,
+        "Trematode Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        b"-----------------------------------M---------------M------------",
+    ),
+    genetic_code(
+        22,
+        "Scenedesmus obliquus Mitochondrial",
+        b"FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"-----------------------------------M----------------------------",
+    ),
+    genetic_code(
+        23,
+        "Thraustochytrium Mitochondrial",
+        b"FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--------------------------------M--M---------------M------------",
+    ),
+    genetic_code(
+        24,
+        "Rhabdopleuridae Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        b"---M---------------M---------------M---------------M------------",
+    ),
+    genetic_code(
+        25,
+        "Candidate Division SR1 and Gracilibacteria",
+        b"FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"---M-------------------------------M---------------M------------",
+    ),
+    genetic_code(
+        26,
+        "Pachysolen tannophilus Nuclear",
+        b"FFLLSSSSYY**CC*WLLLAPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"-------------------M---------------M----------------------------",
+    ),
+    genetic_code(
+        29,
+        "Mesodinium Nuclear",
+        b"FFLLSSSSYYYYCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"-----------------------------------M----------------------------",
+    ),
+    genetic_code(
+        30,
+        "Peritrich Nuclear",
+        b"FFLLSSSSYYEECC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"-----------------------------------M----------------------------",
+    ),
+    genetic_code(
+        32,
+        "Balanophoraceae Plastid",
+        b"FFLLSSSSYY*WCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"---M---------------M------------MMMM---------------M------------",
+    ),
+    genetic_code(
+        33,
+        "Cephalodiscidae Mitochondrial",
+        b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        b"---M---------------M---------------M---------------M------------",
+    ),
+];
+
+impl GeneticCode {
+    pub fn from_id(id: u8) -> Option<&'static GeneticCode> {
+        GENETIC_CODES.iter().find(|code| code.id == id)
+    }
+
+    pub fn ids() -> Vec<u8> {
+        GENETIC_CODES.iter().map(|code| code.id).collect()
+    }
+
+    // `codon` is a 6-bit BASTA codon index.
+    pub fn amino_acid(&self, codon: u8) -> u8 {
+        self.amino_acids[codon as usize]
+    }
+
+    pub fn is_start(&self, codon: u8) -> bool {
+        self.starts[codon as usize]
+    }
+
+    pub fn is_stop(&self, codon: u8) -> bool {
+        self.amino_acid(codon) == b'*'
+    }
+
+    // 6-bit index of a codon written as text, or None if it is not made of A, C, G, T and U.
+    pub fn codon_index(codon: &[u8]) -> Option<u8> {
+        codon.iter().try_fold(0u8, |index, base| {
+            let code = match base.to_ascii_uppercase() {
+                b'A' => 0b00,
+                b'C' => 0b01,
+                b'G' => 0b10,
+                b'T' | b'U' => 0b11,
+                _ => return None,
+            };
+            Some((index << 2) | code)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codon(text: &str) -> u8 {
+        GeneticCode::codon_index(text.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_codon_index() {
+        assert_eq!(codon("AAA"), 0);
+        assert_eq!(codon("ACG"), 0b00_01_10);
+        assert_eq!(codon("uuu"), 63);
+        assert_eq!(GeneticCode::codon_index(b"ANG"), None);
+    }
+
+    #[test]
+    fn test_standard_code() {
+        let standard = GeneticCode::from_id(DEFAULT_TABLE).unwrap();
+
+        assert_eq!(standard.amino_acid(codon("ATG")), b'M');
+        assert_eq!(standard.amino_acid(codon("TGG")), b'W');
+        assert_eq!(standard.amino_acid(codon("GCC")), b'A');
+        assert_eq!(standard.amino_acid(codon("AGA")), b'R');
+        for stop in ["TAA", "TAG", "TGA"] {
+            assert!(standard.is_stop(codon(stop)));
+        }
+        for start in ["ATG", "CTG", "TTG"] {
+            assert!(standard.is_start(codon(start)));
+        }
+        assert!(!standard.is_start(codon("GTG")));
+    }
+
+    #[test]
+    fn test_alternative_codes() {
+        let mitochondrial = GeneticCode::from_id(2).unwrap();
+        assert_eq!(mitochondrial.amino_acid(codon("TGA")), b'W');
+        assert_eq!(mitochondrial.amino_acid(codon("ATA")), b'M');
+        assert!(mitochondrial.is_stop(codon("AGG")));
+
+        let bacterial = GeneticCode::from_id(11).unwrap();
+        assert!(bacterial.is_start(codon("GTG")));
+
+        assert_eq!(GeneticCode::from_id(7), None);
+        assert_eq!(GeneticCode::from_id(27), None);
+    }
+
+    #[test]
+    fn test_nuclear_codes_with_reassigned_stops() {
+        let mesodinium = GeneticCode::from_id(29).unwrap();
+        assert_eq!(mesodinium.amino_acid(codon("TAA")), b'Y');
+        assert_eq!(mesodinium.amino_acid(codon("TAG")), b'Y');
+        assert!(mesodinium.is_stop(codon("TGA")));
+
+        let peritrich = GeneticCode::from_id(30).unwrap();
+        assert_eq!(peritrich.amino_acid(codon("TAA")), b'E');
+
+        let balanophoraceae = GeneticCode::from_id(32).unwrap();
+        assert_eq!(balanophoraceae.amino_acid(codon("TAG")), b'W');
+        assert!(balanophoraceae.is_stop(codon("TAA")));
+
+        let cephalodiscidae = GeneticCode::from_id(33).unwrap();
+        assert_eq!(cephalodiscidae.amino_acid(codon("TAA")), b'Y');
+        assert_eq!(cephalodiscidae.amino_acid(codon("AGG")), b'K');
+        assert!(cephalodiscidae.is_stop(codon("TAG")));
+    }
+
+    #[test]
+    fn test_every_code_has_stops_and_starts() {
+        for code in GENETIC_CODES.iter() {
+            assert!((0..64).any(|codon| code.is_stop(codon)), "{}", code.name);
+            assert!(code.is_start(codon("ATG")), "{}", code.name);
+        }
+    }
+}
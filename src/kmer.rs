@@ -0,0 +1,157 @@
+use crate::{
+    basta::binary_fasta_section::{BinaryFastaSection, Molecule},
+    errors::BinaryFastaError,
+};
+
+// A k-mer is stored in the low 2k bits of a u64, with the BASTA code of its first
+// base in the most significant position. Codes therefore sort like the k-mers.
+pub const MAX_K: usize = 32;
+
+const BASES: [char; 4] = ['A', 'C', 'G', 'T'];
+
+// Canonical k-mers of a section: the smaller of every k-mer and its reverse
+// complement, so a k-mer and its reverse complement are counted together.
+// K-mers with an ambiguous base, such as N, are skipped.
+pub struct CanonicalKmers<'a> {
+    section: &'a BinaryFastaSection,
+    k: usize,
+    mask: u64,
+    position: usize,
+    // Position of the first base after the last ambiguous one.
+    valid_from: usize,
+    // Index of the next ambiguous run.
+    run: usize,
+    forward: u64,
+    reverse: u64,
+}
+
+impl<'a> CanonicalKmers<'a> {
+    pub fn new(section: &'a BinaryFastaSection, k: usize) -> Result<Self, BinaryFastaError> {
+        if k == 0 || k > MAX_K {
+            return Err(BinaryFastaError::InvalidKmerSize { k });
+        }
+        if section.molecule == Molecule::Protein {
+            return Err(BinaryFastaError::NotNucleotide {
+                name: section.descriptor.clone(),
+            });
+        }
+
+        Ok(CanonicalKmers {
+            section,
+            k,
+            mask: if k == MAX_K {
+                u64::MAX
+            } else {
+                (1 << (2 * k)) - 1
+            },
+            position: 0,
+            valid_from: 0,
+            run: 0,
+            forward: 0,
+            reverse: 0,
+        })
+    }
+}
+
+impl Iterator for CanonicalKmers<'_> {
+    type Item = u64;
+
+    // Both strands are rolled one base at a time: the next base is shifted into the
+    // forward k-mer from the right, and its complement into the reverse one from the left.
+    fn next(&mut self) -> Option<u64> {
+        while self.position < self.section.length() {
+            // Ambiguous runs are jumped over, and the k-mers start again after them.
+            if let Some(run) = self.section.ambiguous_runs.get(self.run) {
+                if run.start() == self.position {
+                    self.position = run.end();
+                    self.valid_from = run.end();
+                    self.run += 1;
+                    continue;
+                }
+            }
+
+            let code = self.section.nucleotide_code(self.position) as u64;
+            self.forward = ((self.forward << 2) | code) & self.mask;
+            self.reverse = (self.reverse >> 2) | ((0b11 ^ code) << (2 * (self.k - 1)));
+            self.position += 1;
+
+            if self.position >= self.valid_from + self.k {
+                return Some(self.forward.min(self.reverse));
+            }
+        }
+        None
+    }
+}
+
+pub fn kmer_to_string(kmer: u64, k: usize) -> String {
+    (0..k)
+        .rev()
+        .map(|i| BASES[((kmer >> (2 * i)) & 0b11) as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kmers(sequence: &str, k: usize) -> Vec<String> {
+        CanonicalKmers::new(&BinaryFastaSection::from_text("test", sequence), k)
+            .unwrap()
+            .map(|kmer| kmer_to_string(kmer, k))
+            .collect()
+    }
+
+    #[test]
+    fn test_canonical_kmers() {
+        // AAC/GTT, ACG/CGT, CGT/ACG, GTT/AAC
+        assert_eq!(kmers("AACGTT", 3), vec!["AAC", "ACG", "ACG", "AAC"]);
+        assert_eq!(kmers("TTT", 3), vec!["AAA"]);
+        assert_eq!(kmers("ACGUU", 5), vec!["AACGT"]);
+        assert!(kmers("AC", 3).is_empty());
+    }
+
+    #[test]
+    fn test_ambiguous_bases_are_skipped() {
+        assert_eq!(kmers("AACNGTTRAC", 2), vec!["AA", "AC", "AC", "AA", "AC"]);
+        assert!(kmers("ACNGT", 3).is_empty());
+    }
+
+    #[test]
+    fn test_largest_k() {
+        let sequence = "ACGTTGCAACGTTGCAACGTTGCAACGTTGCAGG";
+        let kmers = kmers(sequence, MAX_K);
+
+        assert_eq!(kmers.len(), 3);
+        assert_eq!(kmers[0], &sequence[..32]);
+    }
+
+    #[test]
+    fn test_kmer_codes_sort_like_strings() {
+        let mut codes: Vec<u64> =
+            CanonicalKmers::new(&BinaryFastaSection::from_text("test", "GATTACAGATTACA"), 4)
+                .unwrap()
+                .collect();
+        codes.sort();
+        let strings: Vec<String> = codes.iter().map(|&kmer| kmer_to_string(kmer, 4)).collect();
+
+        let mut sorted = strings.clone();
+        sorted.sort();
+        assert_eq!(strings, sorted);
+    }
+
+    #[test]
+    fn test_invalid_k_and_protein() {
+        assert!(matches!(
+            CanonicalKmers::new(&BinaryFastaSection::from_text("test", "ACGT"), 0),
+            Err(BinaryFastaError::InvalidKmerSize { k: 0 })
+        ));
+        assert!(matches!(
+            CanonicalKmers::new(&BinaryFastaSection::from_text("test", "ACGT"), 33),
+            Err(BinaryFastaError::InvalidKmerSize { k: 33 })
+        ));
+        assert!(matches!(
+            CanonicalKmers::new(&BinaryFastaSection::from_text("test", "MVLSPADK"), 3),
+            Err(BinaryFastaError::NotNucleotide { .. })
+        ));
+    }
+}
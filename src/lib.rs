@@ -0,0 +1,28 @@
+pub mod annotate;
+pub mod batch;
+pub mod dedup;
+pub mod descriptor;
+pub mod detect;
+pub mod diff;
+pub mod errors;
+pub mod genetic_code;
+pub mod kmer;
+pub mod merge;
+pub mod motif;
+pub mod nucleotide_file;
+pub mod orf;
+pub mod output_file;
+pub mod records;
+pub mod region;
+pub mod rename;
+pub mod sample;
+pub mod sort;
+pub mod split;
+pub mod subset;
+pub mod translate;
+
+pub mod basta;
+pub mod bastq;
+pub mod fasta;
+pub mod fastq;
+pub mod twobit;
@@ -1,51 +1,53 @@
-pub mod errors;
-pub mod nucleotide_file;
+mod commands;
 mod parser;
 
-pub mod basta;
-pub mod fasta;
-
-use std::{io, path::Path};
+use std::process;
 
 use clap::Parser;
-use parser::Args;
+use parser::{Args, Command};
 
-use crate::{
-    basta::binary_fasta_data, errors::BinaryFastaError, fasta::fasta_data,
-    nucleotide_file::NucleotideFile,
-};
+use binary_fasta::{errors::BinaryFastaError, nucleotide_file::Direction};
 
 fn main() -> Result<(), BinaryFastaError> {
     let cli = Args::parse();
 
-    let input_path = Path::new(&cli.input);
-    if !input_path.exists() {
-        return Err(BinaryFastaError::Io(io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("File '{}' not found.", input_path.display()),
-        )));
-    }
-
-    let input_file: NucleotideFile = NucleotideFile::new(input_path)?;
-
-    let output_file_option: Option<NucleotideFile> = cli
-        .output
-        .map(|output_str| NucleotideFile::new(Path::new(&output_str)))
-        .transpose()?;
-    let output_file: NucleotideFile =
-        output_file_option.unwrap_or_else(|| input_file.switch_extension());
-
-    match input_file.format {
-        nucleotide_file::FileFormat::Fasta => {
-            let read_fasta_iter = fasta_data::read(&input_file.file_path)?;
-            let binary_iter = binary_fasta_data::from_fasta(read_fasta_iter);
-            binary_fasta_data::write(binary_iter, &output_file.file_path)?;
+    match cli.command {
+        Some(Command::Encode(args)) => {
+            commands::convert::run(&args.inputs, &args.output, Some(Direction::Encode))
+        }
+        Some(Command::Decode(args)) => {
+            commands::convert::run(&args.inputs, &args.output, Some(Direction::Decode))
         }
-        nucleotide_file::FileFormat::Basta => {
-            let read_basta_iter = binary_fasta_data::read(&input_file.file_path)?;
-            let fasta_iter = fasta_data::from_basta(read_basta_iter);
-            fasta_data::write(fasta_iter, &output_file.file_path)?;
+        Some(Command::Detect(args)) => commands::detect::run(&args.inputs),
+        Some(Command::Revcomp(args)) => commands::revcomp::run(&args),
+        Some(Command::Translate(args)) => commands::translate::run(&args),
+        Some(Command::Orfs(args)) => commands::orfs::run(&args),
+        Some(Command::Kmers(args)) => commands::kmers::run(&args),
+        Some(Command::Grep(args)) => commands::grep::run(&args),
+        Some(Command::Subset(args)) => commands::subset::run(&args),
+        Some(Command::Info(args)) => commands::info::run(&args),
+        Some(Command::Head(args)) => commands::head::run(&args),
+        Some(Command::Tail(args)) => commands::tail::run(&args),
+        Some(Command::Sample(args)) => commands::sample::run(&args),
+        Some(Command::Split(args)) => commands::split::run(&args),
+        Some(Command::Merge(args)) => commands::merge::run(&args),
+        Some(Command::Dedup(args)) => commands::dedup::run(&args),
+        Some(Command::Sort(args)) => commands::sort::run(&args),
+        Some(Command::Rename(args)) => commands::rename::run(&args),
+        Some(Command::Diff(args)) => {
+            // Like diff and cmp: 0 if the files are the same, 1 if they differ, 2 on errors.
+            let code = match commands::diff::run(&args) {
+                Ok(true) => 0,
+                Ok(false) => 1,
+                Err(e) => {
+                    eprintln!("Error: {:?}", e);
+                    2
+                }
+            };
+            process::exit(code)
         }
+        Some(Command::Annotate(args)) => commands::annotate::run(&args),
+        Some(Command::Metadata(args)) => commands::metadata::run(&args),
+        None => commands::convert::run(&cli.input, &cli.output, None),
     }
-    Ok(())
 }
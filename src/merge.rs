@@ -0,0 +1,130 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use clap::ValueEnum;
+
+use crate::{
+    basta::binary_fasta_section::BinaryFastaSection,
+    errors::BinaryFastaError,
+    records::{self, Sections},
+};
+
+// What to do with a sequence whose ID was already seen in an earlier input (or
+// earlier in the same one).
+#[derive(Debug, PartialEq, Clone, Copy, ValueEnum)]
+pub enum Duplicates {
+    // Stop with an error.
+    Error,
+    // Keep the first sequence only.
+    Skip,
+    // Add a numbered suffix to the ID: chr1, chr1_2, chr1_3...
+    Rename,
+}
+
+// Reads the inputs one after another. BASTA sections are passed on as they are read,
+// so writing them to BASTA copies their packed bytes without decoding them.
+pub fn merge(
+    inputs: Vec<PathBuf>,
+    duplicates: Duplicates,
+) -> impl Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>> {
+    let mut seen: HashSet<String> = HashSet::new();
+
+    inputs
+        .into_iter()
+        .flat_map(|input| -> Sections {
+            match records::read(&input) {
+                Ok(sections) => sections,
+                Err(e) => Box::new(std::iter::once(Err(e))),
+            }
+        })
+        .filter_map(move |res| {
+            let mut section = match res {
+                Ok(section) => section,
+                Err(e) => return Some(Err(e)),
+            };
+            if seen.insert(section.id().to_string()) {
+                return Some(Ok(section));
+            }
+
+            match duplicates {
+                Duplicates::Error => Some(Err(BinaryFastaError::DuplicateSequence {
+                    name: section.id().to_string(),
+                })),
+                Duplicates::Skip => None,
+                Duplicates::Rename => {
+                    let id = (2..)
+                        .map(|n| format!("{}_{}", section.id(), n))
+                        .find(|id| !seen.contains(id))?;
+                    section.set_id(&id);
+                    seen.insert(id);
+                    Some(Ok(section))
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    // Writes a FASTA file with the given descriptors, and returns its path.
+    fn fasta(dir: &Path, name: &str, descriptors: &[&str]) -> PathBuf {
+        let path = dir.join(name);
+        let text: String = descriptors
+            .iter()
+            .map(|descriptor| format!(">{}\nACGT\n", descriptor))
+            .collect();
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn merged(inputs: Vec<PathBuf>, duplicates: Duplicates) -> Vec<String> {
+        merge(inputs, duplicates)
+            .map(|res| res.unwrap().descriptor)
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_in_input_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = fasta(dir.path(), "a.fasta", &["chr1", "chr2"]);
+        let b = fasta(dir.path(), "b.fasta", &["chrM"]);
+
+        assert_eq!(
+            merged(vec![b, a], Duplicates::Error),
+            vec!["chrM", "chr1", "chr2"]
+        );
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = fasta(dir.path(), "a.fasta", &["chr1 from a", "chr1_2"]);
+        let b = fasta(dir.path(), "b.fasta", &["chr1 from b", "chr2"]);
+        let inputs = vec![a, b];
+
+        assert_eq!(
+            merged(inputs.clone(), Duplicates::Skip),
+            vec!["chr1 from a", "chr1_2", "chr2"]
+        );
+        assert_eq!(
+            merged(inputs.clone(), Duplicates::Rename),
+            vec!["chr1 from a", "chr1_2", "chr1_3 from b", "chr2"]
+        );
+
+        let results: Vec<_> = merge(inputs, Duplicates::Error).collect();
+        assert!(matches!(
+            &results[2],
+            Err(BinaryFastaError::DuplicateSequence { name }) if name == "chr1"
+        ));
+    }
+
+    #[test]
+    fn test_missing_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.fasta");
+
+        let mut sections = merge(vec![missing], Duplicates::Error);
+        assert!(sections.next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,687 @@
+use std::{fmt, io, io::Write, str::FromStr};
+
+use crate::{
+    basta::binary_fasta_section::{BinaryFastaSection, Molecule},
+    errors::BinaryFastaError,
+};
+
+// The mismatched bases of a match are kept as the bits of a u64.
+pub const MAX_PATTERN_LENGTH: usize = 64;
+
+// Bases matched by each IUPAC code, as a 4-bit mask with one bit per 2-bit BASTA code
+// (A = 0b0001, C = 0b0010, G = 0b0100, T = 0b1000).
+fn iupac_mask(base: u8) -> Option<u8> {
+    let mask = match base.to_ascii_uppercase() {
+        b'A' => 0b0001,
+        b'C' => 0b0010,
+        b'G' => 0b0100,
+        b'T' | b'U' => 0b1000,
+        b'R' => 0b0101,
+        b'Y' => 0b1010,
+        b'S' => 0b0110,
+        b'W' => 0b1001,
+        b'K' => 0b1100,
+        b'M' => 0b0011,
+        b'B' => 0b1110,
+        b'D' => 0b1101,
+        b'H' => 0b1011,
+        b'V' => 0b0111,
+        b'N' => 0b1111,
+        _ => return None,
+    };
+    Some(mask)
+}
+
+// The complement of a set of bases swaps A with T and C with G, which reverses the mask.
+fn complement_mask(mask: u8) -> u8 {
+    mask.reverse_bits() >> 4
+}
+
+// A nucleotide pattern, possibly with IUPAC degenerate bases.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pattern {
+    pub text: String,
+    // Bases allowed at each position of the pattern.
+    masks: Vec<u8>,
+}
+
+impl Pattern {
+    pub fn len(&self) -> usize {
+        self.masks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.masks.is_empty()
+    }
+
+    pub fn reverse_complement(&self) -> Pattern {
+        Pattern {
+            text: self.text.clone(),
+            masks: self
+                .masks
+                .iter()
+                .rev()
+                .map(|&mask| complement_mask(mask))
+                .collect(),
+        }
+    }
+
+    // The pattern in chunks of up to 32 bases, packed like the sequence.
+    fn packed(&self) -> Vec<PackedChunk> {
+        self.masks
+            .chunks(BASES_PER_WORD)
+            .enumerate()
+            .map(|(index, masks)| PackedChunk::new(index * BASES_PER_WORD, masks))
+            .collect()
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = BinaryFastaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let masks: Option<Vec<u8>> = s.bytes().map(iupac_mask).collect();
+        match masks {
+            Some(masks) if !masks.is_empty() && masks.len() <= MAX_PATTERN_LENGTH => Ok(Pattern {
+                text: s.to_string(),
+                masks,
+            }),
+            _ => Err(BinaryFastaError::InvalidPattern {
+                pattern: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+// Bases in a word of the packed sequence. The first base is in the two highest bits.
+const BASES_PER_WORD: usize = 32;
+
+// The low bit of every base in a word, where the mismatches are flagged.
+const LANES: u64 = 0x5555_5555_5555_5555;
+
+// The bit flagging base `index` of a word.
+fn lane(index: usize) -> u64 {
+    1 << (62 - 2 * index)
+}
+
+fn lane_index(bit: u32) -> usize {
+    (62 - bit as usize) / 2
+}
+
+// Up to 32 bases of a pattern, packed with one base allowed by each IUPAC code, so that
+// XORing them with the same number of sequence bases gives the 2-bit difference of
+// every base at once. Which differences are mismatches depends on the IUPAC code,
+// grouped by the bits that are checked:
+// - a single base must be equal (`either` bit set is a mismatch),
+// - R and Y (A or G, C or T) only check the low bit, K and M (G or T, A or C) the high bit,
+// - S and W (C or G, A or T) allow 00 and 11, so the two bits must not differ,
+// - B, D, H and V store the complement of the missing base, the only one at 11,
+// - N is never a mismatch.
+struct PackedChunk {
+    // Position of the first base in the pattern.
+    offset: usize,
+    masks: Vec<u8>,
+    bases: u64,
+    either: u64,
+    low: u64,
+    high: u64,
+    differ: u64,
+    both: u64,
+    // The lanes of all the bases of the chunk.
+    span: u64,
+}
+
+impl PackedChunk {
+    fn new(offset: usize, masks: &[u8]) -> Self {
+        let mut chunk = PackedChunk {
+            offset,
+            masks: masks.to_vec(),
+            bases: 0,
+            either: 0,
+            low: 0,
+            high: 0,
+            differ: 0,
+            both: 0,
+            span: 0,
+        };
+        for (index, &mask) in masks.iter().enumerate() {
+            let lane = lane(index);
+            chunk.span |= lane;
+            let codes: Vec<u64> = (0..4).filter(|code| mask & (1 << code) != 0).collect();
+            let (base, checked) = match codes[..] {
+                [code] => (code, &mut chunk.either),
+                [first, second] => match first ^ second {
+                    0b10 => (first, &mut chunk.low),
+                    0b01 => (first, &mut chunk.high),
+                    _ => (first, &mut chunk.differ),
+                },
+                [_, _, _] => {
+                    let missing = (0..4).find(|code| !codes.contains(code)).unwrap();
+                    (missing ^ 0b11, &mut chunk.both)
+                }
+                _ => continue,
+            };
+            *checked |= lane;
+            chunk.bases |= base * lane;
+        }
+        chunk
+    }
+
+    // Lanes of the bases of `window` that the chunk does not allow, ignoring ambiguity.
+    fn mismatches(&self, window: u64) -> u64 {
+        let difference = window ^ self.bases;
+        let low = difference & LANES;
+        let high = (difference >> 1) & LANES;
+        ((low | high) & self.either)
+            | (low & self.low)
+            | (high & self.high)
+            | ((low ^ high) & self.differ)
+            | (low & high & self.both)
+    }
+}
+
+// The packed sequence as big-endian words, followed by a zero word so that a window can
+// always read the word after the one it starts in. Ambiguous bases are stored as A, so
+// their lanes are flagged in `ambiguous` and checked one by one: they match a pattern
+// base only if the pattern allows every base they stand for (so N is only matched by N).
+struct PackedSequence<'a> {
+    section: &'a BinaryFastaSection,
+    words: Vec<u64>,
+    // Empty if the section has no ambiguous bases.
+    ambiguous: Vec<u64>,
+}
+
+impl<'a> PackedSequence<'a> {
+    fn new(section: &'a BinaryFastaSection) -> Self {
+        let mut words: Vec<u64> = section
+            .sequence
+            .chunks(8)
+            .map(|chunk| {
+                let mut bytes = [0u8; 8];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                u64::from_be_bytes(bytes)
+            })
+            .collect();
+        words.push(0);
+
+        let mut ambiguous: Vec<u64> = Vec::new();
+        if !section.ambiguous_runs.is_empty() {
+            ambiguous = vec![0; words.len()];
+            for run in &section.ambiguous_runs {
+                for position in run.start()..run.end() {
+                    ambiguous[position / BASES_PER_WORD] |= lane(position % BASES_PER_WORD);
+                }
+            }
+        }
+
+        PackedSequence {
+            section,
+            words,
+            ambiguous,
+        }
+    }
+
+    // The 32 bases starting at `position`, shifted out of the two words they span.
+    fn window(words: &[u64], position: usize) -> u64 {
+        let index = position / BASES_PER_WORD;
+        let shift = 2 * (position % BASES_PER_WORD);
+        if shift == 0 {
+            words[index]
+        } else {
+            (words[index] << shift) | (words[index + 1] >> (64 - shift))
+        }
+    }
+
+    // Lanes of the bases of the chunk where it does not match the sequence at `start`.
+    fn mismatches(&self, chunk: &PackedChunk, start: usize) -> u64 {
+        let position = start + chunk.offset;
+        let mut mismatches = chunk.mismatches(Self::window(&self.words, position));
+        if self.ambiguous.is_empty() {
+            return mismatches;
+        }
+
+        let mut ambiguous = Self::window(&self.ambiguous, position) & chunk.span;
+        mismatches &= !ambiguous;
+        while ambiguous != 0 {
+            let bit = ambiguous.trailing_zeros();
+            ambiguous &= ambiguous - 1;
+            let index = lane_index(bit);
+            let mask = self
+                .section
+                .ambiguous_base(position + index)
+                .and_then(iupac_mask)
+                .unwrap_or(0b1111);
+            if mask & !chunk.masks[index] != 0 {
+                mismatches |= 1 << bit;
+            }
+        }
+        mismatches
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Match {
+    // Index of the pattern in the list that was searched for.
+    pub pattern: usize,
+    // 0-based, end exclusive, on the forward strand.
+    pub start: usize,
+    pub end: usize,
+    pub reverse: bool,
+    // Positions of the mismatched bases, bit 0 being the last base of the match.
+    mismatches: u64,
+}
+
+impl Match {
+    pub fn mismatch_count(&self) -> u32 {
+        self.mismatches.count_ones()
+    }
+
+    // 0-based positions of the mismatches in the pattern, as it was written (so counted
+    // from the end of the match on the forward strand for reverse matches).
+    pub fn mismatch_positions(&self) -> Vec<usize> {
+        let length = self.end - self.start;
+        let mut positions: Vec<usize> = (0..length)
+            .filter(|&bit| self.mismatches & (1 << bit) != 0)
+            .map(|bit| if self.reverse { bit } else { length - 1 - bit })
+            .collect();
+        positions.sort_unstable();
+        positions
+    }
+
+    // The pattern is used as the BED name, and the number of mismatches as the score.
+    // With `mismatch_column`, a 7th column lists the 1-based pattern positions of the
+    // mismatches (or '.' for an exact match).
+    pub fn write_bed(
+        &self,
+        f: &mut impl Write,
+        seq_id: &str,
+        pattern: &Pattern,
+        mismatch_column: bool,
+    ) -> io::Result<()> {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            seq_id,
+            self.start,
+            self.end,
+            pattern,
+            self.mismatch_count(),
+            if self.reverse { '-' } else { '+' }
+        )?;
+        if mismatch_column {
+            let positions: Vec<String> = self
+                .mismatch_positions()
+                .iter()
+                .map(|position| (position + 1).to_string())
+                .collect();
+            if positions.is_empty() {
+                write!(f, "\t.")?;
+            } else {
+                write!(f, "\t{}", positions.join(","))?;
+            }
+        }
+        writeln!(f)
+    }
+}
+
+// Finds every occurrence of the patterns in a nucleotide section with at most
+// `max_mismatches` mismatched bases, reading the bases straight from the packed bytes.
+// With `both_strands`, the reverse complement of each pattern is searched as well,
+// except for palindromes, which would only be found twice.
+pub fn find_matches(
+    section: &BinaryFastaSection,
+    patterns: &[Pattern],
+    both_strands: bool,
+    max_mismatches: u32,
+) -> Result<Vec<Match>, BinaryFastaError> {
+    if section.molecule == Molecule::Protein {
+        return Err(BinaryFastaError::NotNucleotide {
+            name: section.descriptor.clone(),
+        });
+    }
+
+    let mut searches: Vec<(usize, usize, bool, Vec<PackedChunk>)> = Vec::new();
+    for (index, pattern) in patterns.iter().enumerate() {
+        searches.push((index, pattern.len(), false, pattern.packed()));
+        let reverse = pattern.reverse_complement();
+        if both_strands && reverse != *pattern {
+            searches.push((index, reverse.len(), true, reverse.packed()));
+        }
+    }
+
+    let sequence = PackedSequence::new(section);
+    let mut matches: Vec<Match> = Vec::new();
+    for (pattern, length, reverse, chunks) in &searches {
+        if section.length() < *length {
+            continue;
+        }
+        for start in 0..=section.length() - length {
+            // The Hamming distance to the pattern is the number of mismatched bases, 32
+            // at a time.
+            let mut distance = 0;
+            let mut lanes = [0u64; MAX_PATTERN_LENGTH / BASES_PER_WORD];
+            for (chunk, lanes) in chunks.iter().zip(lanes.iter_mut()) {
+                *lanes = sequence.mismatches(chunk, start);
+                distance += lanes.count_ones();
+                if distance > max_mismatches {
+                    break;
+                }
+            }
+            if distance > max_mismatches {
+                continue;
+            }
+
+            let mut mismatches = 0u64;
+            for (chunk, mut bits) in chunks.iter().zip(lanes) {
+                while bits != 0 {
+                    let index = chunk.offset + lane_index(bits.trailing_zeros());
+                    bits &= bits - 1;
+                    mismatches |= 1 << (length - 1 - index);
+                }
+            }
+            matches.push(Match {
+                pattern: *pattern,
+                start,
+                end: start + length,
+                reverse: *reverse,
+                mismatches,
+            });
+        }
+    }
+
+    matches.sort_by_key(|m| (m.start, m.end, m.reverse));
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(patterns: &[&str]) -> Vec<Pattern> {
+        patterns.iter().map(|p| p.parse().unwrap()).collect()
+    }
+
+    fn positions(sequence: &str, pattern: &str, both_strands: bool) -> Vec<(usize, bool)> {
+        find_matches(
+            &BinaryFastaSection::from_text("chr1", sequence),
+            &patterns(&[pattern]),
+            both_strands,
+            0,
+        )
+        .unwrap()
+        .iter()
+        .map(|m| (m.start, m.reverse))
+        .collect()
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!("acgu".parse::<Pattern>().unwrap().len(), 4);
+        assert_eq!(
+            "RYN".parse::<Pattern>().unwrap().masks,
+            vec![0b0101, 0b1010, 0b1111]
+        );
+        assert!("".parse::<Pattern>().is_err());
+        assert!("ACGX".parse::<Pattern>().is_err());
+        assert!("A"
+            .repeat(MAX_PATTERN_LENGTH + 1)
+            .parse::<Pattern>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_reverse_complement_pattern() {
+        let pattern: Pattern = "AACR".parse().unwrap();
+        let expected: Pattern = "YGTT".parse().unwrap();
+        assert_eq!(pattern.reverse_complement().masks, expected.masks);
+    }
+
+    #[test]
+    fn test_exact_matches() {
+        assert_eq!(
+            positions("ACGTACGTAC", "GTA", false),
+            vec![(2, false), (6, false)]
+        );
+        assert_eq!(
+            positions("ACGTACGTAC", "ACG", false),
+            vec![(0, false), (4, false)]
+        );
+        assert_eq!(
+            positions("AAAA", "AA", false),
+            vec![(0, false), (1, false), (2, false)]
+        );
+        assert!(positions("ACG", "ACGT", false).is_empty());
+    }
+
+    #[test]
+    fn test_degenerate_bases() {
+        // GANTC matches GAATC and GATTC, but not GACCTC.
+        assert_eq!(
+            positions("GAATCGATTCGACCTC", "GANTC", false),
+            vec![(0, false), (5, false)]
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_bases_in_the_sequence() {
+        // N is only matched by N, and R by R, D or N (which allow both A and G).
+        assert_eq!(
+            positions("ACNTACGT", "ACNT", false),
+            vec![(0, false), (4, false)]
+        );
+        assert_eq!(positions("ACNTACGT", "ACGT", false), vec![(4, false)]);
+        assert_eq!(
+            positions("GRCGACG", "GDC", false),
+            vec![(0, false), (3, false)]
+        );
+
+        // Ambiguous bases that do not match count as mismatches.
+        let matches = find_matches(
+            &BinaryFastaSection::from_text("chr1", "ACNT"),
+            &patterns(&["ACGT"]),
+            false,
+            1,
+        )
+        .unwrap();
+        assert_eq!(matches[0].mismatch_positions(), vec![2]);
+    }
+
+    #[test]
+    fn test_both_strands() {
+        // AAC on the reverse strand is GTT on the forward strand.
+        assert_eq!(
+            positions("AACGGTT", "AAC", true),
+            vec![(0, false), (4, true)]
+        );
+        // Palindromic sites are only reported once.
+        assert_eq!(positions("TTGAATTCTT", "GAATTC", true), vec![(2, false)]);
+    }
+
+    #[test]
+    fn test_long_pattern() {
+        let sequence = format!("GG{}GG", "ACGT".repeat(16));
+        assert_eq!(
+            positions(&sequence, &"ACGT".repeat(16), false),
+            vec![(2, false)]
+        );
+    }
+
+    #[test]
+    fn test_matches_across_word_boundaries() {
+        // Bases 30 to 35 span the first two words of the packed sequence, and bases 60 to
+        // 99 the next three.
+        let mut sequence = "A".repeat(100);
+        sequence.replace_range(30..36, "CGTNCG");
+        sequence.replace_range(60..100, &"GATC".repeat(10));
+        assert_eq!(positions(&sequence, "CGTNCG", false), vec![(30, false)]);
+        assert_eq!(
+            positions(&sequence, &format!("A{}", "GATC".repeat(10)), false),
+            vec![(59, false)]
+        );
+
+        let matches = find_matches(
+            &BinaryFastaSection::from_text("chr1", &sequence),
+            &patterns(&["ACGTACG"]),
+            false,
+            1,
+        )
+        .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].start, 29);
+        assert_eq!(matches[0].mismatch_positions(), vec![4]);
+    }
+
+    // Matches of a pattern found one base at a time from the text of the sequence.
+    fn naive_matches(sequence: &[u8], pattern: &[u8], max_mismatches: usize) -> Vec<Match> {
+        let mut matches = Vec::new();
+        for start in 0..=sequence.len().saturating_sub(pattern.len()) {
+            let mut mismatches = 0u64;
+            for (i, &base) in pattern.iter().enumerate() {
+                let allowed = iupac_mask(base).unwrap();
+                if iupac_mask(sequence[start + i]).unwrap() & !allowed != 0 {
+                    mismatches |= 1 << (pattern.len() - 1 - i);
+                }
+            }
+            if mismatches.count_ones() as usize <= max_mismatches {
+                matches.push(Match {
+                    pattern: 0,
+                    start,
+                    end: start + pattern.len(),
+                    reverse: false,
+                    mismatches,
+                });
+            }
+        }
+        matches
+    }
+
+    #[test]
+    fn test_large_input_against_naive_matching() {
+        let mut state: u32 = 42;
+        let mut random = |choices: &[u8]| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            choices[(state >> 16) as usize % choices.len()]
+        };
+
+        // Mostly plain bases, with a few ambiguous ones.
+        let sequence: Vec<u8> = (0..200_000)
+            .map(|_| random(b"ACGTACGTACGTACGTACGTACGTACGTACGTACGTACGTACGTACGTNR"))
+            .collect();
+        let section =
+            BinaryFastaSection::from_text("chr1", std::str::from_utf8(&sequence).unwrap());
+
+        for length in [5, 12, 32, 33, 64] {
+            let pattern: Vec<u8> = (0..length)
+                .map(|_| random(b"ACGTACGTACGTRYSWKMBDHVN"))
+                .collect();
+            let max_mismatches = length / 3;
+            let text = String::from_utf8(pattern.clone()).unwrap();
+            assert_eq!(
+                find_matches(&section, &patterns(&[&text]), false, max_mismatches as u32).unwrap(),
+                naive_matches(&sequence, &pattern, max_mismatches),
+                "pattern {}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_matches_of_several_patterns_are_sorted() {
+        let matches = find_matches(
+            &BinaryFastaSection::from_text("chr1", "ACGTTT"),
+            &patterns(&["TTT", "CG"]),
+            false,
+            0,
+        )
+        .unwrap();
+        assert_eq!(
+            matches,
+            vec![
+                Match {
+                    pattern: 1,
+                    start: 1,
+                    end: 3,
+                    reverse: false,
+                    mismatches: 0,
+                },
+                Match {
+                    pattern: 0,
+                    start: 3,
+                    end: 6,
+                    reverse: false,
+                    mismatches: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let section = BinaryFastaSection::from_text("chr1", "ACGTTACCTAACGA");
+        let pattern = patterns(&["ACGT"]);
+
+        let exact = find_matches(&section, &pattern, false, 0).unwrap();
+        assert_eq!(exact.len(), 1);
+
+        // ACGT, ACCT (3rd base) and ACGA (4th base).
+        let approximate = find_matches(&section, &pattern, false, 1).unwrap();
+        let found: Vec<(usize, Vec<usize>)> = approximate
+            .iter()
+            .map(|m| (m.start, m.mismatch_positions()))
+            .collect();
+        assert_eq!(found, vec![(0, vec![]), (5, vec![2]), (10, vec![3])]);
+        assert_eq!(approximate[1].mismatch_count(), 1);
+    }
+
+    #[test]
+    fn test_mismatch_positions_on_reverse_strand() {
+        // GTTA is the reverse complement of TAAC, which differs from the pattern GAAC at its 1st base.
+        let matches = find_matches(
+            &BinaryFastaSection::from_text("chr1", "TTGTTATT"),
+            &patterns(&["GAAC"]),
+            true,
+            1,
+        )
+        .unwrap();
+        let reverse: Vec<&Match> = matches.iter().filter(|m| m.reverse).collect();
+
+        assert_eq!(reverse.len(), 1);
+        assert_eq!((reverse[0].start, reverse[0].end), (2, 6));
+        assert_eq!(reverse[0].mismatch_positions(), vec![0]);
+    }
+
+    #[test]
+    fn test_bed_line_with_mismatches() {
+        let m = Match {
+            pattern: 0,
+            start: 5,
+            end: 9,
+            reverse: false,
+            mismatches: 0b0010,
+        };
+        let pattern: Pattern = "ACGT".parse().unwrap();
+
+        let mut bed: Vec<u8> = Vec::new();
+        m.write_bed(&mut bed, "chr1", &pattern, false).unwrap();
+        assert_eq!(bed, b"chr1\t5\t9\tACGT\t1\t+\n");
+
+        let mut bed: Vec<u8> = Vec::new();
+        m.write_bed(&mut bed, "chr1", &pattern, true).unwrap();
+        assert_eq!(bed, b"chr1\t5\t9\tACGT\t1\t+\t3\n");
+    }
+
+    #[test]
+    fn test_rejects_protein() {
+        let protein = BinaryFastaSection::from_text("chr1", "MVLSPADK");
+        assert!(matches!(
+            find_matches(&protein, &patterns(&["ACG"]), false, 0),
+            Err(BinaryFastaError::NotNucleotide { .. })
+        ));
+    }
+}
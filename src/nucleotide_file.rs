@@ -1,14 +1,97 @@
 use std::{
     ffi::OsStr,
+    fmt,
     path::{Path, PathBuf},
 };
 
-use crate::errors::BinaryFastaError;
+use crate::{
+    detect::{self, DetectedFormat},
+    errors::BinaryFastaError,
+};
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum FileFormat {
     Fasta,
     Basta,
+    Fastq,
+    Bastq,
+    TwoBit,
+}
+
+// Text formats are encoded into their binary counterpart, binary formats are decoded.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Direction {
+    Encode,
+    Decode,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Encode => write!(f, "encode"),
+            Direction::Decode => write!(f, "decode"),
+        }
+    }
+}
+
+impl FileFormat {
+    // Common FASTA extensions besides .fa/.fasta: nucleic acid (.fna), coding
+    // regions (.ffn), non-coding RNA (.frn), amino acid (.faa), and the generic
+    // .fas, .mfa, .seq.
+    pub fn from_extension(ext: &str) -> Option<FileFormat> {
+        match ext {
+            "fasta" | "fa" | "fna" | "ffn" | "frn" | "faa" | "fas" | "mfa" | "seq" => {
+                Some(FileFormat::Fasta)
+            }
+            "basta" | "ba" => Some(FileFormat::Basta),
+            "fastq" | "fq" => Some(FileFormat::Fastq),
+            "bastq" | "bq" => Some(FileFormat::Bastq),
+            "2bit" => Some(FileFormat::TwoBit),
+            _ => None,
+        }
+    }
+
+    pub fn from_detected(detected: DetectedFormat) -> Option<FileFormat> {
+        match detected {
+            DetectedFormat::Fasta => Some(FileFormat::Fasta),
+            DetectedFormat::Basta => Some(FileFormat::Basta),
+            DetectedFormat::Fastq => Some(FileFormat::Fastq),
+            DetectedFormat::Bastq => Some(FileFormat::Bastq),
+            DetectedFormat::TwoBit => Some(FileFormat::TwoBit),
+            _ => None,
+        }
+    }
+
+    // The direction in which a file of this format is converted.
+    pub fn direction(&self) -> Direction {
+        match self {
+            FileFormat::Fasta | FileFormat::Fastq => Direction::Encode,
+            FileFormat::Basta | FileFormat::Bastq | FileFormat::TwoBit => Direction::Decode,
+        }
+    }
+
+    // Formats a file of this format can be converted to, the default one first.
+    pub fn conversion_targets(&self) -> &'static [FileFormat] {
+        match self {
+            FileFormat::Fasta => &[FileFormat::Basta, FileFormat::TwoBit],
+            FileFormat::Basta => &[FileFormat::Fasta, FileFormat::TwoBit],
+            FileFormat::TwoBit => &[FileFormat::Fasta, FileFormat::Basta],
+            FileFormat::Fastq => &[FileFormat::Bastq],
+            FileFormat::Bastq => &[FileFormat::Fastq],
+        }
+    }
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileFormat::Fasta => write!(f, "FASTA"),
+            FileFormat::Basta => write!(f, "BASTA"),
+            FileFormat::Fastq => write!(f, "FASTQ"),
+            FileFormat::Bastq => write!(f, "BASTQ"),
+            FileFormat::TwoBit => write!(f, "2bit"),
+        }
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -29,44 +112,103 @@ impl NucleotideFile {
             }
         };
 
-        let (format, long_extension) = match ext {
-            "fasta" | "fa" => (FileFormat::Fasta, ext.len() > 2),
-            "basta" | "ba" => (FileFormat::Basta, ext.len() > 2),
-            _ => {
+        let format = match FileFormat::from_extension(ext) {
+            Some(format) => format,
+            None => {
                 return Err(BinaryFastaError::InvalidFileExtension {
                     path: file_path.to_path_buf(),
                 })
             }
         };
 
-        Ok(NucleotideFile {
+        Ok(Self::with_format(file_path, format))
+    }
+
+    // For when the format is known regardless of the extension (e.g. given on the command line).
+    pub fn with_format(file_path: &Path, format: FileFormat) -> NucleotideFile {
+        let ext = file_path.extension().and_then(OsStr::to_str);
+
+        NucleotideFile {
             format,
             file_path: file_path.to_path_buf(),
-            long_extension,
-        })
+            long_extension: !matches!(ext, Some("fa") | Some("ba") | Some("fq") | Some("bq")),
+        }
     }
 
-    fn get_opposite_type(&self) -> FileFormat {
-        match self.format {
-            FileFormat::Fasta => FileFormat::Basta,
-            FileFormat::Basta => FileFormat::Fasta,
+    // Determines the format from the file content, falling back to the extension
+    // for content that is not recognized (BASTA files written without a header).
+    pub fn detect(file_path: &Path) -> Result<NucleotideFile, BinaryFastaError> {
+        let found = detect::detect_format(file_path)?;
+        match FileFormat::from_detected(found) {
+            Some(format) => Ok(Self::with_format(file_path, format)),
+            None if matches!(found, DetectedFormat::Unknown | DetectedFormat::Empty) => {
+                Self::new(file_path)
+            }
+            None => Err(BinaryFastaError::UnsupportedFormat {
+                path: file_path.to_path_buf(),
+                found,
+            }),
         }
     }
 
-    pub fn switch_extension(&self) -> NucleotideFile {
-        let new_extension = {
-            if self.format == FileFormat::Fasta {
-                if self.long_extension {
-                    "basta"
-                } else {
-                    "ba"
+    // For when the direction was given on the command line, so the extension does not
+    // matter. Fails if the content is recognizably something that cannot go that way.
+    pub fn for_direction(
+        file_path: &Path,
+        direction: Direction,
+    ) -> Result<NucleotideFile, BinaryFastaError> {
+        let found = detect::detect_format(file_path)?;
+        let format = match FileFormat::from_detected(found) {
+            Some(format) if format.direction() == direction => format,
+            // Unrecognized content is left for the FASTA reader to reject,
+            // or is a BASTA file written before the header existed.
+            None if matches!(found, DetectedFormat::Unknown | DetectedFormat::Empty) => {
+                match direction {
+                    Direction::Encode => FileFormat::Fasta,
+                    Direction::Decode => FileFormat::Basta,
                 }
-            } else if self.long_extension {
-                "fasta"
-            } else {
-                "fa"
+            }
+            _ => {
+                return Err(BinaryFastaError::UnexpectedFormat {
+                    path: file_path.to_path_buf(),
+                    direction,
+                    found,
+                })
             }
         };
+        Ok(Self::with_format(file_path, format))
+    }
+
+    fn get_opposite_type(&self) -> FileFormat {
+        self.format.conversion_targets()[0]
+    }
+
+    // The format to write to `output_path`: the one its extension names if the
+    // input can be converted to it, or else the opposite of the input format.
+    pub fn output_format(&self, output_path: &Path) -> FileFormat {
+        let requested = output_path
+            .extension()
+            .and_then(OsStr::to_str)
+            .and_then(FileFormat::from_extension);
+
+        match requested {
+            Some(format) if self.format.conversion_targets().contains(&format) => format,
+            _ => self.get_opposite_type(),
+        }
+    }
+
+    pub fn switch_extension(&self) -> NucleotideFile {
+        let new_extension = match (self.get_opposite_type(), self.long_extension) {
+            (FileFormat::Fasta, true) => "fasta",
+            (FileFormat::Fasta, false) => "fa",
+            (FileFormat::Basta, true) => "basta",
+            (FileFormat::Basta, false) => "ba",
+            (FileFormat::Fastq, true) => "fastq",
+            (FileFormat::Fastq, false) => "fq",
+            (FileFormat::Bastq, true) => "bastq",
+            (FileFormat::Bastq, false) => "bq",
+            (FileFormat::TwoBit, _) => "2bit",
+        };
 
         NucleotideFile {
             format: self.get_opposite_type(),
@@ -153,6 +295,108 @@ mod test {
         assert_eq!(expected, NucleotideFile::new(Path::new("test.ba")).unwrap());
     }
 
+    #[test]
+    fn test_new_fasta_alternative_extension() {
+        let expected = NucleotideFile {
+            format: FileFormat::Fasta,
+            file_path: Path::new("test.fna").to_path_buf(),
+            long_extension: true,
+        };
+
+        assert_eq!(
+            expected,
+            NucleotideFile::new(Path::new("test.fna")).unwrap()
+        );
+        assert_eq!(
+            expected.switch_extension().file_path,
+            Path::new("test.basta").to_path_buf()
+        );
+    }
+
+    #[test]
+    fn test_with_format_ignores_extension() {
+        let expected = NucleotideFile {
+            format: FileFormat::Basta,
+            file_path: Path::new("test.bin").to_path_buf(),
+            long_extension: true,
+        };
+
+        assert_eq!(
+            expected,
+            NucleotideFile::with_format(Path::new("test.bin"), FileFormat::Basta)
+        );
+        assert_eq!(
+            expected.switch_extension().file_path,
+            Path::new("test.fasta").to_path_buf()
+        );
+    }
+
+    #[test]
+    fn test_detect_fasta_by_content() {
+        let file = NucleotideFile::detect(Path::new("tests/data/small_dna.fasta")).unwrap();
+        assert_eq!(file.format, FileFormat::Fasta);
+    }
+
+    #[test]
+    fn test_for_direction_rejects_mismatched_content() {
+        let path = Path::new("tests/data/small_dna.fasta");
+
+        assert!(NucleotideFile::for_direction(path, Direction::Encode).is_ok());
+        assert!(matches!(
+            NucleotideFile::for_direction(path, Direction::Decode),
+            Err(BinaryFastaError::UnexpectedFormat {
+                found: DetectedFormat::Fasta,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_output_format_from_extension() {
+        let fasta = NucleotideFile::new(Path::new("genome.fa")).unwrap();
+        assert_eq!(
+            fasta.output_format(Path::new("genome.2bit")),
+            FileFormat::TwoBit
+        );
+        assert_eq!(
+            fasta.output_format(Path::new("genome.ba")),
+            FileFormat::Basta
+        );
+        // Unknown extensions, and formats the input cannot be converted to, use the default.
+        assert_eq!(
+            fasta.output_format(Path::new("genome.bin")),
+            FileFormat::Basta
+        );
+        assert_eq!(
+            fasta.output_format(Path::new("genome.fq")),
+            FileFormat::Basta
+        );
+
+        let two_bit = NucleotideFile::new(Path::new("hs1.2bit")).unwrap();
+        assert_eq!(two_bit.switch_extension().file_path, Path::new("hs1.fasta"));
+        assert_eq!(
+            two_bit.output_format(Path::new("hs1.basta")),
+            FileFormat::Basta
+        );
+    }
+
+    #[test]
+    fn test_fastq_switch_extension() {
+        let fastq = NucleotideFile::new(Path::new("reads.fq")).unwrap();
+        assert_eq!(fastq.format, FileFormat::Fastq);
+        assert_eq!(fastq.switch_extension().format, FileFormat::Bastq);
+        assert_eq!(
+            fastq.switch_extension().file_path,
+            Path::new("reads.bq").to_path_buf()
+        );
+
+        let bastq = NucleotideFile::new(Path::new("reads.bastq")).unwrap();
+        assert_eq!(
+            bastq.switch_extension().file_path,
+            Path::new("reads.fastq").to_path_buf()
+        );
+    }
+
     #[test]
     fn test_new_fasta_switch_extension() {
         let expected = NucleotideFile {
@@ -0,0 +1,262 @@
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+
+use crate::{
+    basta::binary_fasta_section::BinaryFastaSection,
+    errors::BinaryFastaError,
+    genetic_code::GeneticCode,
+    translate::{Frame, StopCodons, Translation},
+};
+
+#[derive(Debug, PartialEq, Clone, Copy, ValueEnum)]
+pub enum OrfFormat {
+    Bed,
+    Gff,
+}
+
+pub struct OrfOptions {
+    pub code: &'static GeneticCode,
+    // Codons that can start an ORF, by 6-bit codon index.
+    pub starts: [bool; 64],
+    // Minimum ORF length in nucleotides, including the stop codon.
+    pub min_length: usize,
+}
+
+impl OrfOptions {
+    // `starts` is a comma separated list of codons, or "table" for the start codons
+    // of the genetic code.
+    pub fn new(
+        code: &'static GeneticCode,
+        starts: &str,
+        min_length: usize,
+    ) -> Result<Self, BinaryFastaError> {
+        let mut start_codons = [false; 64];
+        if starts == "table" {
+            for (codon, is_start) in start_codons.iter_mut().enumerate() {
+                *is_start = code.is_start(codon as u8);
+            }
+        } else {
+            for codon in starts.split(',').map(str::trim) {
+                match GeneticCode::codon_index(codon.as_bytes()) {
+                    Some(index) if codon.len() == 3 => start_codons[index as usize] = true,
+                    _ => {
+                        return Err(BinaryFastaError::InvalidCodon {
+                            codon: codon.to_string(),
+                        })
+                    }
+                }
+            }
+        }
+
+        Ok(OrfOptions {
+            code,
+            starts: start_codons,
+            min_length,
+        })
+    }
+}
+
+// An open reading frame, from a start codon up to and including the first stop
+// codon in the same frame.
+#[derive(Debug, PartialEq)]
+pub struct Orf {
+    pub frame: Frame,
+    // 0-based, end exclusive, on the forward strand (also for reverse frames).
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Orf {
+    fn strand(&self) -> char {
+        if self.frame.is_reverse() {
+            '-'
+        } else {
+            '+'
+        }
+    }
+
+    pub fn write_bed(&self, f: &mut impl Write, seq_id: &str, name: &str) -> io::Result<()> {
+        writeln!(
+            f,
+            "{}\t{}\t{}\t{}\t0\t{}",
+            seq_id,
+            self.start,
+            self.end,
+            name,
+            self.strand()
+        )
+    }
+
+    // GFF3 coordinates are 1-based and inclusive.
+    pub fn write_gff(&self, f: &mut impl Write, seq_id: &str, name: &str) -> io::Result<()> {
+        writeln!(
+            f,
+            "{}\tbinary_fasta\tORF\t{}\t{}\t.\t{}\t0\tID={};frame={}",
+            seq_id,
+            self.start + 1,
+            self.end,
+            self.strand(),
+            name,
+            self.frame
+        )
+    }
+
+    // The protein encoded by the ORF, starting with M and without the stop codon.
+    pub fn protein(
+        &self,
+        section: &BinaryFastaSection,
+        code: &'static GeneticCode,
+    ) -> Result<BinaryFastaSection, BinaryFastaError> {
+        let translation = Translation {
+            code,
+            stop_codons: StopCodons::Trim,
+            start_as_met: true,
+        };
+
+        let forward = section.subsequence(self.start, self.end);
+        if self.frame.is_reverse() {
+            forward.reverse_complement()?.translate(&translation, 0)
+        } else {
+            forward.translate(&translation, 0)
+        }
+    }
+}
+
+// Finds the ORFs in all six frames. Within a frame, the first start codon after a
+// stop codon opens an ORF, so nested ORFs are reported once, at their longest.
+// ORFs without a stop codon before the end of the sequence are not reported.
+pub fn find_orfs(
+    section: &BinaryFastaSection,
+    options: &OrfOptions,
+) -> Result<Vec<Orf>, BinaryFastaError> {
+    let length = section.length();
+    let reverse = section.reverse_complement()?;
+    let mut orfs: Vec<Orf> = Vec::new();
+
+    for frame in Frame::ALL {
+        let strand = if frame.is_reverse() {
+            &reverse
+        } else {
+            section
+        };
+        let mut open: Option<usize> = None;
+
+        for (i, codon) in strand.codons(frame.offset()).enumerate() {
+            let position = frame.offset() + 3 * i;
+            // Codons with an ambiguous base neither open nor close an ORF.
+            match (open, codon) {
+                (None, Some(codon)) if options.starts[codon as usize] => open = Some(position),
+                (Some(start), Some(codon)) if options.code.is_stop(codon) => {
+                    let end = position + 3;
+                    if end - start >= options.min_length {
+                        let (start, end) = if frame.is_reverse() {
+                            (length - end, length - start)
+                        } else {
+                            (start, end)
+                        };
+                        orfs.push(Orf { frame, start, end });
+                    }
+                    open = None;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    orfs.sort_by_key(|orf| (orf.start, orf.end));
+    Ok(orfs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fasta::fasta_section::FastaSection, genetic_code::DEFAULT_TABLE};
+
+    fn options(starts: &str, min_length: usize) -> OrfOptions {
+        OrfOptions::new(
+            GeneticCode::from_id(DEFAULT_TABLE).unwrap(),
+            starts,
+            min_length,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_forward_orf_with_nested_start() {
+        // ATG AAA ATG TGG TAA, in frame 2.
+        let section = BinaryFastaSection::from_text("contig1", "CATGAAAATGTGGTAACC");
+        let orfs = find_orfs(&section, &options("ATG", 6)).unwrap();
+
+        assert_eq!(
+            orfs,
+            vec![Orf {
+                frame: "2".parse().unwrap(),
+                start: 1,
+                end: 16,
+            }]
+        );
+        let protein = orfs[0].protein(&section, options("ATG", 0).code).unwrap();
+        assert_eq!(FastaSection::from_basta(protein).sequence, "MKMW");
+    }
+
+    #[test]
+    fn test_reverse_orf() {
+        // Reverse complement of ATG GGG TGA
+        let section = BinaryFastaSection::from_text("contig1", "TTCACCCCATTT");
+        let orfs = find_orfs(&section, &options("ATG", 9)).unwrap();
+
+        assert_eq!(orfs.len(), 1);
+        assert_eq!((orfs[0].start, orfs[0].end), (1, 10));
+        assert!(orfs[0].frame.is_reverse());
+
+        let protein = orfs[0].protein(&section, options("ATG", 0).code).unwrap();
+        assert_eq!(FastaSection::from_basta(protein).sequence, "MG");
+    }
+
+    #[test]
+    fn test_start_codons_and_min_length() {
+        let section = BinaryFastaSection::from_text("contig1", "GTGAAATAG");
+
+        assert!(find_orfs(&section, &options("ATG", 0)).unwrap().is_empty());
+        assert_eq!(
+            find_orfs(&section, &options("ATG,GTG", 0)).unwrap().len(),
+            1
+        );
+        assert!(find_orfs(&section, &options("GTG", 12)).unwrap().is_empty());
+
+        // GTG is not a start codon of the standard code, but is one of the bacterial code.
+        assert!(find_orfs(&section, &options("table", 0))
+            .unwrap()
+            .is_empty());
+        let bacterial = OrfOptions::new(GeneticCode::from_id(11).unwrap(), "table", 0).unwrap();
+        assert_eq!(find_orfs(&section, &bacterial).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_start_codons() {
+        let code = GeneticCode::from_id(DEFAULT_TABLE).unwrap();
+        assert!(OrfOptions::new(code, "ATGC", 0).is_err());
+        assert!(OrfOptions::new(code, "ANG", 0).is_err());
+    }
+
+    #[test]
+    fn test_bed_and_gff_lines() {
+        let orf = Orf {
+            frame: "-3".parse().unwrap(),
+            start: 10,
+            end: 40,
+        };
+
+        let mut bed: Vec<u8> = Vec::new();
+        orf.write_bed(&mut bed, "chr1", "chr1_orf1").unwrap();
+        assert_eq!(bed, b"chr1\t10\t40\tchr1_orf1\t0\t-\n");
+
+        let mut gff: Vec<u8> = Vec::new();
+        orf.write_gff(&mut gff, "chr1", "chr1_orf1").unwrap();
+        assert_eq!(
+            gff,
+            b"chr1\tbinary_fasta\tORF\t11\t40\t.\t-\t0\tID=chr1_orf1;frame=-3\n"
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::errors::BinaryFastaError;
+
+// Output is written to a temporary file in the same directory as the destination,
+// and only moved into place once every byte was written. If anything fails
+// before `commit`, the temporary file is removed, so a failed or interrupted run
+// never leaves a truncated file at the destination.
+pub struct OutputFile {
+    writer: BufWriter<File>,
+    temp_path: PathBuf,
+    final_path: PathBuf,
+    overwrite: bool,
+    committed: bool,
+}
+
+impl OutputFile {
+    pub fn create(final_path: &Path, overwrite: bool) -> Result<OutputFile, BinaryFastaError> {
+        check_overwrite(final_path, overwrite)?;
+
+        let (file, temp_path) = create_temp_file(final_path)?;
+
+        Ok(OutputFile {
+            writer: BufWriter::new(file),
+            temp_path,
+            final_path: final_path.to_path_buf(),
+            overwrite,
+            committed: false,
+        })
+    }
+
+    // Flushes the data to disk and moves the temporary file to its final path.
+    pub fn commit(mut self) -> Result<(), BinaryFastaError> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_all()?;
+
+        if self.overwrite {
+            fs::rename(&self.temp_path, &self.final_path)?;
+            self.committed = true;
+            return Ok(());
+        }
+
+        // Unlike a rename, a hard link fails if the destination exists, so a file that
+        // appeared while we were writing is never replaced.
+        fs::hard_link(&self.temp_path, &self.final_path).map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => BinaryFastaError::OutputExists {
+                path: self.final_path.clone(),
+            },
+            _ => e.into(),
+        })?;
+        self.committed = true;
+        fs::remove_file(&self.temp_path)?;
+        Ok(())
+    }
+}
+
+fn check_overwrite(final_path: &Path, overwrite: bool) -> Result<(), BinaryFastaError> {
+    if !overwrite && final_path.exists() {
+        return Err(BinaryFastaError::OutputExists {
+            path: final_path.to_path_buf(),
+        });
+    }
+    Ok(())
+}
+
+// Hidden file next to the destination (so the final rename never crosses filesystems).
+fn temp_path_for(final_path: &Path, attempt: u32) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(final_path.file_name().unwrap_or_default());
+    file_name.push(format!(".{}.{}.tmp", process::id(), attempt));
+    final_path.with_file_name(file_name)
+}
+
+fn create_temp_file(final_path: &Path) -> Result<(File, PathBuf), BinaryFastaError> {
+    let mut attempt = 0;
+    loop {
+        let temp_path = temp_path_for(final_path, attempt);
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => return Ok((file, temp_path)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+// Lets writers go back to fill in a header once the data is written.
+impl Seek for OutputFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.writer.seek(pos)
+    }
+}
+
+impl Drop for OutputFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            // Nothing useful can be done if this fails, the original error is what matters.
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_path_is_hidden_sibling() {
+        let temp_path = temp_path_for(Path::new("/data/genome.basta"), 0);
+
+        assert_eq!(temp_path.parent(), Some(Path::new("/data")));
+        assert_eq!(
+            temp_path.file_name().unwrap().to_str().unwrap(),
+            format!(".genome.basta.{}.0.tmp", process::id())
+        );
+    }
+
+    #[test]
+    fn test_commit_moves_file_into_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let final_path = dir.path().join("out.fasta");
+
+        let mut output = OutputFile::create(&final_path, false).unwrap();
+        output.write_all(b">test\nACGT").unwrap();
+        output.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&final_path).unwrap(), ">test\nACGT");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_dropped_output_is_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let final_path = dir.path().join("out.fasta");
+
+        let mut output = OutputFile::create(&final_path, false).unwrap();
+        output.write_all(b">partial").unwrap();
+        drop(output);
+
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_refuses_to_overwrite_without_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let final_path = dir.path().join("out.fasta");
+        fs::write(&final_path, "original").unwrap();
+
+        assert!(matches!(
+            OutputFile::create(&final_path, false),
+            Err(BinaryFastaError::OutputExists { .. })
+        ));
+
+        let mut output = OutputFile::create(&final_path, true).unwrap();
+        output.write_all(b"replaced").unwrap();
+        output.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&final_path).unwrap(), "replaced");
+    }
+
+    #[test]
+    fn test_commit_keeps_file_created_while_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let final_path = dir.path().join("out.fasta");
+
+        let mut output = OutputFile::create(&final_path, false).unwrap();
+        output.write_all(b">test\nACGT").unwrap();
+        fs::write(&final_path, "original").unwrap();
+
+        assert!(matches!(
+            output.commit(),
+            Err(BinaryFastaError::OutputExists { .. })
+        ));
+        assert_eq!(fs::read_to_string(&final_path).unwrap(), "original");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -1,20 +1,708 @@
-use clap::{arg, command, Parser};
+use clap::{Parser, Subcommand};
+use regex::Regex;
 
-fn parse_input(s: &str) -> Result<String, String> {
-    if s.ends_with(".fa") || s.ends_with(".fasta") || s.ends_with(".ba") || s.ends_with(".basta") {
-        Ok(s.to_string())
-    } else {
-        Err(format!("Invalid input file '{}'. Input file must end with .fa/.fasta for FASTA files, or .ba/.basta for binary FASTA files.", s))
-    }
-}
+use binary_fasta::{
+    basta::metadata::MetadataEntry,
+    genetic_code::DEFAULT_TABLE,
+    merge::Duplicates,
+    motif::Pattern,
+    orf::OrfFormat,
+    region::Region,
+    sort::SortKey,
+    split::SplitFormat,
+    translate::{Frame, StopCodons},
+};
 
 #[derive(Parser, Debug)]
-#[command(version, about = "Binary FASTA encoder/decoder")]
+#[command(
+    version,
+    about = "Binary FASTA encoder/decoder",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 pub struct Args {
-    #[arg(short = 'i', long="input", required=true, value_parser = parse_input, value_name = "file to convert")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    // Without a subcommand, the direction of the conversion is inferred from the input.
+    #[arg(short = 'i', long="input", required=true, num_args = 1.., value_name = "files to convert")]
+    pub input: Vec<String>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Convert FASTA/FASTQ files to BASTA/BASTQ, whatever their extension
+    Encode(ConvertArgs),
+    /// Convert BASTA/BASTQ/2bit files to FASTA/FASTQ, whatever their extension
+    Decode(ConvertArgs),
+    /// Report the format of files based on their content
+    Detect(DetectArgs),
+    /// Reverse-complement every sequence, or the given regions
+    Revcomp(RevcompArgs),
+    /// Translate nucleotide sequences to protein
+    Translate(TranslateArgs),
+    /// Find open reading frames in all six frames, as BED or GFF
+    Orfs(OrfsArgs),
+    /// Count canonical k-mers, as a table or a histogram
+    Kmers(KmersArgs),
+    /// Find the occurrences of nucleotide patterns, as BED
+    Grep(GrepArgs),
+    /// Select sequences by name, from an ID list or with a regular expression
+    Subset(SubsetArgs),
+    /// List the sequences of BASTA files, without decoding them
+    #[command(visible_alias = "list")]
+    Info(InfoArgs),
+    /// Keep the first sequences
+    Head(HeadTailArgs),
+    /// Keep the last sequences
+    Tail(HeadTailArgs),
+    /// Keep a random sample of the sequences
+    Sample(SampleArgs),
+    /// Split the sequences of a file into several files
+    Split(SplitArgs),
+    /// Concatenate the sequences of several files into one
+    Merge(MergeArgs),
+    /// Remove sequences that are identical to an earlier one
+    Dedup(DedupArgs),
+    /// Sort sequences by name, length or sequence
+    Sort(SortArgs),
+    /// Rename sequences from a mapping file or with a regex
+    Rename(RenameArgs),
+    /// Compare the sequences of two files, whatever their format
+    Diff(DiffArgs),
+    /// Attach metadata to sequences, kept in BASTA output
+    Annotate(AnnotateArgs),
+    /// List the metadata of the sequences of a BASTA file
+    Metadata(MetadataArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DetectArgs {
+    #[arg(required = true, value_name = "files to inspect")]
+    pub inputs: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ConvertArgs {
+    #[arg(required = true, value_name = "files to convert")]
+    pub inputs: Vec<String>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RevcompArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
     pub input: String,
-    #[arg(short = 'o', long = "output", value_name = "output file")]
+    #[arg(
+        long = "region",
+        value_name = "NAME[:START-END]",
+        help = "Only reverse-complement this region (1-based, inclusive). Can be repeated"
+    )]
+    pub regions: Vec<Region>,
+    #[command(flatten)]
+    pub output: RecordOutputArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TranslateArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
+    pub input: String,
+    #[arg(
+        short = 't',
+        long = "table",
+        value_name = "NCBI table",
+        default_value_t = DEFAULT_TABLE,
+        help = "Genetic code to translate with, by its NCBI number (1 is the standard code, 11 the bacterial one)"
+    )]
+    pub table: u8,
+    #[arg(
+        long = "frame",
+        value_name = "1|2|3|-1|-2|-3",
+        allow_negative_numbers = true,
+        conflicts_with = "six_frames",
+        help = "Reading frame to translate, negative frames are on the reverse strand. Can be repeated [default: 1]"
+    )]
+    pub frames: Vec<Frame>,
+    #[arg(long = "six-frames", help = "Translate all six reading frames")]
+    pub six_frames: bool,
+    #[arg(
+        long = "stop-codons",
+        value_enum,
+        default_value_t = StopCodons::Keep,
+        help = "Keep stop codons as '*', trim a final one, truncate at the first one, or mask them as 'X'"
+    )]
+    pub stop_codons: StopCodons,
+    #[arg(
+        long = "start-as-met",
+        help = "Translate an alternative start codon at the start of the frame as M"
+    )]
+    pub start_as_met: bool,
+    #[command(flatten)]
+    pub output: RecordOutputArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct OrfsArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
+    pub input: String,
+    #[arg(
+        short = 't',
+        long = "table",
+        value_name = "NCBI table",
+        default_value_t = DEFAULT_TABLE,
+        help = "Genetic code of the stop codons (and start codons, with --start-codons table)"
+    )]
+    pub table: u8,
+    #[arg(
+        long = "start-codons",
+        value_name = "codons",
+        default_value = "ATG",
+        help = "Comma separated start codons, or 'table' for those of the genetic code"
+    )]
+    pub start_codons: String,
+    #[arg(
+        long = "min-length",
+        value_name = "nucleotides",
+        default_value_t = 75,
+        help = "Minimum ORF length, including the stop codon"
+    )]
+    pub min_length: usize,
+    #[arg(long = "format", value_enum, default_value_t = OrfFormat::Bed)]
+    pub format: OrfFormat,
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "output file",
+        help = "Write the coordinates to this file instead of stdout"
+    )]
+    pub output: Option<String>,
+    #[arg(
+        long = "proteins",
+        value_name = "protein file",
+        help = "Also write the translated ORFs, as FASTA or BASTA depending on the extension"
+    )]
+    pub proteins: Option<String>,
+    #[arg(
+        short = 'f',
+        long = "force",
+        help = "Overwrite output files that already exist"
+    )]
+    pub force: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct KmersArgs {
+    #[arg(required = true, value_name = "FASTA, BASTA or 2bit files")]
+    pub inputs: Vec<String>,
+    #[arg(
+        short = 'k',
+        value_name = "length",
+        help = "k-mer length, from 1 to 32"
+    )]
+    pub k: usize,
+    #[arg(
+        long = "histogram",
+        help = "Print how many distinct k-mers occur each number of times, instead of the counts"
+    )]
+    pub histogram: bool,
+    #[arg(
+        long = "min-count",
+        value_name = "count",
+        default_value_t = 1,
+        help = "Leave k-mers seen fewer times out of the count table or histogram"
+    )]
+    pub min_count: u64,
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "output file",
+        help = "Write the table to this file instead of stdout"
+    )]
+    pub output: Option<String>,
+    #[arg(
+        short = 'f',
+        long = "force",
+        help = "Overwrite the output file if it already exists"
+    )]
+    pub force: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct GrepArgs {
+    #[arg(required = true, value_name = "FASTA, BASTA or 2bit files")]
+    pub inputs: Vec<String>,
+    #[arg(
+        short = 'p',
+        long = "pattern",
+        value_name = "pattern",
+        required = true,
+        help = "Nucleotide pattern, with IUPAC degenerate bases allowed. Repeat it to search for several patterns"
+    )]
+    pub patterns: Vec<Pattern>,
+    #[arg(
+        long = "both-strands",
+        help = "Also search for the reverse complement of the patterns"
+    )]
+    pub both_strands: bool,
+    #[arg(
+        long = "max-mismatches",
+        value_name = "k",
+        default_value_t = 0,
+        help = "Also report matches with up to k mismatched bases, and where they are"
+    )]
+    pub max_mismatches: u32,
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "output file",
+        help = "Write the matches to this file instead of stdout"
+    )]
     pub output: Option<String>,
+    #[arg(
+        short = 'f',
+        long = "force",
+        help = "Overwrite the output file if it already exists"
+    )]
+    pub force: bool,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group(
+    clap::ArgGroup::new("selection")
+        .required(true)
+        .multiple(true)
+        .args(["ids", "names", "regex", "fields"])
+))]
+pub struct SubsetArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
+    pub input: String,
+    #[arg(
+        long = "ids",
+        value_name = "ID list file",
+        help = "File with one sequence ID per line"
+    )]
+    pub ids: Option<String>,
+    #[arg(
+        short = 'n',
+        long = "name",
+        value_name = "ID",
+        help = "Sequence ID to select. Repeat it to select several sequences"
+    )]
+    pub names: Vec<String>,
+    #[arg(
+        long = "regex",
+        value_name = "regex",
+        help = "Select the sequences whose descriptor contains a match of this regular expression"
+    )]
+    pub regex: Option<Regex>,
+    #[arg(
+        long = "field",
+        value_name = "name=value",
+        value_parser = parse_field,
+        help = "Select the sequences with this descriptor field (id, accession, entry_name, description or an attribute key like OX). Repeat it to select more"
+    )]
+    pub fields: Vec<(String, String)>,
+    #[command(flatten)]
+    pub output: RecordOutputArgs,
+}
+
+fn parse_field(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!(
+            "'{}' is not a field name and value, like OX=9606.",
+            s
+        )),
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct InfoArgs {
+    #[arg(required = true, value_name = "BASTA files")]
+    pub inputs: Vec<String>,
+    #[arg(
+        short = 's',
+        long = "summary",
+        help = "Only print the file-level information, not every sequence"
+    )]
+    pub summary: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct HeadTailArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
+    pub input: String,
+    #[arg(
+        short = 'n',
+        long = "count",
+        value_name = "sequences",
+        default_value_t = 10
+    )]
+    pub count: usize,
+    #[command(flatten)]
+    pub output: RecordOutputArgs,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group(
+    clap::ArgGroup::new("size")
+        .required(true)
+        .args(["count", "fraction"])
+))]
+pub struct SampleArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
+    pub input: String,
+    #[arg(
+        short = 'n',
+        long = "count",
+        value_name = "sequences",
+        help = "Number of sequences to keep"
+    )]
+    pub count: Option<usize>,
+    #[arg(
+        long = "fraction",
+        value_name = "fraction",
+        value_parser = parse_fraction,
+        help = "Keep each sequence with this probability, between 0 and 1"
+    )]
+    pub fraction: Option<f64>,
+    #[arg(
+        long = "seed",
+        value_name = "seed",
+        help = "Seed of the random sample, to get the same sample every time"
+    )]
+    pub seed: Option<u64>,
+    #[command(flatten)]
+    pub output: RecordOutputArgs,
+}
+
+fn parse_fraction(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(fraction) if (0.0..=1.0).contains(&fraction) => Ok(fraction),
+        _ => Err(format!("'{}' is not a fraction between 0 and 1.", s)),
+    }
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group(
+    clap::ArgGroup::new("split_by")
+        .required(true)
+        .args(["per_sequence", "sequences", "bases"])
+))]
+pub struct SplitArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
+    pub input: String,
+    #[arg(
+        long = "per-sequence",
+        help = "Write each sequence to its own file, named after its ID"
+    )]
+    pub per_sequence: bool,
+    #[arg(
+        long = "sequences",
+        value_name = "count",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Number of sequences per file"
+    )]
+    pub sequences: Option<u64>,
+    #[arg(
+        long = "bases",
+        value_name = "count",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Approximate number of bases per file. Sequences are never split"
+    )]
+    pub bases: Option<u64>,
+    #[arg(
+        short = 'd',
+        long = "output-dir",
+        value_name = "output directory",
+        default_value = "."
+    )]
+    pub output_dir: String,
+    #[arg(
+        long = "prefix",
+        value_name = "prefix",
+        help = "Start of the names of numbered files. Defaults to the name of the input file"
+    )]
+    pub prefix: Option<String>,
+    #[arg(
+        long = "format",
+        value_enum,
+        help = "Format of the output files. Defaults to BASTA for BASTA input, FASTA otherwise"
+    )]
+    pub format: Option<SplitFormat>,
+    #[arg(
+        short = 'f',
+        long = "force",
+        help = "Overwrite output files that already exist"
+    )]
+    pub force: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MergeArgs {
+    #[arg(required = true, value_name = "FASTA, BASTA or 2bit files")]
+    pub inputs: Vec<String>,
+    #[arg(
+        long = "duplicates",
+        value_enum,
+        default_value_t = Duplicates::Error,
+        help = "What to do with a sequence whose ID was already seen"
+    )]
+    pub duplicates: Duplicates,
+    #[command(flatten)]
+    pub output: RecordOutputArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DedupArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
+    pub input: String,
+    #[arg(
+        long = "by-descriptor",
+        help = "Also remove sequences whose ID was already seen"
+    )]
+    pub by_descriptor: bool,
+    #[arg(
+        short = 'm',
+        long = "map",
+        value_name = "file",
+        help = "Write a table of the removed sequences and the ones kept instead"
+    )]
+    pub map: Option<String>,
+    #[command(flatten)]
+    pub output: RecordOutputArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SortArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
+    pub input: String,
+    #[arg(
+        long = "by",
+        value_enum,
+        default_value_t = SortKey::Name,
+        help = "Sort by descriptor (chr2 before chr10), length or sequence"
+    )]
+    pub by: SortKey,
+    #[arg(short = 'r', long = "reverse", help = "Sort in descending order")]
+    pub reverse: bool,
+    #[arg(
+        long = "max-memory",
+        value_name = "size",
+        value_parser = parse_size,
+        help = "Sort in runs of at most this size (e.g. 500M or 2G), written to temporary files"
+    )]
+    pub max_memory: Option<usize>,
+    #[arg(
+        long = "temp-dir",
+        value_name = "directory",
+        help = "Directory for the temporary files of --max-memory. Defaults to the system one"
+    )]
+    pub temp_dir: Option<String>,
+    #[command(flatten)]
+    pub output: RecordOutputArgs,
+}
+
+// Sizes in bytes, or with a K, M or G suffix (powers of 1024).
+fn parse_size(s: &str) -> Result<usize, String> {
+    let (number, unit) = match s.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&s[..i], 1 << 10),
+        Some((i, 'M' | 'm')) => (&s[..i], 1 << 20),
+        Some((i, 'G' | 'g')) => (&s[..i], 1 << 30),
+        _ => (s, 1),
+    };
+    match number
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit))
+    {
+        Some(size) if size > 0 => Ok(size),
+        _ => Err(format!("'{}' is not a size like 1000, 500K, 64M or 2G.", s)),
+    }
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group(
+    clap::ArgGroup::new("rename_by")
+        .required(true)
+        .args(["map", "regex", "template"])
+))]
+pub struct RenameArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
+    pub input: String,
+    #[arg(
+        short = 'm',
+        long = "map",
+        value_name = "file",
+        help = "File of old and new sequence IDs, two columns separated by whitespace"
+    )]
+    pub map: Option<String>,
+    #[arg(
+        long = "regex",
+        value_name = "regex",
+        requires = "replacement",
+        help = "Regular expression to replace in each descriptor"
+    )]
+    pub regex: Option<Regex>,
+    #[arg(
+        long = "replace",
+        value_name = "replacement",
+        requires = "regex",
+        help = "Replacement for every match of --regex, where $1 is the first group"
+    )]
+    pub replacement: Option<String>,
+    #[arg(
+        long = "template",
+        value_name = "template",
+        help = "New descriptor, where {name} is a field of the old one, e.g. '{accession} {description}'"
+    )]
+    pub template: Option<String>,
+    #[command(flatten)]
+    pub output: RecordOutputArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    #[arg(value_name = "first FASTA, BASTA or 2bit file")]
+    pub first: String,
+    #[arg(value_name = "second FASTA, BASTA or 2bit file")]
+    pub second: String,
+    #[arg(
+        long = "mismatches",
+        value_name = "count",
+        default_value_t = 10,
+        help = "Number of mismatches to list for each sequence"
+    )]
+    pub mismatches: usize,
+    #[arg(
+        short = 'a',
+        long = "all-mismatches",
+        conflicts_with = "mismatches",
+        help = "List every mismatch"
+    )]
+    pub all_mismatches: bool,
+    #[arg(
+        short = 'q',
+        long = "quiet",
+        conflicts_with = "output",
+        help = "Print nothing, only exit with 1 if the files differ"
+    )]
+    pub quiet: bool,
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "output file",
+        help = "File to write the differences to. Prints them to stdout if omitted"
+    )]
+    pub output: Option<String>,
+    #[arg(
+        short = 'f',
+        long = "force",
+        help = "Overwrite the output file if it already exists"
+    )]
+    pub force: bool,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group(
+    clap::ArgGroup::new("annotate_with")
+        .required(true)
+        .multiple(true)
+        .args(["entries", "table", "clear"])
+))]
+pub struct AnnotateArgs {
+    #[arg(value_name = "FASTA, BASTA or 2bit file")]
+    pub input: String,
+    #[arg(
+        long = "set",
+        value_name = "key=value",
+        value_parser = parse_metadata,
+        help = "Metadata entry to add: accession, taxid, topology (linear or circular), assembly or any other key. Can be repeated"
+    )]
+    pub entries: Vec<MetadataEntry>,
+    #[arg(
+        short = 'n',
+        long = "name",
+        value_name = "ID",
+        requires = "entries",
+        help = "Only add the --set entries to this sequence. Can be repeated"
+    )]
+    pub names: Vec<String>,
+    #[arg(
+        long = "table",
+        value_name = "file",
+        help = "File of sequence IDs, keys and values, three columns separated by tabs"
+    )]
+    pub table: Option<String>,
+    #[arg(
+        long = "clear",
+        help = "Remove the existing metadata of every sequence first"
+    )]
+    pub clear: bool,
+    #[command(flatten)]
+    pub output: RecordOutputArgs,
+}
+
+fn parse_metadata(s: &str) -> Result<MetadataEntry, String> {
+    let Some((key, value)) = s.split_once('=') else {
+        return Err(format!(
+            "'{}' is not a key and value, like topology=circular.",
+            s
+        ));
+    };
+    MetadataEntry::parse(key, value).map_err(|e| e.to_string())
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MetadataArgs {
+    #[arg(value_name = "BASTA file")]
+    pub input: String,
+}
+
+// Output of the subcommands that write sequences, rather than convert files.
+#[derive(clap::Args, Debug)]
+pub struct RecordOutputArgs {
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "output file",
+        help = "Output file, in the format given by its extension. Prints FASTA to stdout if omitted"
+    )]
+    pub output: Option<String>,
+    #[arg(
+        short = 'f',
+        long = "force",
+        help = "Overwrite the output file if it already exists"
+    )]
+    pub force: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct OutputArgs {
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "output file",
+        conflicts_with = "output_dir"
+    )]
+    pub output: Option<String>,
+    #[arg(long = "output-dir", value_name = "output directory")]
+    pub output_dir: Option<String>,
+    #[arg(
+        short = 'r',
+        long = "recursive",
+        help = "Convert every FASTA/FASTQ/BASTA/BASTQ file inside input directories"
+    )]
+    pub recursive: bool,
+    #[arg(
+        short = 'f',
+        long = "force",
+        help = "Overwrite output files that already exist"
+    )]
+    pub force: bool,
 }
 
 #[cfg(test)]
@@ -26,15 +714,521 @@ mod tests {
     fn parses_with_input_and_output() {
         let args = Args::parse_from(["test-bin", "-i", "input.fa", "-o", "output.ba"]);
 
-        assert_eq!(args.input, "input.fa");
-        assert_eq!(args.output.as_deref(), Some("output.ba"));
+        assert_eq!(args.input, vec!["input.fa"]);
+        assert_eq!(args.output.output.as_deref(), Some("output.ba"));
     }
 
     #[test]
     fn parses_with_input_only_and_no_output() {
         let args = Args::parse_from(["test-bin", "--input", "sequence.fasta"]);
 
-        assert_eq!(args.input, "sequence.fasta");
-        assert!(args.output.is_none());
+        assert_eq!(args.input, vec!["sequence.fasta"]);
+        assert!(args.output.output.is_none());
+    }
+
+    #[test]
+    fn parses_multiple_inputs_with_output_dir() {
+        let args = Args::parse_from([
+            "test-bin",
+            "-i",
+            "gene1.fa",
+            "gene2.fa",
+            "--output-dir",
+            "converted",
+        ]);
+
+        assert_eq!(args.input, vec!["gene1.fa", "gene2.fa"]);
+        assert_eq!(args.output.output_dir.as_deref(), Some("converted"));
+        assert!(!args.output.recursive);
+    }
+
+    #[test]
+    fn rejects_output_with_output_dir() {
+        let result = Args::try_parse_from([
+            "test-bin",
+            "-i",
+            "gene1.fa",
+            "-o",
+            "gene1.ba",
+            "--output-dir",
+            "converted",
+        ]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parses_encode_subcommand() {
+        let args = Args::parse_from(["test-bin", "encode", "gene1.fna", "gene2.fna", "--force"]);
+
+        match args.command {
+            Some(Command::Encode(convert)) => {
+                assert_eq!(convert.inputs, vec!["gene1.fna", "gene2.fna"]);
+                assert!(convert.output.force);
+            }
+            _ => panic!("expected the encode subcommand"),
+        }
+    }
+
+    #[test]
+    fn parses_detect_subcommand() {
+        let args = Args::parse_from(["test-bin", "detect", "a.fa", "b.dat"]);
+
+        match args.command {
+            Some(Command::Detect(detect)) => assert_eq!(detect.inputs, vec!["a.fa", "b.dat"]),
+            _ => panic!("expected the detect subcommand"),
+        }
+    }
+
+    #[test]
+    fn parses_decode_subcommand() {
+        let args = Args::parse_from(["test-bin", "decode", "genome.bin", "-o", "genome.fa"]);
+
+        match args.command {
+            Some(Command::Decode(convert)) => {
+                assert_eq!(convert.inputs, vec!["genome.bin"]);
+                assert_eq!(convert.output.output.as_deref(), Some("genome.fa"));
+            }
+            _ => panic!("expected the decode subcommand"),
+        }
+    }
+
+    #[test]
+    fn parses_revcomp_with_regions() {
+        let args = Args::parse_from([
+            "test-bin",
+            "revcomp",
+            "genome.basta",
+            "--region",
+            "chr1:101-200",
+            "--region",
+            "chr2",
+            "-o",
+            "minus.fasta",
+        ]);
+
+        let Some(Command::Revcomp(args)) = args.command else {
+            panic!("expected the revcomp subcommand");
+        };
+        assert_eq!(args.input, "genome.basta");
+        assert_eq!(args.regions.len(), 2);
+        assert_eq!(args.regions[0].range, Some((100, 200)));
+        assert_eq!(args.output.output.as_deref(), Some("minus.fasta"));
+    }
+
+    #[test]
+    fn rejects_invalid_region() {
+        let result = Args::try_parse_from([
+            "test-bin",
+            "revcomp",
+            "genome.basta",
+            "--region",
+            "chr1:0-5",
+        ]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parses_translate_options() {
+        let args = Args::parse_from([
+            "test-bin",
+            "translate",
+            "genes.fna",
+            "--table",
+            "11",
+            "--frame",
+            "1",
+            "--frame",
+            "-2",
+            "--stop-codons",
+            "truncate",
+        ]);
+
+        let Some(Command::Translate(args)) = args.command else {
+            panic!("expected the translate subcommand");
+        };
+        assert_eq!(args.table, 11);
+        assert_eq!(
+            args.frames,
+            vec!["1".parse().unwrap(), "-2".parse().unwrap()]
+        );
+        assert_eq!(args.stop_codons, StopCodons::Truncate);
+        assert!(!args.six_frames);
+    }
+
+    #[test]
+    fn translate_defaults_to_standard_code() {
+        let args = Args::parse_from(["test-bin", "translate", "genes.fna"]);
+
+        let Some(Command::Translate(args)) = args.command else {
+            panic!("expected the translate subcommand");
+        };
+        assert_eq!(args.table, 1);
+        assert!(args.frames.is_empty());
+        assert_eq!(args.stop_codons, StopCodons::Keep);
+    }
+
+    #[test]
+    fn parses_orfs_options() {
+        let args = Args::parse_from([
+            "test-bin",
+            "orfs",
+            "contigs.fna",
+            "--start-codons",
+            "ATG,GTG",
+            "--min-length",
+            "300",
+            "--format",
+            "gff",
+            "--proteins",
+            "orfs.faa",
+        ]);
+
+        let Some(Command::Orfs(args)) = args.command else {
+            panic!("expected the orfs subcommand");
+        };
+        assert_eq!(args.start_codons, "ATG,GTG");
+        assert_eq!(args.min_length, 300);
+        assert_eq!(args.format, OrfFormat::Gff);
+        assert_eq!(args.proteins.as_deref(), Some("orfs.faa"));
+        assert_eq!(args.table, 1);
+    }
+
+    #[test]
+    fn parses_kmers_options() {
+        let args = Args::parse_from(["test-bin", "kmers", "a.fasta", "b.basta", "-k", "21"]);
+
+        let Some(Command::Kmers(args)) = args.command else {
+            panic!("expected the kmers subcommand");
+        };
+        assert_eq!(args.inputs, vec!["a.fasta", "b.basta"]);
+        assert_eq!(args.k, 21);
+        assert!(!args.histogram);
+        assert_eq!(args.min_count, 1);
+    }
+
+    #[test]
+    fn parses_grep_patterns() {
+        let args = Args::parse_from([
+            "test-bin",
+            "grep",
+            "genome.basta",
+            "-p",
+            "GAATTC",
+            "--pattern",
+            "GGNCC",
+            "--both-strands",
+        ]);
+
+        let Some(Command::Grep(args)) = args.command else {
+            panic!("expected the grep subcommand");
+        };
+        let patterns: Vec<String> = args.patterns.iter().map(|p| p.to_string()).collect();
+        assert_eq!(patterns, vec!["GAATTC", "GGNCC"]);
+        assert!(args.both_strands);
+        assert_eq!(args.max_mismatches, 0);
+    }
+
+    #[test]
+    fn parses_subset_selection() {
+        let args = Args::parse_from([
+            "test-bin",
+            "subset",
+            "pangenome.basta",
+            "--ids",
+            "genes.txt",
+            "-n",
+            "geneA",
+            "-n",
+            "geneB",
+            "--regex",
+            "^sp\\|",
+        ]);
+
+        let Some(Command::Subset(args)) = args.command else {
+            panic!("expected the subset subcommand");
+        };
+        assert_eq!(args.ids.as_deref(), Some("genes.txt"));
+        assert_eq!(args.names, vec!["geneA", "geneB"]);
+        assert_eq!(args.regex.unwrap().as_str(), "^sp\\|");
+    }
+
+    #[test]
+    fn subset_requires_a_selection() {
+        assert!(Args::try_parse_from(["test-bin", "subset", "pangenome.basta"]).is_err());
+        assert!(
+            Args::try_parse_from(["test-bin", "subset", "pangenome.basta", "--regex", "("])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_info_and_its_list_alias() {
+        for command in ["info", "list"] {
+            let args = Args::parse_from(["test-bin", command, "genome.basta", "--summary"]);
+
+            let Some(Command::Info(args)) = args.command else {
+                panic!("expected the info subcommand");
+            };
+            assert_eq!(args.inputs, vec!["genome.basta"]);
+            assert!(args.summary);
+        }
+    }
+
+    #[test]
+    fn parses_head_and_sample() {
+        let args = Args::parse_from(["test-bin", "head", "genome.basta"]);
+        let Some(Command::Head(args)) = args.command else {
+            panic!("expected the head subcommand");
+        };
+        assert_eq!(args.count, 10);
+
+        let args = Args::parse_from([
+            "test-bin",
+            "sample",
+            "genome.basta",
+            "--fraction",
+            "0.25",
+            "--seed",
+            "42",
+        ]);
+        let Some(Command::Sample(args)) = args.command else {
+            panic!("expected the sample subcommand");
+        };
+        assert_eq!(args.fraction, Some(0.25));
+        assert_eq!(args.count, None);
+        assert_eq!(args.seed, Some(42));
+    }
+
+    #[test]
+    fn sample_requires_one_size() {
+        let sample = |extra: &[&str]| {
+            let mut args = vec!["test-bin", "sample", "genome.basta"];
+            args.extend_from_slice(extra);
+            Args::try_parse_from(args)
+        };
+
+        assert!(sample(&[]).is_err());
+        assert!(sample(&["-n", "5", "--fraction", "0.5"]).is_err());
+        assert!(sample(&["--fraction", "1.5"]).is_err());
+        assert!(sample(&["-n", "5"]).is_ok());
+    }
+
+    #[test]
+    fn parses_split_options() {
+        let args = Args::parse_from([
+            "test-bin",
+            "split",
+            "genome.basta",
+            "--bases",
+            "1000000",
+            "-d",
+            "chunks",
+            "--format",
+            "2bit",
+        ]);
+
+        let Some(Command::Split(args)) = args.command else {
+            panic!("expected the split subcommand");
+        };
+        assert_eq!(args.bases, Some(1_000_000));
+        assert_eq!(args.output_dir, "chunks");
+        assert_eq!(args.format, Some(SplitFormat::TwoBit));
+        assert!(!args.per_sequence);
+
+        assert!(Args::try_parse_from(["test-bin", "split", "genome.basta"]).is_err());
+        assert!(
+            Args::try_parse_from(["test-bin", "split", "genome.basta", "--sequences", "0"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_merge_options() {
+        let args = Args::parse_from(["test-bin", "merge", "a.fasta", "b.basta", "-o", "all.basta"]);
+        let Some(Command::Merge(args)) = args.command else {
+            panic!("expected the merge subcommand");
+        };
+        assert_eq!(args.inputs, vec!["a.fasta", "b.basta"]);
+        assert_eq!(args.duplicates, Duplicates::Error);
+
+        let args = Args::parse_from(["test-bin", "merge", "a.fasta", "--duplicates", "rename"]);
+        let Some(Command::Merge(args)) = args.command else {
+            panic!("expected the merge subcommand");
+        };
+        assert_eq!(args.duplicates, Duplicates::Rename);
+    }
+
+    #[test]
+    fn parses_dedup_options() {
+        let args = Args::parse_from([
+            "test-bin",
+            "dedup",
+            "amplicons.basta",
+            "--by-descriptor",
+            "-m",
+            "map.tsv",
+        ]);
+        let Some(Command::Dedup(args)) = args.command else {
+            panic!("expected the dedup subcommand");
+        };
+        assert!(args.by_descriptor);
+        assert_eq!(args.map.as_deref(), Some("map.tsv"));
+        assert_eq!(args.output.output, None);
+    }
+
+    #[test]
+    fn parses_sort_options() {
+        let args = Args::parse_from([
+            "test-bin",
+            "sort",
+            "genome.fasta",
+            "--by",
+            "length",
+            "-r",
+            "--max-memory",
+            "64M",
+        ]);
+        let Some(Command::Sort(args)) = args.command else {
+            panic!("expected the sort subcommand");
+        };
+        assert_eq!(args.by, SortKey::Length);
+        assert!(args.reverse);
+        assert_eq!(args.max_memory, Some(64 << 20));
+    }
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("1000"), Ok(1000));
+        assert_eq!(parse_size("500K"), Ok(500 << 10));
+        assert_eq!(parse_size("2g"), Ok(2 << 30));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("1.5G").is_err());
+    }
+
+    #[test]
+    fn parses_rename_options() {
+        let args = Args::parse_from([
+            "test-bin",
+            "rename",
+            "genome.basta",
+            "--regex",
+            "^",
+            "--replace",
+            "chr",
+        ]);
+        let Some(Command::Rename(args)) = args.command else {
+            panic!("expected the rename subcommand");
+        };
+        assert_eq!(
+            args.regex.map(|regex| regex.to_string()),
+            Some(String::from("^"))
+        );
+        assert_eq!(args.replacement.as_deref(), Some("chr"));
+
+        let rename = |extra: &[&str]| {
+            let mut args = vec!["test-bin", "rename", "genome.basta"];
+            args.extend_from_slice(extra);
+            Args::try_parse_from(args)
+        };
+        assert!(rename(&[]).is_err());
+        assert!(rename(&["--regex", "^"]).is_err());
+        assert!(rename(&["--map", "names.tsv", "--regex", "^", "--replace", "chr"]).is_err());
+        assert!(rename(&["-m", "names.tsv"]).is_ok());
+        assert!(rename(&["--template", "{accession}"]).is_ok());
+    }
+
+    #[test]
+    fn parses_subset_fields() {
+        let args = Args::parse_from([
+            "test-bin",
+            "subset",
+            "uniprot.basta",
+            "--field",
+            "OX=9606",
+            "--field",
+            "description=",
+        ]);
+        let Some(Command::Subset(args)) = args.command else {
+            panic!("expected the subset subcommand");
+        };
+        assert_eq!(
+            args.fields,
+            vec![
+                (String::from("OX"), String::from("9606")),
+                (String::from("description"), String::new())
+            ]
+        );
+        assert!(parse_field("=9606").is_err());
+        assert!(parse_field("OX").is_err());
+    }
+
+    #[test]
+    fn parses_annotate_options() {
+        let args = Args::parse_from([
+            "test-bin",
+            "annotate",
+            "genome.basta",
+            "--set",
+            "topology=circular",
+            "--set",
+            "note=from RefSeq",
+            "-n",
+            "chrM",
+        ]);
+        let Some(Command::Annotate(args)) = args.command else {
+            panic!("expected the annotate subcommand");
+        };
+        assert_eq!(
+            args.entries,
+            vec![
+                MetadataEntry::parse("topology", "circular").unwrap(),
+                MetadataEntry::parse("note", "from RefSeq").unwrap()
+            ]
+        );
+        assert_eq!(args.names, vec!["chrM"]);
+
+        let annotate = |extra: &[&str]| {
+            let mut args = vec!["test-bin", "annotate", "genome.basta"];
+            args.extend_from_slice(extra);
+            Args::try_parse_from(args)
+        };
+        assert!(annotate(&[]).is_err());
+        assert!(annotate(&["--set", "taxid=human"]).is_err());
+        assert!(annotate(&["--set", "topology"]).is_err());
+        assert!(annotate(&["--table", "metadata.tsv", "-n", "chrM"]).is_err());
+        assert!(annotate(&["--table", "metadata.tsv", "--clear"]).is_ok());
+    }
+
+    #[test]
+    fn parses_diff_options() {
+        let args = Args::parse_from(["test-bin", "diff", "old.fasta", "new.basta", "-a"]);
+        let Some(Command::Diff(args)) = args.command else {
+            panic!("expected the diff subcommand");
+        };
+        assert_eq!(args.first, "old.fasta");
+        assert_eq!(args.second, "new.basta");
+        assert!(args.all_mismatches);
+        assert_eq!(args.mismatches, 10);
+
+        assert!(Args::try_parse_from(["test-bin", "diff", "old.fasta"]).is_err());
+        assert!(Args::try_parse_from([
+            "test-bin",
+            "diff",
+            "old.fasta",
+            "new.basta",
+            "-a",
+            "--mismatches",
+            "5"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_grep_pattern() {
+        let res = Args::try_parse_from(["test-bin", "grep", "genome.basta", "-p", "GAZ"]);
+        assert!(res.is_err());
     }
 }
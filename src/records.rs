@@ -0,0 +1,183 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    basta::{binary_fasta_data, binary_fasta_section::BinaryFastaSection},
+    batch,
+    errors::BinaryFastaError,
+    fasta::fasta_data,
+    nucleotide_file::{FileFormat, NucleotideFile},
+    output_file::OutputFile,
+    twobit::two_bit_data,
+};
+
+// Subcommands that work on sequences (rather than converting files) read any
+// FASTA, BASTA or 2bit file as packed BASTA sections, and write them back out in
+// the format given by the extension of the output file.
+pub type Sections = Box<dyn Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>>;
+
+pub fn read(file_path: &Path) -> Result<Sections, BinaryFastaError> {
+    let input_file = NucleotideFile::detect(file_path)?;
+
+    match input_file.format {
+        FileFormat::Fasta => {
+            let read_fasta_iter = fasta_data::read(file_path)?;
+            Ok(Box::new(binary_fasta_data::from_fasta(read_fasta_iter)))
+        }
+        FileFormat::Basta => Ok(Box::new(binary_fasta_data::read(file_path)?)),
+        FileFormat::TwoBit => {
+            let read_two_bit_iter = two_bit_data::read(file_path)?;
+            Ok(Box::new(binary_fasta_data::from_two_bit(read_two_bit_iter)))
+        }
+        format @ (FileFormat::Fastq | FileFormat::Bastq) => {
+            Err(BinaryFastaError::UnsupportedInput {
+                path: file_path.to_path_buf(),
+                format,
+            })
+        }
+    }
+}
+
+// BASTA files can be looked through from their section headers, without reading sequences.
+pub fn is_basta(file_path: &Path) -> Result<bool, BinaryFastaError> {
+    Ok(NucleotideFile::detect(file_path)?.format == FileFormat::Basta)
+}
+
+// Like `read`, but only yields the sections whose descriptor is selected. BASTA files
+// skip the other sections without reading their sequence.
+pub fn read_selected<F>(file_path: &Path, mut select: F) -> Result<Sections, BinaryFastaError>
+where
+    F: FnMut(&str) -> bool + 'static,
+{
+    if is_basta(file_path)? {
+        return Ok(Box::new(binary_fasta_data::read_selected(
+            file_path, select,
+        )?));
+    }
+
+    Ok(Box::new(read(file_path)?.filter(move |res| match res {
+        Ok(section) => select(&section.descriptor),
+        Err(_) => true,
+    })))
+}
+
+// Without an output file, the sequences are printed to stdout as FASTA.
+pub fn write<I>(iter: I, output: Option<&Path>, overwrite: bool) -> Result<(), BinaryFastaError>
+where
+    I: Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>,
+{
+    let Some(output_path) = output else {
+        let mut stdout = BufWriter::new(io::stdout().lock());
+        fasta_data::write_to(fasta_data::from_basta(iter), &mut stdout)?;
+        stdout.flush()?;
+        return Ok(());
+    };
+
+    let mut output = SectionOutput::create(output_path, overwrite)?;
+    output.write(iter)?;
+    output.commit()
+}
+
+// An output file for sections, in the format given by its extension. It is created
+// before anything is written, so that a subcommand with several outputs can create
+// them all before writing any, and commit them once all were written.
+pub struct SectionOutput {
+    file: OutputFile,
+    format: FileFormat,
+}
+
+impl SectionOutput {
+    pub fn create(output_path: &Path, overwrite: bool) -> Result<Self, BinaryFastaError> {
+        let format = output_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(FileFormat::from_extension);
+        let Some(format @ (FileFormat::Fasta | FileFormat::Basta | FileFormat::TwoBit)) = format
+        else {
+            return Err(BinaryFastaError::UnsupportedOutputExtension {
+                path: output_path.to_path_buf(),
+            });
+        };
+
+        batch::create_parent_dir(output_path)?;
+        Ok(SectionOutput {
+            file: OutputFile::create(output_path, overwrite)?,
+            format,
+        })
+    }
+
+    pub fn write<I>(&mut self, iter: I) -> Result<(), BinaryFastaError>
+    where
+        I: Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>,
+    {
+        match self.format {
+            FileFormat::Fasta => fasta_data::write_to(fasta_data::from_basta(iter), &mut self.file),
+            FileFormat::Basta => binary_fasta_data::write_to(iter, &mut self.file),
+            FileFormat::TwoBit => {
+                two_bit_data::write_to(two_bit_data::from_basta(iter), &mut self.file)
+            }
+            FileFormat::Fastq | FileFormat::Bastq => unreachable!(),
+        }
+    }
+
+    pub fn commit(self) -> Result<(), BinaryFastaError> {
+        self.file.commit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_fasta_and_basta_alike() {
+        let dir = tempfile::tempdir().unwrap();
+        let basta_path = dir.path().join("small_dna.basta");
+
+        let fasta_iter = read(Path::new("tests/data/small_dna.fasta")).unwrap();
+        write(fasta_iter, Some(&basta_path), false).unwrap();
+
+        let basta_sections: Vec<_> = read(&basta_path)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let fasta_sections: Vec<_> = read(Path::new("tests/data/small_dna.fasta"))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(basta_sections, fasta_sections);
+    }
+
+    #[test]
+    fn test_read_selected_fasta_and_basta_alike() {
+        let dir = tempfile::tempdir().unwrap();
+        let basta_path = dir.path().join("small_protein.basta");
+        let fasta_path = Path::new("tests/data/small_protein.fasta");
+        write(read(fasta_path).unwrap(), Some(&basta_path), false).unwrap();
+
+        let ids = |path: &Path| -> Vec<String> {
+            read_selected(path, |descriptor| !descriptor.starts_with("sp|P69905"))
+                .unwrap()
+                .map(|res| res.unwrap().id().to_string())
+                .collect()
+        };
+        assert_eq!(ids(&basta_path), ids(fasta_path));
+        assert_eq!(ids(fasta_path).len(), 2);
+    }
+
+    #[test]
+    fn test_rejects_reads_and_unknown_extensions() {
+        assert!(matches!(
+            read(Path::new("tests/data/small_reads.fastq")),
+            Err(BinaryFastaError::UnsupportedInput { .. })
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            write(std::iter::empty(), Some(&dir.path().join("out.txt")), false),
+            Err(BinaryFastaError::UnsupportedOutputExtension { .. })
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+use std::{collections::VecDeque, fmt, str::FromStr};
+
+use crate::{basta::binary_fasta_section::BinaryFastaSection, errors::BinaryFastaError};
+
+// A sequence, or part of one, written like samtools regions: NAME for the whole
+// sequence, or NAME:START-END with 1-based inclusive coordinates.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Region {
+    pub name: String,
+    // 0-based, end exclusive. None for the whole sequence.
+    pub range: Option<(usize, usize)>,
+}
+
+impl Region {
+    // The 0-based range of the region in a sequence of the given length.
+    pub fn bounds(&self, length: usize) -> Result<(usize, usize), BinaryFastaError> {
+        match self.range {
+            None => Ok((0, length)),
+            Some((_, end)) if end > length => Err(BinaryFastaError::InvalidRegion {
+                region: self.to_string(),
+            }),
+            Some(range) => Ok(range),
+        }
+    }
+}
+
+impl FromStr for Region {
+    type Err = BinaryFastaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || BinaryFastaError::InvalidRegion {
+            region: s.to_string(),
+        };
+
+        // Sequence names may contain ':', as in "HLA:A", so the whole string is a name
+        // unless what follows the last ':' is a START-END range.
+        let range = s.rsplit_once(':').and_then(|(name, range)| {
+            let (start, end) = range.split_once('-')?;
+            let start: usize = start.replace(',', "").parse().ok()?;
+            let end: usize = end.replace(',', "").parse().ok()?;
+            Some((name, start, end))
+        });
+        let Some((name, start, end)) = range else {
+            if s.is_empty() {
+                return Err(invalid());
+            }
+            return Ok(Region {
+                name: s.to_string(),
+                range: None,
+            });
+        };
+
+        if name.is_empty() || start == 0 || start > end {
+            return Err(invalid());
+        }
+
+        Ok(Region {
+            name: name.to_string(),
+            range: Some((start - 1, end)),
+        })
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.range {
+            None => write!(f, "{}", self.name),
+            Some((start, end)) => write!(f, "{}:{}-{}", self.name, start + 1, end),
+        }
+    }
+}
+
+// Cuts the regions out of the sections, in the order the sections appear in the
+// file. Regions are matched against the sequence ID (the descriptor up to the first
+// whitespace), and partial regions are named after the region, like samtools does.
+pub fn extract<I>(
+    sections: I,
+    regions: Vec<Region>,
+) -> impl Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>
+where
+    I: Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>,
+{
+    let mut sections = sections;
+    let mut found = vec![false; regions.len()];
+    let mut pending: VecDeque<Result<BinaryFastaSection, BinaryFastaError>> = VecDeque::new();
+    let mut done = false;
+
+    std::iter::from_fn(move || loop {
+        if let Some(next) = pending.pop_front() {
+            return Some(next);
+        }
+        if done {
+            return None;
+        }
+
+        match sections.next() {
+            Some(Ok(section)) => {
+                for (i, region) in regions.iter().enumerate() {
+                    if region.name != section.id() {
+                        continue;
+                    }
+                    found[i] = true;
+                    pending.push_back(region.bounds(section.length()).map(|(start, end)| {
+                        let mut extracted = section.subsequence(start, end);
+                        if region.range.is_some() {
+                            extracted.descriptor = region.to_string();
+                        }
+                        extracted
+                    }));
+                }
+            }
+            Some(Err(e)) => return Some(Err(e)),
+            None => {
+                done = true;
+                // Names are only known to be missing once the whole file was read.
+                if let Some(i) = found.iter().position(|&found| !found) {
+                    return Some(Err(BinaryFastaError::SequenceNotFound {
+                        name: regions[i].name.clone(),
+                    }));
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fasta::fasta_section::FastaSection;
+
+    #[test]
+    fn test_parse_regions() {
+        assert_eq!(
+            "chr1:101-200".parse::<Region>().unwrap(),
+            Region {
+                name: String::from("chr1"),
+                range: Some((100, 200)),
+            }
+        );
+        assert_eq!(
+            "chr1:1,001-2,000".parse::<Region>().unwrap().range,
+            Some((1000, 2000))
+        );
+        assert_eq!(
+            "HLA:A:1-5".parse::<Region>().unwrap().name,
+            String::from("HLA:A")
+        );
+        assert_eq!("chrM".parse::<Region>().unwrap().range, None);
+
+        // Without a range after the last ':', the whole string is the name.
+        for name in ["HLA:A", "chr1:a-b", "chr1:10", "chr1:"] {
+            assert_eq!(
+                name.parse::<Region>().unwrap(),
+                Region {
+                    name: String::from(name),
+                    range: None,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_regions() {
+        for region in ["", "chr1:0-10", "chr1:20-10", ":1-10"] {
+            assert!(region.parse::<Region>().is_err(), "{}", region);
+        }
+    }
+
+    #[test]
+    fn test_display_and_bounds() {
+        let region: Region = "chr1:101-200".parse().unwrap();
+        assert_eq!(region.to_string(), "chr1:101-200");
+        assert_eq!(region.bounds(200).unwrap(), (100, 200));
+        assert!(region.bounds(150).is_err());
+
+        let whole: Region = "chr1".parse().unwrap();
+        assert_eq!(whole.bounds(150).unwrap(), (0, 150));
+    }
+
+    fn sections() -> Vec<Result<BinaryFastaSection, BinaryFastaError>> {
+        [("chr1 first", "GATTACAGGCAT"), ("chr2", "ACGT")]
+            .into_iter()
+            .map(|(descriptor, sequence)| Ok(BinaryFastaSection::from_text(descriptor, sequence)))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_regions() {
+        let regions = vec![
+            "chr2".parse().unwrap(),
+            "chr1:2-5".parse().unwrap(),
+            "chr1:12-12".parse().unwrap(),
+        ];
+        let extracted: Vec<FastaSection> = extract(sections().into_iter(), regions)
+            .map(|res| FastaSection::from_basta(res.unwrap()))
+            .collect();
+
+        let descriptors: Vec<&str> = extracted.iter().map(|s| s.descriptor.as_str()).collect();
+        let sequences: Vec<&str> = extracted.iter().map(|s| s.sequence.as_str()).collect();
+        assert_eq!(descriptors, vec!["chr1:2-5", "chr1:12-12", "chr2"]);
+        assert_eq!(sequences, vec!["ATTA", "T", "ACGT"]);
+    }
+
+    #[test]
+    fn test_extract_missing_or_out_of_range() {
+        let missing = extract(sections().into_iter(), vec!["chr3".parse().unwrap()]);
+        assert!(matches!(
+            missing.last(),
+            Some(Err(BinaryFastaError::SequenceNotFound { .. }))
+        ));
+
+        let mut too_long = extract(sections().into_iter(), vec!["chr2:1-5".parse().unwrap()]);
+        assert!(matches!(
+            too_long.next(),
+            Some(Err(BinaryFastaError::InvalidRegion { .. }))
+        ));
+    }
+}
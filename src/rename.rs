@@ -0,0 +1,168 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use regex::Regex;
+
+use crate::{
+    basta::binary_fasta_section::BinaryFastaSection, descriptor::Descriptor,
+    errors::BinaryFastaError,
+};
+
+// New descriptors for sections. Only the descriptor changes, the packed sequence is
+// passed on as it is.
+#[derive(Debug)]
+pub enum Renamer {
+    // New sequence IDs by old ID. The rest of the descriptor is kept, and sections
+    // whose ID is not in the map are left as they are.
+    Map(HashMap<String, String>),
+    // Replaces every match of the regex in the whole descriptor. The replacement can
+    // refer to capture groups as $1 or ${name}.
+    Regex { regex: Regex, replacement: String },
+    // A new descriptor, where "{name}" stands for a field of the old one (see
+    // `Descriptor::field`), as in "{accession} {description}".
+    Template(String),
+}
+
+impl Renamer {
+    pub fn read_map(path: &Path) -> Result<Renamer, BinaryFastaError> {
+        let text = fs::read_to_string(path)?;
+        let map = parse_map(&text).map_err(|line| BinaryFastaError::InvalidRenameMap {
+            path: path.to_path_buf(),
+            line,
+        })?;
+        Ok(Renamer::Map(map))
+    }
+
+    pub fn rename(&self, section: &mut BinaryFastaSection) -> Result<(), BinaryFastaError> {
+        match self {
+            Renamer::Map(map) => {
+                if let Some(id) = map.get(section.id()) {
+                    section.set_id(id);
+                }
+            }
+            Renamer::Regex { regex, replacement } => {
+                let descriptor = regex.replace_all(&section.descriptor, replacement.as_str());
+                section.descriptor = descriptor.into_owned();
+            }
+            Renamer::Template(template) => {
+                section.descriptor = fill_template(template, &section.descriptor)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// A '{' without a closing '}' is kept as it is.
+fn fill_template(template: &str, descriptor: &str) -> Result<String, BinaryFastaError> {
+    let parsed = Descriptor::parse(descriptor);
+    let mut filled = String::new();
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|i| open + i) else {
+            break;
+        };
+        let name = &rest[open + 1..close];
+        let value = parsed
+            .field(name)
+            .ok_or_else(|| BinaryFastaError::MissingDescriptorField {
+                field: name.to_string(),
+                descriptor: descriptor.to_string(),
+            })?;
+        filled.push_str(&rest[..open]);
+        filled.push_str(value);
+        rest = &rest[close + 1..];
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+// Two columns separated by whitespace (tabs, as in UCSC and Ensembl alias tables, or
+// spaces): the old ID, then the new one. Empty lines and lines starting with '#' are
+// skipped. Returns the 1-based number of the first line that is not a pair of IDs.
+fn parse_map(text: &str) -> Result<HashMap<String, String>, usize> {
+    let mut map = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        let [old, new] = columns[..] else {
+            return Err(i + 1);
+        };
+        map.insert(old.to_string(), new.to_string());
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn renamed(renamer: &Renamer, descriptor: &str) -> String {
+        let mut section = BinaryFastaSection::from_text(descriptor, "ACGT");
+        let sequence = section.sequence.clone();
+
+        renamer.rename(&mut section).unwrap();
+        assert_eq!(section.sequence, sequence);
+        section.descriptor
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let map = parse_map("# UCSC\tEnsembl\nchr1\t1\n\nchrM   MT\n").unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["chrM"], "MT");
+
+        assert_eq!(parse_map("chr1\t1\nchr2\n"), Err(2));
+        assert_eq!(parse_map("chr1 1 extra\n"), Err(1));
+    }
+
+    #[test]
+    fn test_rename_with_map() {
+        let renamer = Renamer::Map(parse_map("1\tchr1\nMT\tchrM\n").unwrap());
+
+        assert_eq!(renamed(&renamer, "1 dna:chromosome"), "chr1 dna:chromosome");
+        assert_eq!(renamed(&renamer, "MT"), "chrM");
+        assert_eq!(renamed(&renamer, "2 dna:chromosome"), "2 dna:chromosome");
+    }
+
+    #[test]
+    fn test_rename_with_regex() {
+        let strip = Renamer::Regex {
+            regex: Regex::new(r"\s.*").unwrap(),
+            replacement: String::new(),
+        };
+        assert_eq!(renamed(&strip, "1 dna:chromosome chromosome:GRCh38"), "1");
+
+        let prefix = Renamer::Regex {
+            regex: Regex::new("^").unwrap(),
+            replacement: String::from("chr"),
+        };
+        assert_eq!(renamed(&prefix, "1 dna:chromosome"), "chr1 dna:chromosome");
+
+        let groups = Renamer::Regex {
+            regex: Regex::new(r"^sp\|(\w+)\|(\w+)").unwrap(),
+            replacement: String::from("${2}_$1"),
+        };
+        assert_eq!(renamed(&groups, "sp|P69905|HBA_HUMAN"), "HBA_HUMAN_P69905");
+    }
+
+    #[test]
+    fn test_rename_with_template() {
+        let template = Renamer::Template(String::from("{accession} {description} [{OS}]"));
+        assert_eq!(
+            renamed(
+                &template,
+                "sp|P69905|HBA_HUMAN Hemoglobin subunit alpha OS=Homo sapiens OX=9606"
+            ),
+            "P69905 Hemoglobin subunit alpha [Homo sapiens]"
+        );
+
+        assert!(matches!(
+            fill_template("{GN}", "sp|P69905|HBA_HUMAN OS=Homo sapiens"),
+            Err(BinaryFastaError::MissingDescriptorField { field, .. }) if field == "GN"
+        ));
+        assert_eq!(fill_template("{id}_{", "chr1 x").unwrap(), "chr1_{");
+    }
+}
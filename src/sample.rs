@@ -0,0 +1,130 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// SplitMix64 (https://prng.di.unimi.it/splitmix64.c): small, fast, and good enough
+// for sampling. The same seed always gives the same sample.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    // Seeded from the clock, for when no seed was given.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos() as u64);
+        Rng::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform in [0, n). The modulo bias is negligible for the numbers of sequences
+    // this is used with.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+// Reservoir sampling (algorithm R): picks `count` items, each with the same probability,
+// in a single pass over items of unknown number. The items are returned with their
+// index, in the order they came in.
+pub fn reservoir<T, E>(
+    items: impl Iterator<Item = Result<T, E>>,
+    count: usize,
+    rng: &mut Rng,
+) -> Result<Vec<(usize, T)>, E> {
+    let mut sample: Vec<(usize, T)> = Vec::with_capacity(count);
+
+    for (index, item) in items.enumerate() {
+        let item = item?;
+        if sample.len() < count {
+            sample.push((index, item));
+        } else {
+            let slot = rng.below(index as u64 + 1) as usize;
+            if slot < count {
+                sample[slot] = (index, item);
+            }
+        }
+    }
+
+    sample.sort_by_key(|(index, _)| *index);
+    Ok(sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(total: usize, count: usize, seed: u64) -> Vec<usize> {
+        let items = (0..total).map(Ok::<usize, ()>);
+        reservoir(items, count, &mut Rng::new(seed))
+            .unwrap()
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_sample() {
+        assert_eq!(sample(1000, 10, 42), sample(1000, 10, 42));
+        assert_ne!(sample(1000, 10, 42), sample(1000, 10, 43));
+    }
+
+    #[test]
+    fn test_sample_is_sorted_and_distinct() {
+        let indexes = sample(1000, 50, 7);
+
+        assert_eq!(indexes.len(), 50);
+        assert!(indexes.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(indexes.iter().all(|&index| index < 1000));
+    }
+
+    #[test]
+    fn test_small_inputs_are_kept_whole() {
+        assert_eq!(sample(3, 10, 1), vec![0, 1, 2]);
+        assert!(sample(0, 10, 1).is_empty());
+        assert!(sample(10, 0, 1).is_empty());
+    }
+
+    #[test]
+    fn test_sampling_is_roughly_uniform() {
+        // Every index should be picked about 100 times out of 1000 samples of 1 in 10.
+        let mut picked = [0; 10];
+        for seed in 0..1000 {
+            picked[sample(10, 1, seed)[0]] += 1;
+        }
+        assert!(
+            picked.iter().all(|&n| (60..140).contains(&n)),
+            "{:?}",
+            picked
+        );
+    }
+
+    #[test]
+    fn test_next_f64_range() {
+        let mut rng = Rng::new(3);
+        assert!((0..1000)
+            .map(|_| rng.next_f64())
+            .all(|x| (0.0..1.0).contains(&x)));
+    }
+
+    #[test]
+    fn test_errors_are_returned() {
+        let items = vec![Ok(1), Err("broken"), Ok(3)].into_iter();
+        assert_eq!(reservoir(items, 2, &mut Rng::new(0)), Err("broken"));
+    }
+}
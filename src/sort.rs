@@ -0,0 +1,528 @@
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    fs,
+    mem::size_of,
+    path::{Path, PathBuf},
+    process,
+};
+
+use clap::ValueEnum;
+
+use crate::{
+    basta::{
+        binary_fasta_data,
+        binary_fasta_section::{BinaryFastaSection, Molecule, SectionHeader},
+    },
+    errors::BinaryFastaError,
+    records::Sections,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy, ValueEnum)]
+pub enum SortKey {
+    // Descriptor, in natural order.
+    Name,
+    // Number of nucleotides or amino acids.
+    Length,
+    // Residues, in the order of their packed codes (alphabetical for nucleotides).
+    Sequence,
+}
+
+// Sorting is stable: sections that compare equal keep their order in the input.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Order {
+    pub key: SortKey,
+    pub reverse: bool,
+}
+
+impl Order {
+    pub fn compare(self, a: &BinaryFastaSection, b: &BinaryFastaSection) -> Ordering {
+        let ordering = match self.key {
+            SortKey::Name => natural_cmp(&a.descriptor, &b.descriptor),
+            SortKey::Length => a.length().cmp(&b.length()),
+            SortKey::Sequence => sequence_cmp(a, b),
+        };
+        self.direction(ordering)
+    }
+
+    // Name and length are in the section headers, so BASTA files can be sorted without
+    // reading their sequences.
+    pub fn uses_headers_only(self) -> bool {
+        self.key != SortKey::Sequence
+    }
+
+    pub fn compare_headers(self, a: &SectionHeader, b: &SectionHeader) -> Ordering {
+        let ordering = match self.key {
+            SortKey::Name => natural_cmp(&a.descriptor, &b.descriptor),
+            SortKey::Length => a.length().cmp(&b.length()),
+            SortKey::Sequence => unreachable!("Sequences are not in the section headers."),
+        };
+        self.direction(ordering)
+    }
+
+    fn direction(self, ordering: Ordering) -> Ordering {
+        if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+// Compares runs of digits by their value, so chr2 comes before chr10, and everything
+// else byte by byte. Of two equal numbers, the one with fewer leading zeros comes first.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (digits_a, rest_a) = split_digits(a);
+                let (digits_b, rest_b) = split_digits(b);
+                let value_a = trim_zeros(digits_a);
+                let value_b = trim_zeros(digits_b);
+
+                let ordering = value_a
+                    .len()
+                    .cmp(&value_b.len())
+                    .then_with(|| value_a.cmp(value_b))
+                    .then_with(|| digits_a.len().cmp(&digits_b.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                (a, b) = (rest_a, rest_b);
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(y);
+                }
+                (a, b) = (&a[1..], &b[1..]);
+            }
+        }
+    }
+}
+
+fn split_digits(bytes: &[u8]) -> (&[u8], &[u8]) {
+    let end = bytes
+        .iter()
+        .position(|byte| !byte.is_ascii_digit())
+        .unwrap_or(bytes.len());
+    bytes.split_at(end)
+}
+
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let start = digits
+        .iter()
+        .position(|&digit| digit != b'0')
+        .unwrap_or(digits.len());
+    &digits[start..]
+}
+
+// Nucleotide sequences come before protein sequences. The packed bytes compare like the
+// residues, except that a sequence and the same sequence followed by residues with code 0
+// have the same bytes, which the length orders. Ambiguous bases are packed as A, so
+// sequences that only differ in them are then ordered by their ambiguous runs.
+fn sequence_cmp(a: &BinaryFastaSection, b: &BinaryFastaSection) -> Ordering {
+    let is_protein = |section: &BinaryFastaSection| section.molecule == Molecule::Protein;
+
+    is_protein(a)
+        .cmp(&is_protein(b))
+        .then_with(|| packed_residues(a).cmp(packed_residues(b)))
+        .then_with(|| a.length().cmp(&b.length()))
+        .then_with(|| a.ambiguous_runs.cmp(&b.ambiguous_runs))
+}
+
+// The packed bytes, with the padding bits of the last byte cleared.
+fn packed_residues(section: &BinaryFastaSection) -> impl Iterator<Item = u8> + '_ {
+    let bits = section.length() * section.molecule.bits_per_residue();
+    section.sequence.iter().enumerate().map(move |(i, &byte)| {
+        match bits.saturating_sub(8 * i).min(8) {
+            0 => 0,
+            used => byte & (0xFFu8 << (8 - used)),
+        }
+    })
+}
+
+pub fn sort_in_memory(
+    sections: impl Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>,
+    order: Order,
+) -> Result<Vec<BinaryFastaSection>, BinaryFastaError> {
+    let mut sections = sections.collect::<Result<Vec<_>, _>>()?;
+    sections.sort_by(|a, b| order.compare(a, b));
+    Ok(sections)
+}
+
+// Sorts a BASTA file from its section headers, then reads the sections in order.
+pub fn sort_basta_by_headers(file_path: &Path, order: Order) -> Result<Sections, BinaryFastaError> {
+    let (_, entries) = binary_fasta_data::list_sections(file_path)?;
+    let mut entries = entries.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by(|a, b| order.compare_headers(&a.header, &b.header));
+
+    let offsets = entries.into_iter().map(|entry| entry.offset);
+    Ok(Box::new(binary_fasta_data::read_at(file_path, offsets)?))
+}
+
+// Memory taken by a section while it is sorted, roughly.
+fn section_size(section: &BinaryFastaSection) -> usize {
+    size_of::<BinaryFastaSection>()
+        + section.descriptor.len()
+        + section.sequence.len()
+        + size_of_val(section.ambiguous_runs.as_slice())
+}
+
+// Most run files read at the same time while merging.
+const MAX_MERGED_RUNS: usize = 64;
+
+// Sorts sections that may not fit in memory. Sections are sorted in runs of at most
+// `max_memory` bytes, which are written to BASTA files in a new directory of `temp_dir`
+// and merged while the result is read, at most MAX_MERGED_RUNS at a time. The directory
+// is removed once the result is dropped. If all sections fit in one run, nothing is written.
+pub fn sort_external(
+    sections: impl Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>,
+    order: Order,
+    max_memory: usize,
+    temp_dir: &Path,
+) -> Result<Sections, BinaryFastaError> {
+    let mut runs: Option<RunDirectory> = None;
+    let mut run_paths: Vec<PathBuf> = Vec::new();
+    let mut chunk: Vec<BinaryFastaSection> = Vec::new();
+    let mut chunk_size = 0;
+
+    for res in sections {
+        let section = res?;
+        let size = section_size(&section);
+        if !chunk.is_empty() && chunk_size + size > max_memory {
+            let directory = match runs.take() {
+                Some(directory) => directory,
+                None => RunDirectory::create(temp_dir)?,
+            };
+            run_paths.push(runs.insert(directory).write_run(&mut chunk, order)?);
+            chunk_size = 0;
+        }
+        chunk_size += size;
+        chunk.push(section);
+    }
+
+    let Some(mut runs) = runs else {
+        chunk.sort_by(|a, b| order.compare(a, b));
+        return Ok(Box::new(chunk.into_iter().map(Ok)));
+    };
+    run_paths.push(runs.write_run(&mut chunk, order)?);
+
+    // Too many runs to open at once are merged in groups of neighbouring runs into
+    // longer runs first, which keeps equal sections in their input order.
+    while run_paths.len() > MAX_MERGED_RUNS {
+        let mut merged_paths = Vec::new();
+        for group in run_paths.chunks(MAX_MERGED_RUNS) {
+            let path = runs.next_path();
+            binary_fasta_data::write(Merge::open(group, order)?, &path, false)?;
+            for run_path in group {
+                fs::remove_file(run_path)?;
+            }
+            merged_paths.push(path);
+        }
+        run_paths = merged_paths;
+    }
+
+    let mut merge = Merge::open(&run_paths, order)?;
+    Ok(Box::new(std::iter::from_fn(move || {
+        // Keeps the directory until the merge is done.
+        let _ = &runs;
+        merge.next()
+    })))
+}
+
+// The next section of a run, ordered so that the heap of the merge gives the smallest
+// section first, and of equal sections the one from the earliest run.
+struct Head {
+    section: BinaryFastaSection,
+    run: usize,
+    order: Order,
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.order
+            .compare(&self.section, &other.section)
+            .then(self.run.cmp(&other.run))
+            .reverse()
+    }
+}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Head {}
+
+// Merges sorted run files, reading one section of each at a time.
+struct Merge {
+    runs: Vec<Sections>,
+    heads: BinaryHeap<Head>,
+    order: Order,
+    // Returned before the next section, as soon as it is read.
+    error: Option<BinaryFastaError>,
+}
+
+impl Merge {
+    fn open(run_paths: &[PathBuf], order: Order) -> Result<Self, BinaryFastaError> {
+        let mut merge = Merge {
+            runs: Vec::with_capacity(run_paths.len()),
+            heads: BinaryHeap::with_capacity(run_paths.len()),
+            order,
+            error: None,
+        };
+        for path in run_paths {
+            merge.runs.push(Box::new(binary_fasta_data::read(path)?));
+            merge.advance(merge.runs.len() - 1)?;
+        }
+        Ok(merge)
+    }
+
+    // Reads the next section of run `run` into the heap.
+    fn advance(&mut self, run: usize) -> Result<(), BinaryFastaError> {
+        if let Some(section) = self.runs[run].next().transpose()? {
+            self.heads.push(Head {
+                section,
+                run,
+                order: self.order,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Merge {
+    type Item = Result<BinaryFastaSection, BinaryFastaError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        let head = self.heads.pop()?;
+        if let Err(e) = self.advance(head.run) {
+            self.error = Some(e);
+        }
+        Some(Ok(head.section))
+    }
+}
+
+// A directory for the sorted runs, removed with everything in it when dropped.
+struct RunDirectory {
+    path: PathBuf,
+    runs: usize,
+}
+
+impl RunDirectory {
+    fn create(temp_dir: &Path) -> Result<Self, BinaryFastaError> {
+        let mut attempt = 0;
+        loop {
+            let path = temp_dir.join(format!("binary_fasta_sort.{}.{}", process::id(), attempt));
+            match fs::create_dir(&path) {
+                Ok(()) => return Ok(RunDirectory { path, runs: 0 }),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => {
+                    attempt += 1
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    // Sorts `chunk` and writes it to the next run file, leaving `chunk` empty.
+    fn write_run(
+        &mut self,
+        chunk: &mut Vec<BinaryFastaSection>,
+        order: Order,
+    ) -> Result<PathBuf, BinaryFastaError> {
+        chunk.sort_by(|a, b| order.compare(a, b));
+        let path = self.next_path();
+        binary_fasta_data::write(chunk.drain(..).map(Ok), &path, false)?;
+        Ok(path)
+    }
+
+    fn next_path(&mut self) -> PathBuf {
+        self.runs += 1;
+        self.path.join(format!("run.{:05}.basta", self.runs))
+    }
+}
+
+impl Drop for RunDirectory {
+    fn drop(&mut self) {
+        // Nothing useful can be done if this fails.
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sections() -> Vec<BinaryFastaSection> {
+        vec![
+            BinaryFastaSection::from_text("chr10", "ACGTACGTAC"),
+            BinaryFastaSection::from_text("chr2", "TTTT"),
+            BinaryFastaSection::from_text("chrX", "ACG"),
+            BinaryFastaSection::from_text("chr1", "ACGA"),
+            BinaryFastaSection::from_text("chr2 again", "GG"),
+            BinaryFastaSection::from_text("prot1", "PEP"),
+        ]
+    }
+
+    fn sorted(order: Order) -> Vec<String> {
+        sort_in_memory(sections().into_iter().map(Ok), order)
+            .unwrap()
+            .into_iter()
+            .map(|section| section.descriptor)
+            .collect()
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut names = vec!["chr10", "chr2", "chrX", "chr1", "chr02", "contig", "chr1_a"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            vec!["chr1", "chr1_a", "chr2", "chr02", "chr10", "chrX", "contig"]
+        );
+        assert_eq!(
+            natural_cmp("a99999999999999999999999", "a100000000000000000000000"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_sort_by_name_and_length() {
+        let by_name = Order {
+            key: SortKey::Name,
+            reverse: false,
+        };
+        assert_eq!(
+            sorted(by_name),
+            vec!["chr1", "chr2", "chr2 again", "chr10", "chrX", "prot1"]
+        );
+
+        // Equal lengths keep their input order, also when reversed.
+        let by_length = Order {
+            key: SortKey::Length,
+            reverse: true,
+        };
+        assert_eq!(
+            sorted(by_length),
+            vec!["chr10", "chr2", "chr1", "chrX", "prot1", "chr2 again"]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_sequence() {
+        let by_sequence = Order {
+            key: SortKey::Sequence,
+            reverse: false,
+        };
+        // ACG < ACGA < ACGTACGTAC < GG < TTTT, then proteins.
+        assert_eq!(
+            sorted(by_sequence),
+            vec!["chrX", "chr1", "chr10", "chr2 again", "chr2", "prot1"]
+        );
+
+        let mut padded = BinaryFastaSection::from_text("padded", "ACG");
+        padded.sequence[0] |= 0b11;
+        assert_eq!(
+            sequence_cmp(&padded, &BinaryFastaSection::from_text("a", "ACG")),
+            Ordering::Equal
+        );
+
+        // N is packed as A, but the sequences are not equal.
+        assert_eq!(
+            sequence_cmp(
+                &BinaryFastaSection::from_text("a", "ACGA"),
+                &BinaryFastaSection::from_text("b", "ACGN")
+            ),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_sort_external() {
+        let dir = tempfile::tempdir().unwrap();
+        let order = Order {
+            key: SortKey::Name,
+            reverse: false,
+        };
+
+        // Small enough for every section to go to its own run.
+        let merged: Vec<_> = sort_external(sections().into_iter().map(Ok), order, 1, dir.path())
+            .unwrap()
+            .map(|res| res.unwrap().descriptor)
+            .collect();
+        assert_eq!(merged, sorted(order));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        let in_memory: Vec<_> = sort_external(
+            sections().into_iter().map(Ok),
+            order,
+            usize::MAX,
+            dir.path(),
+        )
+        .unwrap()
+        .map(|res| res.unwrap().descriptor)
+        .collect();
+        assert_eq!(in_memory, sorted(order));
+    }
+
+    #[test]
+    fn test_sort_external_in_several_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let order = Order {
+            key: SortKey::Length,
+            reverse: false,
+        };
+        // More runs than are merged at once, with many sections of the same length.
+        let count = MAX_MERGED_RUNS * 3 + 5;
+        let sections = || {
+            (0..count).map(|i| {
+                Ok(BinaryFastaSection::from_text(
+                    &format!("s{}", (i * 37) % count),
+                    &"A".repeat(i % 7 + 1),
+                ))
+            })
+        };
+
+        let merged: Vec<_> = sort_external(sections(), order, 1, dir.path())
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(merged, sort_in_memory(sections(), order).unwrap());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_sort_basta_by_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.basta");
+        binary_fasta_data::write(sections().into_iter().map(Ok), &path, false).unwrap();
+        let order = Order {
+            key: SortKey::Length,
+            reverse: false,
+        };
+
+        let from_headers: Vec<_> = sort_basta_by_headers(&path, order)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            from_headers,
+            sort_in_memory(sections().into_iter().map(Ok), order).unwrap()
+        );
+    }
+}
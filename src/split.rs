@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+
+use clap::ValueEnum;
+
+use crate::{basta::binary_fasta_section::BinaryFastaSection, errors::BinaryFastaError};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SplitBy {
+    // One sequence per file.
+    Sequence,
+    // A number of sequences per file.
+    Count(usize),
+    // About a number of bases per file.
+    Bases(usize),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, ValueEnum)]
+pub enum SplitFormat {
+    Fasta,
+    Basta,
+    #[value(name = "2bit")]
+    TwoBit,
+}
+
+impl SplitFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SplitFormat::Fasta => "fasta",
+            SplitFormat::Basta => "basta",
+            SplitFormat::TwoBit => "2bit",
+        }
+    }
+}
+
+// Groups consecutive sections into the chunks written to each file. A chunk split by
+// bases is closed before the next sequence would take it over the limit, so only a
+// single sequence longer than the limit makes a larger chunk.
+pub fn chunks<I>(
+    sections: I,
+    by: SplitBy,
+) -> impl Iterator<Item = Result<Vec<BinaryFastaSection>, BinaryFastaError>>
+where
+    I: Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>,
+{
+    let mut sections = sections.peekable();
+
+    std::iter::from_fn(move || {
+        let mut chunk: Vec<BinaryFastaSection> = Vec::new();
+        let mut bases = 0;
+
+        loop {
+            let length = match sections.peek() {
+                None => break,
+                Some(Ok(section)) => section.length(),
+                // Errors are returned after the sections before them.
+                Some(Err(_)) if chunk.is_empty() => {
+                    return sections.next().map(|res| res.map(|_| chunk))
+                }
+                Some(Err(_)) => break,
+            };
+
+            let full = match by {
+                SplitBy::Sequence => !chunk.is_empty(),
+                SplitBy::Count(count) => chunk.len() >= count,
+                SplitBy::Bases(limit) => !chunk.is_empty() && bases + length > limit,
+            };
+            if full {
+                break;
+            }
+
+            bases += length;
+            if let Some(Ok(section)) = sections.next() {
+                chunk.push(section);
+            }
+        }
+
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(Ok(chunk))
+        }
+    })
+}
+
+// File names from sequence IDs: characters other than letters, digits, '.', '-' and '_'
+// are replaced by '_', and names already in `used` get a numbered suffix.
+pub fn file_name(id: &str, used: &mut HashSet<String>) -> String {
+    let mut name: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    // Names starting with '.' would be hidden, or be "." and "..".
+    if name.is_empty() || name.starts_with('.') {
+        name.insert(0, '_');
+    }
+
+    let mut unique = name.clone();
+    let mut suffix = 2;
+    while used.contains(&unique) {
+        unique = format!("{}_{}", name, suffix);
+        suffix += 1;
+    }
+    used.insert(unique.clone());
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sections(lengths: &[usize]) -> Vec<Result<BinaryFastaSection, BinaryFastaError>> {
+        lengths
+            .iter()
+            .enumerate()
+            .map(|(i, &length)| {
+                Ok(BinaryFastaSection::from_text(
+                    &format!("seq{}", i + 1),
+                    &"A".repeat(length),
+                ))
+            })
+            .collect()
+    }
+
+    fn chunk_names(lengths: &[usize], by: SplitBy) -> Vec<Vec<String>> {
+        chunks(sections(lengths).into_iter(), by)
+            .map(|chunk| chunk.unwrap().into_iter().map(|s| s.descriptor).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_by_sequence_and_count() {
+        assert_eq!(
+            chunk_names(&[1, 2, 3], SplitBy::Sequence),
+            vec![vec!["seq1"], vec!["seq2"], vec!["seq3"]]
+        );
+        assert_eq!(
+            chunk_names(&[1, 2, 3], SplitBy::Count(2)),
+            vec![vec!["seq1", "seq2"], vec!["seq3"]]
+        );
+        assert!(chunk_names(&[], SplitBy::Count(2)).is_empty());
+    }
+
+    #[test]
+    fn test_split_by_bases() {
+        assert_eq!(
+            chunk_names(&[4, 4, 4, 20, 1], SplitBy::Bases(10)),
+            vec![
+                vec!["seq1", "seq2"],
+                vec!["seq3"],
+                vec!["seq4"],
+                vec!["seq5"]
+            ]
+        );
+        assert_eq!(
+            chunk_names(&[5, 5, 5], SplitBy::Bases(10)),
+            vec![vec!["seq1", "seq2"], vec!["seq3"]]
+        );
+    }
+
+    #[test]
+    fn test_errors_come_after_previous_sections() {
+        let mut input = sections(&[1, 2]);
+        input.insert(1, Err(BinaryFastaError::UnexpectedEof));
+
+        let mut chunks = chunks(input.into_iter(), SplitBy::Count(5));
+        assert_eq!(chunks.next().unwrap().unwrap().len(), 1);
+        assert!(matches!(
+            chunks.next(),
+            Some(Err(BinaryFastaError::UnexpectedEof))
+        ));
+        assert_eq!(chunks.next().unwrap().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_file_names() {
+        let mut used = HashSet::new();
+
+        assert_eq!(file_name("chr1", &mut used), "chr1");
+        assert_eq!(
+            file_name("sp|P69905|HBA_HUMAN", &mut used),
+            "sp_P69905_HBA_HUMAN"
+        );
+        assert_eq!(file_name("../etc/passwd", &mut used), "_.._etc_passwd");
+        assert_eq!(file_name("chr1", &mut used), "chr1_2");
+        assert_eq!(file_name("chr1", &mut used), "chr1_3");
+        assert_eq!(file_name("", &mut used), "_");
+    }
+}
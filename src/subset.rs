@@ -0,0 +1,116 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use regex::Regex;
+
+use crate::{descriptor::Descriptor, errors::BinaryFastaError};
+
+// Sections to keep, by sequence ID (the descriptor up to the first whitespace), by
+// a regular expression searched anywhere in the descriptor, or by the value of a field
+// of the parsed descriptor (see `Descriptor::field`). A section is kept if it matches
+// any of them.
+#[derive(Debug, Default)]
+pub struct Selection {
+    ids: HashSet<String>,
+    regex: Option<Regex>,
+    fields: Vec<(String, String)>,
+}
+
+impl Selection {
+    pub fn new(
+        ids: impl IntoIterator<Item = String>,
+        regex: Option<Regex>,
+        fields: Vec<(String, String)>,
+    ) -> Self {
+        Selection {
+            ids: ids.into_iter().collect(),
+            regex,
+            fields,
+        }
+    }
+
+    pub fn matches(&self, descriptor: &str) -> bool {
+        let id = descriptor.split_whitespace().next().unwrap_or("");
+        self.ids.contains(id)
+            || self
+                .regex
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(descriptor))
+            || self.matches_fields(descriptor)
+    }
+
+    fn matches_fields(&self, descriptor: &str) -> bool {
+        if self.fields.is_empty() {
+            return false;
+        }
+        let parsed = Descriptor::parse(descriptor);
+        self.fields
+            .iter()
+            .any(|(name, value)| parsed.field(name) == Some(value.as_str()))
+    }
+}
+
+// Reads an ID list with one sequence per line. Only the first word of each line is used,
+// and a leading '>' is ignored, so FASTA descriptor lines can be used as they are.
+pub fn read_id_list(path: &Path) -> Result<Vec<String>, BinaryFastaError> {
+    Ok(parse_id_list(&fs::read_to_string(path)?))
+}
+
+fn parse_id_list(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| {
+            line.trim_start()
+                .trim_start_matches('>')
+                .split_whitespace()
+                .next()
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_id_list() {
+        let text = "chr1\n\n  chr2 some gene\n>chr3 descriptor line\n";
+        assert_eq!(parse_id_list(text), vec!["chr1", "chr2", "chr3"]);
+    }
+
+    #[test]
+    fn test_selection_by_id() {
+        let selection = Selection::new(vec![String::from("geneA")], None, vec![]);
+
+        assert!(selection.matches("geneA"));
+        assert!(selection.matches("geneA some description"));
+        assert!(!selection.matches("geneAB"));
+        assert!(!selection.matches("other geneA"));
+    }
+
+    #[test]
+    fn test_selection_by_regex() {
+        let regex = Regex::new("^sp\\|.*HUMAN").unwrap();
+        let selection = Selection::new(vec![String::from("chrM")], Some(regex), vec![]);
+
+        assert!(selection.matches("sp|P69905|HBA_HUMAN Hemoglobin subunit alpha"));
+        assert!(!selection.matches("sp|P01942|HBA_MOUSE Hemoglobin subunit alpha"));
+        assert!(selection.matches("chrM"));
+        assert!(!Selection::default().matches("chrM"));
+    }
+
+    #[test]
+    fn test_selection_by_field() {
+        let fields = vec![
+            (String::from("OX"), String::from("9606")),
+            (String::from("accession"), String::from("P01942")),
+        ];
+        let selection = Selection::new(vec![], None, fields);
+
+        assert!(selection
+            .matches("sp|P69905|HBA_HUMAN Hemoglobin subunit alpha OS=Homo sapiens OX=9606"));
+        assert!(selection
+            .matches("sp|P01942|HBA_MOUSE Hemoglobin subunit alpha OS=Mus musculus OX=10090"));
+        assert!(!selection
+            .matches("sp|P01966|HBA_BOVIN Hemoglobin subunit alpha OS=Bos taurus OX=9913"));
+    }
+}
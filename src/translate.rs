@@ -0,0 +1,213 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    basta::binary_fasta_section::BinaryFastaSection, errors::BinaryFastaError,
+    genetic_code::GeneticCode,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy, clap::ValueEnum)]
+pub enum StopCodons {
+    // Translate stop codons as '*'.
+    Keep,
+    // Like keep, but drop a stop codon at the very end.
+    Trim,
+    // End the translation at the first stop codon.
+    Truncate,
+    // Translate stop codons as 'X'.
+    Mask,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Translation {
+    pub code: &'static GeneticCode,
+    pub stop_codons: StopCodons,
+    // Translate the first codon as M if it is a start codon, as it is read by the
+    // initiator tRNA (e.g. GTG in bacteria).
+    pub start_as_met: bool,
+}
+
+impl Translation {
+    // Codons are 6-bit indexes, or None if they hold bases other than A, C, G, T/U.
+    pub fn translate(&self, codons: impl Iterator<Item = Option<u8>>) -> String {
+        let mut protein = String::new();
+
+        for (i, codon) in codons.enumerate() {
+            let Some(codon) = codon else {
+                protein.push('X');
+                continue;
+            };
+
+            if i == 0 && self.start_as_met && self.code.is_start(codon) {
+                protein.push('M');
+            } else if self.code.is_stop(codon) {
+                match self.stop_codons {
+                    StopCodons::Keep | StopCodons::Trim => protein.push('*'),
+                    StopCodons::Truncate => break,
+                    StopCodons::Mask => protein.push('X'),
+                }
+            } else {
+                protein.push(self.code.amino_acid(codon) as char);
+            }
+        }
+
+        if self.stop_codons == StopCodons::Trim && protein.ends_with('*') {
+            protein.pop();
+        }
+        protein
+    }
+}
+
+// Reading frames 1, 2, 3 start at the first, second and third base of the sequence,
+// and -1, -2, -3 at the same bases of its reverse complement.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Frame(i8);
+
+impl Frame {
+    pub const ALL: [Frame; 6] = [
+        Frame(1),
+        Frame(2),
+        Frame(3),
+        Frame(-1),
+        Frame(-2),
+        Frame(-3),
+    ];
+
+    pub fn offset(self) -> usize {
+        (self.0.unsigned_abs() - 1) as usize
+    }
+
+    pub fn is_reverse(self) -> bool {
+        self.0 < 0
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Frame(1)
+    }
+}
+
+impl FromStr for Frame {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim_start_matches('+').parse::<i8>() {
+            Ok(frame @ (1..=3 | -3..=-1)) => Ok(Frame(frame)),
+            _ => Err(format!(
+                "'{}' is not a reading frame. Use 1, 2, 3, -1, -2 or -3.",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Translates the section in the given frame, keeping its descriptor.
+pub fn translate_section(
+    section: &BinaryFastaSection,
+    frame: Frame,
+    translation: &Translation,
+) -> Result<BinaryFastaSection, BinaryFastaError> {
+    if frame.is_reverse() {
+        section
+            .reverse_complement()?
+            .translate(translation, frame.offset())
+    } else {
+        section.translate(translation, frame.offset())
+    }
+}
+
+// Adds the frame to the sequence ID, so the translations of one sequence in several
+// frames can be told apart: "chr1 desc" becomes "chr1_frame=-2 desc".
+pub fn frame_descriptor(descriptor: &str, frame: Frame) -> String {
+    let id_length = descriptor
+        .find(char::is_whitespace)
+        .unwrap_or(descriptor.len());
+    let (id, rest) = descriptor.split_at(id_length);
+    format!("{}_frame={}{}", id, frame, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fasta::fasta_section::FastaSection, genetic_code::DEFAULT_TABLE};
+
+    fn translation(stop_codons: StopCodons) -> Translation {
+        Translation {
+            code: GeneticCode::from_id(DEFAULT_TABLE).unwrap(),
+            stop_codons,
+            start_as_met: false,
+        }
+    }
+
+    fn translate(sequence: &str, translation: &Translation) -> String {
+        let codons = sequence
+            .as_bytes()
+            .chunks_exact(3)
+            .map(GeneticCode::codon_index);
+        translation.translate(codons)
+    }
+
+    #[test]
+    fn test_stop_codon_handling() {
+        let sequence = "ATGTAAGGGTAG";
+
+        assert_eq!(translate(sequence, &translation(StopCodons::Keep)), "M*G*");
+        assert_eq!(translate(sequence, &translation(StopCodons::Trim)), "M*G");
+        assert_eq!(translate(sequence, &translation(StopCodons::Truncate)), "M");
+        assert_eq!(translate(sequence, &translation(StopCodons::Mask)), "MXGX");
+    }
+
+    #[test]
+    fn test_start_as_met_and_ambiguous_codons() {
+        let mut bacterial = translation(StopCodons::Keep);
+        bacterial.code = GeneticCode::from_id(11).unwrap();
+        assert_eq!(translate("GTGGTGNNA", &bacterial), "VVX");
+
+        bacterial.start_as_met = true;
+        assert_eq!(translate("GTGGTGNNA", &bacterial), "MVX");
+    }
+
+    #[test]
+    fn test_parse_frames() {
+        assert_eq!("1".parse::<Frame>().unwrap(), Frame(1));
+        assert_eq!("+3".parse::<Frame>().unwrap(), Frame(3));
+        assert_eq!("-2".parse::<Frame>().unwrap(), Frame(-2));
+        assert!("0".parse::<Frame>().is_err());
+        assert!("4".parse::<Frame>().is_err());
+    }
+
+    #[test]
+    fn test_translate_section_in_all_frames() {
+        let section = BinaryFastaSection::from_text("gene", "ATGGCCTTAAGGTGA");
+
+        let proteins: Vec<String> = Frame::ALL
+            .iter()
+            .map(|&frame| {
+                let protein =
+                    translate_section(&section, frame, &translation(StopCodons::Keep)).unwrap();
+                FastaSection::from_basta(protein).sequence
+            })
+            .collect();
+
+        // Reverse complement: TCACCTTAAGGCCAT
+        assert_eq!(
+            proteins,
+            vec!["MALR*", "WP*G", "GLKV", "SP*GH", "HLKA", "TLRP"]
+        );
+    }
+
+    #[test]
+    fn test_frame_descriptor() {
+        assert_eq!(
+            frame_descriptor("chr1 some gene", Frame(-2)),
+            "chr1_frame=-2 some gene"
+        );
+        assert_eq!(frame_descriptor("chr1", Frame(1)), "chr1_frame=1");
+    }
+}
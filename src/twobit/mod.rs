@@ -0,0 +1,2 @@
+pub mod two_bit_data;
+pub mod two_bit_section;
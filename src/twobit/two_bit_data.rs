@@ -0,0 +1,387 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use crate::{
+    basta::binary_fasta_section::BinaryFastaSection,
+    errors::BinaryFastaError,
+    fasta::fasta_section::FastaSection,
+    output_file::OutputFile,
+    twobit::two_bit_section::{packed_length, TwoBitSection},
+};
+
+// UCSC .2bit layout (https://genome.ucsc.edu/FAQ/FAQformat.html#format7):
+// a header (signature, version, sequence count, reserved), an index of
+// (name, record offset) pairs, then one record per sequence. Integers use the
+// byte order of the machine that wrote the file, which the signature reveals.
+const SIGNATURE: u32 = 0x1A41_2743;
+const HEADER_LENGTH: u64 = 16;
+
+// Version 1 files use 64-bit record offsets, for files larger than 4 GiB.
+const MAX_VERSION: u32 = 1;
+
+pub fn from_fasta<I>(fasta_data: I) -> impl Iterator<Item = Result<TwoBitSection, BinaryFastaError>>
+where
+    I: Iterator<Item = Result<FastaSection, BinaryFastaError>>,
+{
+    fasta_data.map(|res| res.and_then(TwoBitSection::from_fasta))
+}
+
+pub fn from_basta<I>(
+    binary_fasta_data: I,
+) -> impl Iterator<Item = Result<TwoBitSection, BinaryFastaError>>
+where
+    I: Iterator<Item = Result<BinaryFastaSection, BinaryFastaError>>,
+{
+    binary_fasta_data.map(|res| res.and_then(TwoBitSection::from_basta))
+}
+
+struct TwoBitReader {
+    reader: BufReader<File>,
+    little_endian: bool,
+}
+
+impl TwoBitReader {
+    fn read_u32(&mut self) -> Result<u32, BinaryFastaError> {
+        let mut arr = [0u8; 4];
+        self.read_exact(&mut arr)?;
+        Ok(if self.little_endian {
+            u32::from_le_bytes(arr)
+        } else {
+            u32::from_be_bytes(arr)
+        })
+    }
+
+    fn read_u64(&mut self) -> Result<u64, BinaryFastaError> {
+        let mut arr = [0u8; 8];
+        self.read_exact(&mut arr)?;
+        Ok(if self.little_endian {
+            u64::from_le_bytes(arr)
+        } else {
+            u64::from_be_bytes(arr)
+        })
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), BinaryFastaError> {
+        self.reader.read_exact(buf).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => BinaryFastaError::UnexpectedEof,
+            _ => e.into(),
+        })
+    }
+
+    // The count is checked before allocating: blocks do not overlap, so there is at most
+    // one per base. `TwoBitSection::check_blocks` checks the rest once they are read.
+    fn read_blocks(
+        &mut self,
+        name: &str,
+        dna_size: u32,
+    ) -> Result<Vec<(u32, u32)>, BinaryFastaError> {
+        let count = self.read_u32()?;
+        if count > dna_size {
+            return Err(BinaryFastaError::InvalidTwoBitBlocks {
+                name: name.to_string(),
+            });
+        }
+        let count = count as usize;
+        let mut starts = Vec::with_capacity(count);
+        for _ in 0..count {
+            starts.push(self.read_u32()?);
+        }
+        let mut blocks = Vec::with_capacity(count);
+        for start in starts {
+            blocks.push((start, self.read_u32()?));
+        }
+        Ok(blocks)
+    }
+
+    fn read_section(
+        &mut self,
+        name: String,
+        offset: u64,
+    ) -> Result<TwoBitSection, BinaryFastaError> {
+        self.reader.seek(SeekFrom::Start(offset))?;
+
+        let dna_size = self.read_u32()?;
+        let n_blocks = self.read_blocks(&name, dna_size)?;
+        let mask_blocks = self.read_blocks(&name, dna_size)?;
+        let _reserved = self.read_u32()?;
+
+        let mut packed_dna = vec![0u8; packed_length(dna_size)];
+        self.read_exact(&mut packed_dna)?;
+
+        let section = TwoBitSection {
+            name,
+            dna_size,
+            n_blocks,
+            mask_blocks,
+            packed_dna,
+        };
+        section.check_blocks()?;
+        Ok(section)
+    }
+}
+
+pub fn read(
+    file_path: &Path,
+) -> Result<impl Iterator<Item = Result<TwoBitSection, BinaryFastaError>>, BinaryFastaError> {
+    let mut reader = BufReader::new(File::open(file_path)?);
+
+    let mut signature = [0u8; 4];
+    reader
+        .read_exact(&mut signature)
+        .map_err(|_| BinaryFastaError::MalformedTwoBitHeader {
+            path: file_path.to_path_buf(),
+        })?;
+    let little_endian = if u32::from_le_bytes(signature) == SIGNATURE {
+        true
+    } else if u32::from_be_bytes(signature) == SIGNATURE {
+        false
+    } else {
+        return Err(BinaryFastaError::MalformedTwoBitHeader {
+            path: file_path.to_path_buf(),
+        });
+    };
+
+    let mut two_bit = TwoBitReader {
+        reader,
+        little_endian,
+    };
+
+    let version = two_bit.read_u32()?;
+    if version > MAX_VERSION {
+        return Err(BinaryFastaError::UnsupportedVersion {
+            version: u8::try_from(version).unwrap_or(u8::MAX),
+        });
+    }
+    let sequence_count = two_bit.read_u32()?;
+    let _reserved = two_bit.read_u32()?;
+
+    let mut index: Vec<(String, u64)> = Vec::new();
+    for _ in 0..sequence_count {
+        let mut name_length = [0u8; 1];
+        two_bit.read_exact(&mut name_length)?;
+        let mut name = vec![0u8; name_length[0] as usize];
+        two_bit.read_exact(&mut name)?;
+        let name = String::from_utf8(name).map_err(|_| BinaryFastaError::InvalidUtf8Descriptor)?;
+
+        let offset = if version == 0 {
+            two_bit.read_u32()? as u64
+        } else {
+            two_bit.read_u64()?
+        };
+        index.push((name, offset));
+    }
+
+    let mut entries = index.into_iter();
+    Ok(std::iter::from_fn(move || {
+        entries
+            .next()
+            .map(|(name, offset)| two_bit.read_section(name, offset))
+    }))
+}
+
+// Every record has to be known before writing, since the index at the start of the
+// file holds their offsets. This holds the packed sequences in memory.
+pub fn write<I>(iter: I, file_path: &Path, overwrite: bool) -> Result<(), BinaryFastaError>
+where
+    I: Iterator<Item = Result<TwoBitSection, BinaryFastaError>>,
+{
+    let mut writer = OutputFile::create(file_path, overwrite)?;
+    write_to(iter, &mut writer)?;
+    writer.commit()
+}
+
+pub fn write_to<I, W>(iter: I, writer: &mut W) -> Result<(), BinaryFastaError>
+where
+    I: Iterator<Item = Result<TwoBitSection, BinaryFastaError>>,
+    W: Write,
+{
+    let sections = iter.collect::<Result<Vec<TwoBitSection>, BinaryFastaError>>()?;
+
+    // Sequences are looked up by name in the index.
+    let mut names = HashSet::new();
+    if let Some(section) = sections.iter().find(|section| !names.insert(&section.name)) {
+        return Err(BinaryFastaError::DuplicateSequence {
+            name: section.name.clone(),
+        });
+    }
+
+    let index_length = |offset_size: u64| -> u64 {
+        sections
+            .iter()
+            .map(|section| 1 + section.name.len() as u64 + offset_size)
+            .sum()
+    };
+    let records_length: u64 = sections.iter().map(TwoBitSection::record_length).sum();
+
+    let version: u32 = if HEADER_LENGTH + index_length(4) + records_length > u32::MAX as u64 {
+        1
+    } else {
+        0
+    };
+    let offset_size = if version == 0 { 4 } else { 8 };
+
+    // Written little-endian, like the UCSC tools on x86.
+    writer.write_all(&SIGNATURE.to_le_bytes())?;
+    writer.write_all(&version.to_le_bytes())?;
+    writer.write_all(&(sections.len() as u32).to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+
+    let mut offset = HEADER_LENGTH + index_length(offset_size);
+    for section in &sections {
+        let name_length =
+            u8::try_from(section.name.len()).map_err(|_| BinaryFastaError::TwoBitNameTooLong {
+                name: section.name.clone(),
+            })?;
+        writer.write_all(&[name_length])?;
+        writer.write_all(section.name.as_bytes())?;
+        if version == 0 {
+            writer.write_all(&(offset as u32).to_le_bytes())?;
+        } else {
+            writer.write_all(&offset.to_le_bytes())?;
+        }
+        offset += section.record_length();
+    }
+
+    for section in &sections {
+        writer.write_all(&section.dna_size.to_le_bytes())?;
+        for blocks in [&section.n_blocks, &section.mask_blocks] {
+            writer.write_all(&(blocks.len() as u32).to_le_bytes())?;
+            for (start, _) in blocks.iter() {
+                writer.write_all(&start.to_le_bytes())?;
+            }
+            for (_, length) in blocks.iter() {
+                writer.write_all(&length.to_le_bytes())?;
+            }
+        }
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_all(&section.packed_dna)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_sections() -> Vec<FastaSection> {
+        vec![
+            FastaSection {
+                descriptor: String::from("chr1"),
+                sequence: String::from("NNNNacgtACGTACGTnnGGa"),
+            },
+            FastaSection {
+                descriptor: String::from("chrM"),
+                sequence: String::from("GATC"),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genome.2bit");
+
+        let fasta_iter = example_sections().into_iter().map(Ok);
+        write(from_fasta(fasta_iter), &path, false).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes[..4], [0x43, 0x27, 0x41, 0x1a]);
+
+        let sections: Vec<FastaSection> = read(&path)
+            .unwrap()
+            .map(|res| res.unwrap().to_fasta())
+            .collect();
+        assert_eq!(sections, example_sections());
+    }
+
+    #[test]
+    fn test_read_big_endian() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big_endian.2bit");
+
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend(SIGNATURE.to_be_bytes());
+        bytes.extend(0u32.to_be_bytes()); // version
+        bytes.extend(1u32.to_be_bytes()); // sequence count
+        bytes.extend(0u32.to_be_bytes()); // reserved
+        bytes.push(3);
+        bytes.extend(b"seq");
+        bytes.extend(27u32.to_be_bytes()); // offset: 16 + 1 + 3 + 4 + 3 padding bytes
+        bytes.extend([0u8; 3]);
+        bytes.extend(5u32.to_be_bytes()); // dnaSize
+        bytes.extend(0u32.to_be_bytes()); // nBlockCount
+        bytes.extend(0u32.to_be_bytes()); // maskBlockCount
+        bytes.extend(0u32.to_be_bytes()); // reserved
+        bytes.extend([0b1001_1100, 0b1100_0000]); // ACGTG
+        std::fs::write(&path, bytes).unwrap();
+
+        let sections: Vec<FastaSection> = read(&path)
+            .unwrap()
+            .map(|res| res.unwrap().to_fasta())
+            .collect();
+        assert_eq!(
+            sections,
+            vec![FastaSection {
+                descriptor: String::from("seq"),
+                sequence: String::from("ACGTG"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_read_rejects_blocks_outside_the_sequence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corrupt.2bit");
+
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend(SIGNATURE.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes()); // version
+        bytes.extend(1u32.to_le_bytes()); // sequence count
+        bytes.extend(0u32.to_le_bytes()); // reserved
+        bytes.push(3);
+        bytes.extend(b"seq");
+        bytes.extend(24u32.to_le_bytes()); // offset
+        bytes.extend(5u32.to_le_bytes()); // dnaSize
+        bytes.extend(1u32.to_le_bytes()); // nBlockCount
+        bytes.extend(3u32.to_le_bytes()); // nBlockStarts
+        bytes.extend(4u32.to_le_bytes()); // nBlockSizes, past the end
+        bytes.extend(0u32.to_le_bytes()); // maskBlockCount
+        bytes.extend(0u32.to_le_bytes()); // reserved
+        bytes.extend([0b1001_1100, 0b1100_0000]);
+        std::fs::write(&path, bytes).unwrap();
+
+        let mut sections = read(&path).unwrap();
+        assert!(matches!(
+            sections.next(),
+            Some(Err(BinaryFastaError::InvalidTwoBitBlocks { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_write_rejects_long_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("long.2bit");
+
+        let sections = vec![FastaSection {
+            descriptor: "x".repeat(256),
+            sequence: String::from("ACGT"),
+        }];
+        assert!(matches!(
+            write(from_fasta(sections.into_iter().map(Ok)), &path, false),
+            Err(BinaryFastaError::TwoBitNameTooLong { .. })
+        ));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_read_rejects_other_files() {
+        assert!(matches!(
+            read(Path::new("tests/data/small_dna.fasta")),
+            Err(BinaryFastaError::MalformedTwoBitHeader { .. })
+        ));
+    }
+}
@@ -0,0 +1,378 @@
+use crate::{
+    basta::{
+        ambiguous::AmbiguousRun,
+        binary_fasta_section::{BinaryFastaSection, Molecule},
+        metadata::Metadata,
+    },
+    errors::BinaryFastaError,
+    fasta::fasta_section::FastaSection,
+};
+
+// Maps every byte of 4 packed nucleotides to the same nucleotides in another
+// 2-bit code, given the new code of each old code.
+const fn translation_table(codes: [u8; 4]) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut translated = 0u8;
+        let mut shift = 0;
+        while shift < 8 {
+            translated |= codes[(byte >> shift) & 0b11] << shift;
+            shift += 2;
+        }
+        table[byte] = translated;
+        byte += 1;
+    }
+    table
+}
+
+// BASTA: A = 00; C = 01; G = 10; T = 11
+// 2bit:  T = 00; C = 01; A = 10; G = 11
+const BASTA_TO_TWO_BIT: [u8; 256] = translation_table([0b10, 0b01, 0b11, 0b00]);
+const TWO_BIT_TO_BASTA: [u8; 256] = translation_table([0b11, 0b01, 0b00, 0b10]);
+
+const TWO_BIT_BASES: [u8; 4] = [b'T', b'C', b'A', b'G'];
+
+#[derive(Debug, PartialEq)]
+pub struct TwoBitSection {
+    // The sequence ID, as 2bit files have no room for the rest of the descriptor.
+    pub name: String,
+    pub dna_size: u32,
+    // (start, length) of every run of N bases, which are stored as T in `packed_dna`.
+    pub n_blocks: Vec<(u32, u32)>,
+    // (start, length) of every run of lower case (soft-masked) bases.
+    pub mask_blocks: Vec<(u32, u32)>,
+    // 2 bits per base, with the first base in the most significant bits.
+    // Where T = 00; C = 01; A = 10; G = 11 (which is not the BASTA encoding).
+    pub packed_dna: Vec<u8>,
+}
+
+impl TwoBitSection {
+    // Like faToTwoBit, any character other than A, C, G, T (or U) is stored as N.
+    pub fn from_fasta(fasta_section: FastaSection) -> Result<Self, BinaryFastaError> {
+        let name = id(&fasta_section.descriptor);
+        if fasta_section.molecule()? == Molecule::Protein {
+            return Err(BinaryFastaError::NotNucleotide { name });
+        }
+
+        let dna_size = u32::try_from(fasta_section.sequence.len()).expect("Sequence is too long.");
+        let mut n_blocks: Vec<(u32, u32)> = Vec::new();
+        let mut mask_blocks: Vec<(u32, u32)> = Vec::new();
+        let mut packed_dna = vec![0u8; packed_length(dna_size)];
+
+        for (i, c) in fasta_section.sequence.bytes().enumerate() {
+            let position = i as u32;
+            let code = match c.to_ascii_uppercase() {
+                b'T' | b'U' => Some(0b00),
+                b'C' => Some(0b01),
+                b'A' => Some(0b10),
+                b'G' => Some(0b11),
+                _ => None,
+            };
+
+            match code {
+                Some(code) => packed_dna[i / 4] |= code << (6 - 2 * (i % 4)),
+                None => extend_block(&mut n_blocks, position),
+            }
+            if c.is_ascii_lowercase() {
+                extend_block(&mut mask_blocks, position);
+            }
+        }
+
+        Ok(TwoBitSection {
+            name,
+            dna_size,
+            n_blocks,
+            mask_blocks,
+            packed_dna,
+        })
+    }
+
+    // The packed bytes are translated directly, without decoding the sequence.
+    // 2bit only stores DNA, so RNA sequences are stored with T in place of U, and
+    // every ambiguous base is stored as N.
+    pub fn from_basta(basta_section: BinaryFastaSection) -> Result<Self, BinaryFastaError> {
+        if basta_section.molecule == Molecule::Protein {
+            return Err(BinaryFastaError::NotNucleotide {
+                name: basta_section.descriptor,
+            });
+        }
+
+        let dna_size = basta_section.sequence_length.unsigned_abs();
+        let mut packed_dna: Vec<u8> = basta_section
+            .sequence
+            .iter()
+            .map(|&byte| BASTA_TO_TWO_BIT[byte as usize])
+            .collect();
+        clear_padding(&mut packed_dna, dna_size);
+
+        let mut n_blocks: Vec<(u32, u32)> = Vec::new();
+        for run in &basta_section.ambiguous_runs {
+            match n_blocks.last_mut() {
+                // Runs of different ambiguous bases may be next to each other.
+                Some((start, length)) if *start + *length == run.start => *length += run.length,
+                _ => n_blocks.push((run.start, run.length)),
+            }
+        }
+        // N bases are stored as T, like faToTwoBit does.
+        clear_blocks(&mut packed_dna, &n_blocks);
+
+        Ok(TwoBitSection {
+            name: basta_section.id().to_string(),
+            dna_size,
+            n_blocks,
+            mask_blocks: Vec::new(),
+            packed_dna,
+        })
+    }
+
+    pub fn to_fasta(&self) -> FastaSection {
+        let mut sequence: Vec<u8> = (0..self.dna_size as usize)
+            .map(|i| {
+                let code = (self.packed_dna[i / 4] >> (6 - 2 * (i % 4))) & 0b11;
+                TWO_BIT_BASES[code as usize]
+            })
+            .collect();
+
+        for &(start, length) in &self.n_blocks {
+            let start = start as usize;
+            sequence[start..start + length as usize].fill(b'N');
+        }
+        for &(start, length) in &self.mask_blocks {
+            let start = start as usize;
+            sequence[start..start + length as usize].make_ascii_lowercase();
+        }
+
+        FastaSection {
+            descriptor: self.name.clone(),
+            // Only ASCII characters were written into the sequence.
+            sequence: String::from_utf8(sequence).expect("Sequence is not ASCII."),
+        }
+    }
+
+    // Soft-masking is dropped (as when converting FASTA to BASTA), and N blocks become
+    // runs of N.
+    pub fn to_basta(&self) -> Result<BinaryFastaSection, BinaryFastaError> {
+        let mut sequence: Vec<u8> = self
+            .packed_dna
+            .iter()
+            .map(|&byte| TWO_BIT_TO_BASTA[byte as usize])
+            .collect();
+        clear_padding(&mut sequence, self.dna_size);
+        // Ambiguous bases are packed as A in BASTA.
+        clear_blocks(&mut sequence, &self.n_blocks);
+
+        Ok(BinaryFastaSection {
+            descriptor: self.name.clone(),
+            sequence,
+            sequence_length: i32::try_from(self.dna_size).expect("Sequence is too long."),
+            molecule: Molecule::Nucleotide,
+            metadata: Metadata::new(),
+            ambiguous_runs: self
+                .n_blocks
+                .iter()
+                .map(|&(start, length)| AmbiguousRun {
+                    start,
+                    length,
+                    base: b'N',
+                })
+                .collect(),
+        })
+    }
+
+    // Blocks read from a file must be in order, not overlap, and be inside the sequence,
+    // as `to_fasta` and `to_basta` rely on it.
+    pub fn check_blocks(&self) -> Result<(), BinaryFastaError> {
+        for blocks in [&self.n_blocks, &self.mask_blocks] {
+            let mut end = 0u64;
+            for &(start, length) in blocks {
+                if length == 0 || (start as u64) < end {
+                    return Err(BinaryFastaError::InvalidTwoBitBlocks {
+                        name: self.name.clone(),
+                    });
+                }
+                end = start as u64 + length as u64;
+            }
+            if end > self.dna_size as u64 {
+                return Err(BinaryFastaError::InvalidTwoBitBlocks {
+                    name: self.name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Size of the record in the file, after the index.
+    pub fn record_length(&self) -> u64 {
+        // dnaSize, nBlockCount, maskBlockCount and reserved fields,
+        // plus a start and a length per block.
+        let block_count = (self.n_blocks.len() + self.mask_blocks.len()) as u64;
+        16 + 8 * block_count + self.packed_dna.len() as u64
+    }
+}
+
+pub fn packed_length(dna_size: u32) -> usize {
+    (dna_size as usize).div_ceil(4)
+}
+
+fn extend_block(blocks: &mut Vec<(u32, u32)>, position: u32) {
+    match blocks.last_mut() {
+        Some((start, length)) if *start + *length == position => *length += 1,
+        _ => blocks.push((position, 1)),
+    }
+}
+
+// The name of a sequence in a 2bit file is its ID, the descriptor up to the first
+// whitespace.
+fn id(descriptor: &str) -> String {
+    descriptor
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .to_string()
+}
+
+// Sets the bases of the blocks to code 0, which is A in BASTA and T in 2bit.
+fn clear_blocks(packed: &mut [u8], blocks: &[(u32, u32)]) {
+    for &(start, length) in blocks {
+        for position in start as usize..(start + length) as usize {
+            packed[position / 4] &= !(0b11 << (6 - 2 * (position % 4)));
+        }
+    }
+}
+
+// Zeroes the bits after the last base, so they do not depend on the source encoding.
+fn clear_padding(packed: &mut [u8], length: u32) {
+    let bases_in_last_byte = length % 4;
+    if bases_in_last_byte != 0 {
+        if let Some(last) = packed.last_mut() {
+            *last &= 0xFF << (8 - 2 * bases_in_last_byte);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translation_tables() {
+        // ACGT in BASTA is 00 01 10 11, and 10 01 11 00 in 2bit.
+        assert_eq!(BASTA_TO_TWO_BIT[0b0001_1011], 0b1001_1100);
+        assert_eq!(TWO_BIT_TO_BASTA[0b1001_1100], 0b0001_1011);
+
+        for byte in 0..=255u8 {
+            assert_eq!(
+                TWO_BIT_TO_BASTA[BASTA_TO_TWO_BIT[byte as usize] as usize],
+                byte
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_fasta_with_n_and_mask_blocks() {
+        let fasta_section = FastaSection {
+            descriptor: String::from("chr1"),
+            sequence: String::from("acgTNNGa"),
+        };
+
+        let expected = TwoBitSection {
+            name: String::from("chr1"),
+            dna_size: 8,
+            n_blocks: vec![(4, 2)],
+            mask_blocks: vec![(0, 3), (7, 1)],
+            packed_dna: vec![0b1001_1100, 0b0000_1110],
+        };
+        assert_eq!(TwoBitSection::from_fasta(fasta_section).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_to_fasta_restores_n_and_mask_blocks() {
+        let fasta_section = FastaSection {
+            descriptor: String::from("chr1"),
+            sequence: String::from("acgTNNGa"),
+        };
+        let two_bit_section = TwoBitSection::from_fasta(fasta_section).unwrap();
+
+        assert_eq!(two_bit_section.to_fasta().sequence, "acgTNNGa");
+    }
+
+    #[test]
+    fn test_basta_round_trip() {
+        let basta_section = BinaryFastaSection {
+            descriptor: String::from("chr1"),
+            sequence: vec![0b0001_1011, 0b1110_0000],
+            sequence_length: 6,
+            molecule: Molecule::Nucleotide,
+            metadata: Metadata::new(),
+            ambiguous_runs: Vec::new(),
+        };
+
+        let two_bit_section = TwoBitSection::from_basta(basta_section).unwrap();
+        assert_eq!(two_bit_section.to_fasta().sequence, "ACGTTG");
+
+        let basta_section = two_bit_section.to_basta().unwrap();
+        assert_eq!(basta_section.sequence, vec![0b0001_1011, 0b1110_0000]);
+        assert_eq!(basta_section.sequence_length, 6);
+    }
+
+    #[test]
+    fn test_n_blocks_round_trip_through_basta() {
+        let fasta_section = FastaSection {
+            descriptor: String::from("chr1 chromosome 1"),
+            sequence: String::from("ACNNTRYG"),
+        };
+        let basta_section =
+            BinaryFastaSection::from_text(&fasta_section.descriptor, &fasta_section.sequence);
+        let two_bit_section = TwoBitSection::from_fasta(fasta_section).unwrap();
+
+        // Both give the same record, named after the sequence ID, with R and Y as N.
+        let from_basta = TwoBitSection::from_basta(basta_section).unwrap();
+        assert_eq!(from_basta, two_bit_section);
+        assert_eq!(from_basta.name, "chr1");
+        assert_eq!(from_basta.n_blocks, vec![(2, 2), (5, 2)]);
+
+        let basta_section = two_bit_section.to_basta().unwrap();
+        assert_eq!(FastaSection::from_basta(basta_section).sequence, "ACNNTNNG");
+    }
+
+    #[test]
+    fn test_from_fasta_rejects_protein() {
+        let fasta_section = FastaSection {
+            descriptor: String::from("sp|P69905|HBA_HUMAN"),
+            sequence: String::from("MVLSPADKTNVKAAW"),
+        };
+
+        assert!(matches!(
+            TwoBitSection::from_fasta(fasta_section),
+            Err(BinaryFastaError::NotNucleotide { .. })
+        ));
+    }
+
+    #[test]
+    fn test_check_blocks() {
+        let section = |n_blocks: Vec<(u32, u32)>, mask_blocks: Vec<(u32, u32)>| TwoBitSection {
+            name: String::from("chr1"),
+            dna_size: 8,
+            n_blocks,
+            mask_blocks,
+            packed_dna: vec![0; 2],
+        };
+
+        assert!(section(vec![(0, 2), (4, 4)], vec![(0, 8)])
+            .check_blocks()
+            .is_ok());
+        for (n_blocks, mask_blocks) in [
+            (vec![(4, 5)], vec![]),
+            (vec![(0, 2), (1, 2)], vec![]),
+            (vec![(4, 1), (0, 1)], vec![]),
+            (vec![(2, 0)], vec![]),
+            (vec![], vec![(u32::MAX, 2)]),
+        ] {
+            assert!(matches!(
+                section(n_blocks, mask_blocks).check_blocks(),
+                Err(BinaryFastaError::InvalidTwoBitBlocks { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn converts_multiple_inputs_into_output_dir() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let output_dir = tmp_dir.path().join("converted");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg("tests/data/small_dna.fasta")
+        .arg("tests/data/small_rna.fasta")
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Converted 2 of 2 files"));
+
+    assert!(output_dir.join("small_dna.basta").is_file());
+    assert!(output_dir.join("small_rna.basta").is_file());
+    Ok(())
+}
+
+#[test]
+fn converts_directory_recursively() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let input_dir = tmp_dir.path().join("genes");
+    fs::create_dir_all(input_dir.join("chr1"))?;
+    fs::copy("tests/data/small_dna.fasta", input_dir.join("gene1.fa"))?;
+    fs::copy(
+        "tests/data/small_rna.fasta",
+        input_dir.join("chr1/gene2.fa"),
+    )?;
+    fs::write(input_dir.join("notes.txt"), "not a sequence file")?;
+
+    let output_dir = tmp_dir.path().join("converted");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(&input_dir)
+        .arg("--recursive")
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Converted 2 of 2 files"));
+
+    assert!(output_dir.join("gene1.ba").is_file());
+    assert!(output_dir.join("chr1/gene2.ba").is_file());
+    Ok(())
+}
+
+#[test]
+fn same_file_names_cannot_share_an_output_dir() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    for dir in ["a", "b"] {
+        fs::create_dir_all(tmp_dir.path().join(dir))?;
+        fs::copy(
+            "tests/data/small_dna.fasta",
+            tmp_dir.path().join(dir).join("gene.fa"),
+        )?;
+    }
+    let output_dir = tmp_dir.path().join("converted");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(tmp_dir.path().join("a/gene.fa"))
+        .arg(tmp_dir.path().join("b/gene.fa"))
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("OutputCollision"));
+
+    // Nothing was converted.
+    assert!(!output_dir.exists());
+    Ok(())
+}
+
+#[test]
+fn continues_past_failures_and_reports_them() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg("tests/data/malformed.fasta")
+        .arg("tests/data/small_dna.fasta")
+        .arg("--output-dir")
+        .arg(tmp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("Converted 1 of 2 files"))
+        .stderr(predicates::str::contains(
+            "Failed to convert 'tests/data/malformed.fasta'",
+        ));
+
+    assert!(tmp_dir.path().join("small_dna.basta").is_file());
+    Ok(())
+}
+
+#[test]
+fn directory_without_recursive_should_fail() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(tmp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Use --recursive"));
+    Ok(())
+}
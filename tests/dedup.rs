@@ -0,0 +1,78 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+const AMPLICONS: &str = ">otu1 first\nACGTACGTA\n>otu2\nGGCC\n>otu3 copy of otu1\nacgtacgta\n>otu2 again\nTTTT\n>otu4\nGGCC\n";
+
+#[test]
+fn dedup_sequences_with_map() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("amplicons.fasta");
+    let basta_path = tmp_dir.path().join("amplicons.basta");
+    fs::write(&fasta_path, AMPLICONS)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg(&fasta_path)
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+
+    for input in [&fasta_path, &basta_path] {
+        let map_path = tmp_dir.path().join("map.tsv");
+        Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+            .arg("dedup")
+            .arg(input)
+            .arg("--map")
+            .arg(&map_path)
+            .arg("--force")
+            .assert()
+            .success()
+            .stdout(">otu1 first\nACGTACGTA\n>otu2\nGGCC\n>otu2 again\nTTTT\n");
+
+        assert_eq!(
+            fs::read_to_string(&map_path)?,
+            "DROPPED\tKEPT\tREASON\notu3 copy of otu1\totu1 first\tsequence\notu4\totu2\tsequence\n"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn dedup_by_descriptor() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("amplicons.fasta");
+    let map_path = tmp_dir.path().join("map.tsv");
+    fs::write(&fasta_path, AMPLICONS)?;
+    fs::write(&map_path, "existing")?;
+
+    // The map file is not overwritten without --force.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("dedup")
+        .arg(&fasta_path)
+        .arg("--by-descriptor")
+        .arg("-m")
+        .arg(&map_path)
+        .assert()
+        .failure()
+        .stdout("");
+
+    fs::remove_file(&map_path)?;
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("dedup")
+        .arg(&fasta_path)
+        .arg("--by-descriptor")
+        .arg("-m")
+        .arg(&map_path)
+        .assert()
+        .success()
+        .stdout(">otu1 first\nACGTACGTA\n>otu2\nGGCC\n");
+
+    assert_eq!(
+        fs::read_to_string(&map_path)?,
+        "DROPPED\tKEPT\tREASON\notu3 copy of otu1\totu1 first\tsequence\notu2 again\totu2\tdescriptor\notu4\totu2\tsequence\n"
+    );
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+const RELEASE_1: &str = ">chr1\nACGTACGTAC\n>chr2\nGGGG\n>chrM\nTTAA\n>chrUn\nCC\n";
+const RELEASE_2: &str = ">chrM\nTTAA\n>chr1\nACGAACGTAG\n>chr2 v2\nGGGGG\n>chrY\nAAAA\n";
+
+#[test]
+fn diff_identical_files() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("release.fasta");
+    let basta_path = tmp_dir.path().join("release.basta");
+    fs::write(&fasta_path, RELEASE_1)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg(&fasta_path)
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("diff")
+        .arg(&fasta_path)
+        .arg(&basta_path)
+        .arg("--quiet")
+        .assert()
+        .code(0)
+        .stdout("");
+    Ok(())
+}
+
+#[test]
+fn diff_reports_differences() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let first_path = tmp_dir.path().join("release_1.fasta");
+    let second_path = tmp_dir.path().join("release_2.fasta");
+    fs::write(&first_path, RELEASE_1)?;
+    fs::write(&second_path, RELEASE_2)?;
+
+    let expected = format!(
+        "# first: {}\n# second: {}\n\
+         mismatches\tchr1\t2\nmismatch\tchr1\t4\tT\tA\n\
+         descriptor\tchr2\tchr2\tchr2 v2\nlength\tchr2\t4\t5\n\
+         only_in_first\tchrUn\nonly_in_second\tchrY\n\
+         # identical: 1\n# different: 2\n# only in first: 1\n# only in second: 1\n",
+        first_path.display(),
+        second_path.display()
+    );
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("diff")
+        .arg(&first_path)
+        .arg(&second_path)
+        .arg("--mismatches")
+        .arg("1")
+        .assert()
+        .code(1)
+        .stdout(expected);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("diff")
+        .arg(&first_path)
+        .arg(tmp_dir.path().join("missing.fasta"))
+        .assert()
+        .code(2);
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn encode_and_decode_any_extension() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fna_path = tmp_dir.path().join("genes.fna");
+    fs::copy("tests/data/multiline_section.fasta", &fna_path)?;
+    let binary_path = tmp_dir.path().join("genes.bin");
+    let roundtrip_path = tmp_dir.path().join("genes.txt");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg(&fna_path)
+        .arg("--output")
+        .arg(&binary_path)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("decode")
+        .arg(&binary_path)
+        .arg("--output")
+        .arg(&roundtrip_path)
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&fna_path)?.trim_end(),
+        fs::read_to_string(&roundtrip_path)?.trim_end()
+    );
+    Ok(())
+}
+
+#[test]
+fn encode_infers_basta_output_path() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fna_path = tmp_dir.path().join("genes.fna");
+    fs::copy("tests/data/small_dna.fasta", &fna_path)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg(&fna_path)
+        .assert()
+        .success();
+
+    assert!(tmp_dir.path().join("genes.basta").is_file());
+    Ok(())
+}
+
+#[test]
+fn direction_is_sniffed_without_subcommand() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    // A FASTA file with an extension that does not say so.
+    let misnamed_path = tmp_dir.path().join("genes.txt");
+    fs::copy("tests/data/small_dna.fasta", &misnamed_path)?;
+    let binary_path = tmp_dir.path().join("genes.dat");
+    let fasta_path = tmp_dir.path().join("genes.fasta");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(&misnamed_path)
+        .arg("--output")
+        .arg(&binary_path)
+        .assert()
+        .success();
+
+    // The BASTA magic is recognized even though the extension is unknown.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(&binary_path)
+        .arg("--output")
+        .arg(&fasta_path)
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&misnamed_path)?.trim_end(),
+        fs::read_to_string(&fasta_path)?.trim_end()
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_rejects_fasta_input() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("decode")
+        .arg("tests/data/small_dna.fasta")
+        .arg("--output")
+        .arg(tmp_dir.path().join("out.fasta"))
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "UnexpectedFormat { path: \"tests/data/small_dna.fasta\", direction: Decode, found: Fasta }",
+        ));
+    Ok(())
+}
+
+#[test]
+fn detect_reports_content_format() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fastq_path = tmp_dir.path().join("reads.fa");
+    fs::write(&fastq_path, "@read1\nACGT\n+\nIIII\n")?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("detect")
+        .arg("tests/data/small_dna.fasta")
+        .arg(&fastq_path)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "tests/data/small_dna.fasta: FASTA",
+        ))
+        .stdout(predicates::str::contains("reads.fa: FASTQ"));
+    Ok(())
+}
+
+#[test]
+fn misnamed_gzip_is_reported() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let gzip_path = tmp_dir.path().join("genes.fa");
+    fs::write(&gzip_path, [0x1f, 0x8b, 0x08, 0x00])?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(&gzip_path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("UnsupportedFormat"))
+        .stderr(predicates::str::contains("found: Gzip"));
+    Ok(())
+}
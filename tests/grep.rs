@@ -0,0 +1,92 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+const CONTIGS: &str = ">contig1 test\nTTGAATTCAAGGACCTT\n>contig2\nAGGTCCA\n";
+
+#[test]
+fn grep_reports_matches_as_bed() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("contigs.fasta");
+    let basta_path = tmp_dir.path().join("contigs.basta");
+    fs::write(&fasta_path, CONTIGS)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg(&fasta_path)
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+
+    for input in [&fasta_path, &basta_path] {
+        Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+            .arg("grep")
+            .arg(input)
+            .arg("-p")
+            .arg("GAATTC")
+            .arg("-p")
+            .arg("GGWCC")
+            .assert()
+            .success()
+            .stdout(
+                "contig1\t2\t8\tGAATTC\t0\t+\ncontig1\t10\t15\tGGWCC\t0\t+\ncontig2\t1\t6\tGGWCC\t0\t+\n",
+            );
+    }
+    Ok(())
+}
+
+#[test]
+fn grep_on_both_strands() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("contigs.fasta");
+    fs::write(&fasta_path, CONTIGS)?;
+
+    // AGGTCC on contig2 is GGACCT on the reverse strand.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("grep")
+        .arg(&fasta_path)
+        .arg("-p")
+        .arg("GGACCT")
+        .arg("--both-strands")
+        .assert()
+        .success()
+        .stdout("contig1\t10\t16\tGGACCT\t0\t+\ncontig2\t0\t6\tGGACCT\t0\t-\n");
+    Ok(())
+}
+
+#[test]
+fn grep_rejects_invalid_patterns() -> Result<(), Box<dyn Error>> {
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("grep")
+        .arg("tests/data/small_dna.fasta")
+        .arg("-p")
+        .arg("GAZTTC")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("is not a nucleotide pattern"));
+    Ok(())
+}
+
+#[test]
+fn grep_with_mismatches() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("contigs.fasta");
+    fs::write(&fasta_path, CONTIGS)?;
+
+    // GAATTC matches exactly, GGACCT (on contig1) has its 4th base mismatched.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("grep")
+        .arg(&fasta_path)
+        .arg("-p")
+        .arg("GGATCT")
+        .arg("-p")
+        .arg("GAATTC")
+        .arg("--max-mismatches")
+        .arg("1")
+        .assert()
+        .success()
+        .stdout("contig1\t2\t8\tGAATTC\t0\t+\t.\ncontig1\t10\t16\tGGATCT\t1\t+\t4\n");
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+// Some records have N and other ambiguous bases, which BASTA keeps next to the packed bases.
+const RECORDS: &str = ">r1\nAAAA\n>r2\nCCNN\n>r3\nGGGG\n>r4\nTTTT\n>r5\nANNT\n>r6\nTGCR\n";
+
+// Writes the records as FASTA and as BASTA.
+fn inputs(dir: &Path) -> Result<[PathBuf; 2], Box<dyn Error>> {
+    let fasta_path = dir.join("records.fasta");
+    let basta_path = dir.join("records.basta");
+    fs::write(&fasta_path, RECORDS)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg(&fasta_path)
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+    Ok([fasta_path, basta_path])
+}
+
+fn run(args: &[&str], input: &Path) -> String {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg(args[0])
+        .arg(input)
+        .args(&args[1..])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn head_and_tail() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+
+    for input in inputs(tmp_dir.path())? {
+        assert_eq!(run(&["head", "-n", "2"], &input), ">r1\nAAAA\n>r2\nCCNN\n");
+        assert_eq!(run(&["tail", "-n", "2"], &input), ">r5\nANNT\n>r6\nTGCR\n");
+        assert_eq!(run(&["tail", "-n", "10"], &input), RECORDS);
+        assert_eq!(run(&["tail", "-n", "0"], &input), "");
+    }
+    Ok(())
+}
+
+#[test]
+fn sample_is_reproducible_with_a_seed() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let [fasta_path, basta_path] = inputs(tmp_dir.path())?;
+
+    let sample = run(&["sample", "-n", "3", "--seed", "7"], &fasta_path);
+    assert_eq!(sample.matches('>').count(), 3);
+    for record in sample.split('>').skip(1) {
+        assert!(RECORDS.contains(&format!(">{}", record)));
+    }
+    assert_eq!(
+        sample,
+        run(&["sample", "-n", "3", "--seed", "7"], &fasta_path)
+    );
+    // BASTA is sampled from the section headers, with the same result.
+    assert_eq!(
+        sample,
+        run(&["sample", "-n", "3", "--seed", "7"], &basta_path)
+    );
+
+    let fraction = run(&["sample", "--fraction", "0.5", "--seed", "7"], &fasta_path);
+    assert_eq!(
+        fraction,
+        run(&["sample", "--fraction", "0.5", "--seed", "7"], &basta_path)
+    );
+    assert_eq!(run(&["sample", "--fraction", "1"], &basta_path), RECORDS);
+    assert_eq!(run(&["sample", "--fraction", "0"], &basta_path), "");
+    Ok(())
+}
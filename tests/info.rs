@@ -0,0 +1,96 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn info_lists_sections_without_decoding() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let basta_path = tmp_dir.path().join("small_dna.basta");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg("tests/data/small_dna.fasta")
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+
+    // 6 header bytes, then 6 bytes of fields, the 13 byte descriptor and 5 bytes of sequence.
+    let expected = format!(
+        "# file: {}\n\
+         # format version: 2\n\
+         # flags: 0x00\n\
+         index\tdescriptor\tlength\tmolecule\toffset\tpacked_bytes\n\
+         1\tSmall example\t18\tDNA\t6\t5\n\
+         # sections: 1\n\
+         # total bases: 18\n\
+         # file size: 30 bytes\n\
+         # estimated FASTA size: 34 bytes\n\
+         # compression ratio: 1.13\n",
+        basta_path.display()
+    );
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("info")
+        .arg(&basta_path)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("list")
+        .arg(&basta_path)
+        .arg("--summary")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("# sections: 1\n"))
+        .stdout(predicates::str::contains("Small example").not());
+    Ok(())
+}
+
+#[test]
+fn info_estimates_the_size_of_the_decoded_file() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let basta_path = tmp_dir.path().join("multiline.basta");
+    let fasta_path = tmp_dir.path().join("multiline.fasta");
+
+    // Sequences of 204 and 104 bases, written on several lines.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg("tests/data/multiline_section.fasta")
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("decode")
+        .arg(&basta_path)
+        .arg("-o")
+        .arg(&fasta_path)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("info")
+        .arg(&basta_path)
+        .arg("--summary")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(format!(
+            "# estimated FASTA size: {} bytes\n",
+            fs::metadata(&fasta_path)?.len()
+        )));
+    Ok(())
+}
+
+#[test]
+fn info_rejects_other_formats() -> Result<(), Box<dyn Error>> {
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("info")
+        .arg("tests/data/small_dna.fasta")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("NotBasta"));
+    Ok(())
+}
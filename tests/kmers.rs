@@ -0,0 +1,113 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn kmers_count_table_and_histogram() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("reads.fasta");
+    fs::write(&fasta_path, ">read1\nAACGTT\n>read2\nGGGA\n")?;
+
+    // AAC and GTT, ACG and CGT are reverse complements of each other.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("kmers")
+        .arg(&fasta_path)
+        .arg("-k")
+        .arg("3")
+        .assert()
+        .success()
+        .stdout("AAC\t2\nACG\t2\nCCC\t1\nGGA\t1\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("kmers")
+        .arg(&fasta_path)
+        .arg("-k")
+        .arg("3")
+        .arg("--min-count")
+        .arg("2")
+        .assert()
+        .success()
+        .stdout("AAC\t2\nACG\t2\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("kmers")
+        .arg(&fasta_path)
+        .arg("-k")
+        .arg("3")
+        .arg("--histogram")
+        .assert()
+        .success()
+        .stdout("1\t2\n2\t2\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("kmers")
+        .arg(&fasta_path)
+        .arg("-k")
+        .arg("3")
+        .arg("--histogram")
+        .arg("--min-count")
+        .arg("2")
+        .assert()
+        .success()
+        .stdout("2\t2\n");
+    Ok(())
+}
+
+#[test]
+fn kmers_from_basta_match_fasta() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let basta_path = tmp_dir.path().join("small_dna.basta");
+    let fasta_counts = tmp_dir.path().join("fasta.tsv");
+    let basta_counts = tmp_dir.path().join("basta.tsv");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg("tests/data/small_dna.fasta")
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+
+    for (input, output) in [
+        ("tests/data/small_dna.fasta", &fasta_counts),
+        (basta_path.to_str().unwrap(), &basta_counts),
+    ] {
+        Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+            .arg("kmers")
+            .arg(input)
+            .arg("-k")
+            .arg("5")
+            .arg("-o")
+            .arg(output)
+            .assert()
+            .success();
+    }
+
+    let counts = fs::read_to_string(&fasta_counts)?;
+    assert!(!counts.is_empty());
+    assert_eq!(counts, fs::read_to_string(&basta_counts)?);
+    Ok(())
+}
+
+#[test]
+fn kmers_rejects_invalid_k_and_protein() -> Result<(), Box<dyn Error>> {
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("kmers")
+        .arg("tests/data/small_dna.fasta")
+        .arg("-k")
+        .arg("33")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("InvalidKmerSize { k: 33 }"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("kmers")
+        .arg("tests/data/small_protein.fasta")
+        .arg("-k")
+        .arg("3")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("NotNucleotide"));
+    Ok(())
+}
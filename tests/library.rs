@@ -0,0 +1,32 @@
+use binary_fasta::{
+    detect::{detect_bytes, detect_format, DetectedFormat},
+    kmer::{kmer_to_string, CanonicalKmers},
+    motif::{find_matches, Pattern},
+    records,
+};
+use std::error::Error;
+use std::path::Path;
+
+// The modules behind the subcommands can be used from other crates.
+#[test]
+fn read_count_and_search_from_a_library() -> Result<(), Box<dyn Error>> {
+    let path = Path::new("tests/data/small_dna.fasta");
+    assert_eq!(detect_format(path)?, DetectedFormat::Fasta);
+    assert_eq!(detect_bytes(b"BSTA\x02\x00"), DetectedFormat::Basta);
+
+    let sections = records::read(path)?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(sections.len(), 1);
+
+    // AAAACCCCGGGGTTTTGG
+    let kmers: Vec<String> = CanonicalKmers::new(&sections[0], 8)?
+        .map(|kmer| kmer_to_string(kmer, 8))
+        .collect();
+    assert_eq!(kmers.len(), 11);
+    assert_eq!(kmers[0], "AAAACCCC");
+
+    let pattern: Pattern = "CCGG".parse()?;
+    let matches = find_matches(&sections[0], &[pattern], false, 0)?;
+    assert_eq!(matches.len(), 1);
+    assert_eq!((matches[0].start, matches[0].end), (6, 10));
+    Ok(())
+}
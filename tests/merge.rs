@@ -0,0 +1,85 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn merge_fasta_and_basta() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("a.fasta");
+    let other_fasta_path = tmp_dir.path().join("b.fasta");
+    let basta_path = tmp_dir.path().join("b.basta");
+    let merged_path = tmp_dir.path().join("merged.basta");
+    fs::write(&fasta_path, ">chr1\nACGT\n>chr2\nGGCC\n")?;
+    fs::write(&other_fasta_path, ">chrM mitochondrion\nTTAACG\n")?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg(&other_fasta_path)
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("merge")
+        .arg(&fasta_path)
+        .arg(&basta_path)
+        .assert()
+        .success()
+        .stdout(">chr1\nACGT\n>chr2\nGGCC\n>chrM mitochondrion\nTTAACG\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("merge")
+        .arg(&basta_path)
+        .arg(&fasta_path)
+        .arg("-o")
+        .arg(&merged_path)
+        .assert()
+        .success();
+
+    // The BASTA sections are copied as they are, so the merged file starts with the
+    // whole of the BASTA input.
+    let basta = fs::read(&basta_path)?;
+    assert!(fs::read(&merged_path)?.starts_with(&basta));
+    Ok(())
+}
+
+#[test]
+fn merge_duplicate_ids() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let a_path = tmp_dir.path().join("a.fasta");
+    let b_path = tmp_dir.path().join("b.fasta");
+    fs::write(&a_path, ">chr1 from a\nACGT\n")?;
+    fs::write(&b_path, ">chr1 from b\nTTTT\n>chr2\nGG\n")?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("merge")
+        .arg(&a_path)
+        .arg(&b_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("chr1"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("merge")
+        .arg(&a_path)
+        .arg(&b_path)
+        .arg("--duplicates")
+        .arg("skip")
+        .assert()
+        .success()
+        .stdout(">chr1 from a\nACGT\n>chr2\nGG\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("merge")
+        .arg(&a_path)
+        .arg(&b_path)
+        .arg("--duplicates")
+        .arg("rename")
+        .assert()
+        .success()
+        .stdout(">chr1 from a\nACGT\n>chr1_2 from b\nTTTT\n>chr2\nGG\n");
+    Ok(())
+}
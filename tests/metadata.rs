@@ -0,0 +1,119 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+const GENOME: &str = ">chr1 dna:chromosome\nACGT\n>chrM mitochondrion\nGGCC\n";
+
+#[test]
+fn annotate_and_list_metadata() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("genome.fasta");
+    let annotated_path = tmp_dir.path().join("annotated.basta");
+    let sorted_path = tmp_dir.path().join("sorted.basta");
+    let table_path = tmp_dir.path().join("metadata.tsv");
+    fs::write(&fasta_path, GENOME)?;
+    fs::write(
+        &table_path,
+        "chrM\ttopology\tcircular\nchrM\taccession\tNC_012920.1\n",
+    )?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("annotate")
+        .arg(&fasta_path)
+        .arg("--set")
+        .arg("assembly=GRCh38.p14")
+        .arg("--set")
+        .arg("taxid=9606")
+        .arg("--table")
+        .arg(&table_path)
+        .arg("-o")
+        .arg(&annotated_path)
+        .assert()
+        .success();
+
+    // Metadata is kept by subcommands that pass sections on.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("sort")
+        .arg(&annotated_path)
+        .arg("--by")
+        .arg("length")
+        .arg("-r")
+        .arg("-o")
+        .arg(&sorted_path)
+        .assert()
+        .success();
+
+    let table = "ID\tKEY\tVALUE\n\
+                 chr1\tassembly\tGRCh38.p14\n\
+                 chr1\ttaxid\t9606\n\
+                 chrM\tassembly\tGRCh38.p14\n\
+                 chrM\ttaxid\t9606\n\
+                 chrM\ttopology\tcircular\n\
+                 chrM\taccession\tNC_012920.1\n";
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("metadata")
+        .arg(&sorted_path)
+        .assert()
+        .success()
+        .stdout(table);
+
+    // FASTA output has no room for it.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("head")
+        .arg(&annotated_path)
+        .assert()
+        .success()
+        .stdout(GENOME);
+
+    // A table written by metadata can be read back.
+    let copy_path = tmp_dir.path().join("copy.basta");
+    fs::write(&table_path, table)?;
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("annotate")
+        .arg(&fasta_path)
+        .arg("--table")
+        .arg(&table_path)
+        .arg("-o")
+        .arg(&copy_path)
+        .assert()
+        .success();
+    assert_eq!(fs::read(&copy_path)?, fs::read(&annotated_path)?);
+    Ok(())
+}
+
+#[test]
+fn annotate_rejects_invalid_metadata() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("genome.fasta");
+    let table_path = tmp_dir.path().join("metadata.tsv");
+    fs::write(&fasta_path, GENOME)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("annotate")
+        .arg(&fasta_path)
+        .arg("--set")
+        .arg("topology=round")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("topology linear or circular"));
+
+    fs::write(&table_path, "chrM topology circular\n")?;
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("annotate")
+        .arg(&fasta_path)
+        .arg("--table")
+        .arg(&table_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("InvalidMetadataTable"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("metadata")
+        .arg(&fasta_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("NotBasta"));
+    Ok(())
+}
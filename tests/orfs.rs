@@ -0,0 +1,86 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+// An ORF on the forward strand (ATG AAA GGG TTT CCC TAG), and one on the reverse
+// strand (the reverse complement of ATG TGG GGC TGA).
+const CONTIG: &str = ">contig1 test\nCCATGAAAGGGTTTCCCTAGCCTCAGCCCCACATCC\n";
+
+#[test]
+fn orfs_are_written_as_bed() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("contig.fna");
+    fs::write(&fasta_path, CONTIG)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("orfs")
+        .arg(&fasta_path)
+        .arg("--min-length")
+        .arg("12")
+        .assert()
+        .success()
+        .stdout("contig1\t2\t20\tcontig1_orf1\t0\t+\ncontig1\t22\t34\tcontig1_orf2\t0\t-\n");
+    Ok(())
+}
+
+#[test]
+fn orfs_as_gff_with_proteins() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("contig.fna");
+    fs::write(&fasta_path, CONTIG)?;
+    let gff_path = tmp_dir.path().join("orfs.gff");
+    let proteins_path = tmp_dir.path().join("orfs.faa");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("orfs")
+        .arg(&fasta_path)
+        .arg("--min-length")
+        .arg("15")
+        .arg("--format")
+        .arg("gff")
+        .arg("-o")
+        .arg(&gff_path)
+        .arg("--proteins")
+        .arg(&proteins_path)
+        .assert()
+        .success()
+        .stdout("");
+
+    assert_eq!(
+        fs::read_to_string(&gff_path)?,
+        "##gff-version 3\ncontig1\tbinary_fasta\tORF\t3\t20\t.\t+\t0\tID=contig1_orf1;frame=3\n"
+    );
+    assert_eq!(fs::read_to_string(&proteins_path)?, ">contig1_orf1\nMKGFP\n");
+    Ok(())
+}
+
+#[test]
+fn orfs_leave_no_output_when_proteins_cannot_be_written() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("contig.fna");
+    fs::write(&fasta_path, CONTIG)?;
+    let bed_path = tmp_dir.path().join("orfs.bed");
+
+    // Proteins cannot be written to FASTQ, and an existing file is not overwritten.
+    let proteins_path = tmp_dir.path().join("orfs.faa");
+    fs::write(&proteins_path, "existing")?;
+    for (proteins, error) in [
+        (tmp_dir.path().join("orfs.fastq"), "UnsupportedOutputExtension"),
+        (proteins_path.clone(), "OutputExists"),
+    ] {
+        Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+            .arg("orfs")
+            .arg(&fasta_path)
+            .arg("-o")
+            .arg(&bed_path)
+            .arg("--proteins")
+            .arg(&proteins)
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(error));
+        assert!(!bed_path.exists());
+    }
+    assert_eq!(fs::read_to_string(&proteins_path)?, "existing");
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn existing_output_is_not_overwritten() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let output_path = tmp_dir.path().join("reference.basta");
+    fs::write(&output_path, "precious")?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg("tests/data/small_dna.fasta")
+        .arg("--output")
+        .arg(&output_path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("OutputExists"));
+
+    assert_eq!(fs::read_to_string(&output_path)?, "precious");
+    // No temporary file is left behind.
+    assert_eq!(fs::read_dir(tmp_dir.path())?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn existing_output_is_overwritten_with_force() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let output_path = tmp_dir.path().join("reference.basta");
+    fs::write(&output_path, "stale")?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg("tests/data/small_dna.fasta")
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--force")
+        .assert()
+        .success();
+
+    assert_ne!(fs::read(&output_path)?, b"stale");
+    assert_eq!(fs::read_dir(tmp_dir.path())?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn failed_conversion_leaves_no_output() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let input_path = tmp_dir.path().join("truncated.fasta");
+    let output_path = tmp_dir.path().join("truncated.basta");
+    // The first section is long enough to be written to disk before the second
+    // one fails to convert.
+    fs::write(
+        &input_path,
+        format!(">good\n{}\n>bad\nACGT-ACGT\n", "ACGT".repeat(10_000)),
+    )?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("InvalidSequenceCharacter"));
+
+    // Only the input is left.
+    assert_eq!(fs::read_dir(tmp_dir.path())?.count(), 1);
+    Ok(())
+}
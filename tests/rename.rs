@@ -0,0 +1,139 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+const ENSEMBL: &str = ">1 dna:chromosome chromosome:GRCh38:1\nACGT\n>MT dna:chromosome\nGGCC\n";
+
+#[test]
+fn rename_with_map_keeps_sequences() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("ensembl.fasta");
+    let basta_path = tmp_dir.path().join("ensembl.basta");
+    let renamed_path = tmp_dir.path().join("ucsc.basta");
+    let map_path = tmp_dir.path().join("names.tsv");
+    fs::write(&fasta_path, ENSEMBL)?;
+    fs::write(&map_path, "# Ensembl\tUCSC\n1\tchr1\nMT\tchrM\n")?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg(&fasta_path)
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("rename")
+        .arg(&basta_path)
+        .arg("--map")
+        .arg(&map_path)
+        .arg("-o")
+        .arg(&renamed_path)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("head")
+        .arg(&renamed_path)
+        .assert()
+        .success()
+        .stdout(">chr1 dna:chromosome chromosome:GRCh38:1\nACGT\n>chrM dna:chromosome\nGGCC\n");
+
+    fs::write(&map_path, "1\tchr1\nMT\n")?;
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("rename")
+        .arg(&basta_path)
+        .arg("--map")
+        .arg(&map_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("InvalidRenameMap"));
+    Ok(())
+}
+
+#[test]
+fn rename_with_regex() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("ensembl.fasta");
+    fs::write(&fasta_path, ENSEMBL)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("rename")
+        .arg(&fasta_path)
+        .arg("--regex")
+        .arg(r"^(\S+)\s.*")
+        .arg("--replace")
+        .arg("chr$1")
+        .assert()
+        .success()
+        .stdout(">chr1\nACGT\n>chrMT\nGGCC\n");
+    Ok(())
+}
+
+#[test]
+fn rename_with_template() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("uniprot.fasta");
+    fs::write(
+        &fasta_path,
+        ">sp|P69905|HBA_HUMAN Hemoglobin subunit alpha OS=Homo sapiens OX=9606 GN=HBA1\nMVLSPADK\n",
+    )?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("rename")
+        .arg(&fasta_path)
+        .arg("--template")
+        .arg("{GN}_{OX} {accession}")
+        .assert()
+        .success()
+        .stdout(">HBA1_9606 P69905\nMVLSPADK\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("rename")
+        .arg(&fasta_path)
+        .arg("--template")
+        .arg("{gene}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("MissingDescriptorField"));
+    Ok(())
+}
+
+#[test]
+fn rename_to_too_long_descriptor() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("ensembl.fasta");
+    let basta_path = tmp_dir.path().join("renamed.basta");
+    fs::write(&fasta_path, ENSEMBL)?;
+    let prefix = "X".repeat(300);
+
+    // BASTA descriptors are at most 255 bytes long, FASTA ones are not limited.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("rename")
+        .arg(&fasta_path)
+        .arg("--regex")
+        .arg("^")
+        .arg("--replace")
+        .arg(&prefix)
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("DescriptorTooLong"));
+    assert!(!basta_path.exists());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("rename")
+        .arg(&fasta_path)
+        .arg("--regex")
+        .arg("^")
+        .arg("--replace")
+        .arg(&prefix)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(">{}1 ", prefix)));
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn revcomp_prints_fasta_to_stdout() -> Result<(), Box<dyn Error>> {
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("revcomp")
+        .arg("tests/data/small_dna.fasta")
+        .assert()
+        .success()
+        .stdout(">Small example\nCCAAAACCCCGGGGTTTT\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("revcomp")
+        .arg("tests/data/small_rna.fasta")
+        .assert()
+        .success()
+        .stdout(">Small example\nGGAAAACCCCGGGGUUUU\n");
+    Ok(())
+}
+
+#[test]
+fn revcomp_twice_through_basta_restores_the_input() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let minus_path = tmp_dir.path().join("minus.basta");
+    let plus_path = tmp_dir.path().join("plus.fasta");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("revcomp")
+        .arg("tests/data/multiline_section.fasta")
+        .arg("-o")
+        .arg(&minus_path)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("revcomp")
+        .arg(&minus_path)
+        .arg("-o")
+        .arg(&plus_path)
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string("tests/data/multiline_section.fasta")?.trim_end(),
+        fs::read_to_string(&plus_path)?.trim_end()
+    );
+    Ok(())
+}
+
+#[test]
+fn revcomp_regions() -> Result<(), Box<dyn Error>> {
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("revcomp")
+        .arg("tests/data/small_dna.fasta")
+        .arg("--region")
+        .arg("Small:13-18")
+        .assert()
+        .success()
+        .stdout(">Small:13-18\nCCAAAA\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("revcomp")
+        .arg("tests/data/small_dna.fasta")
+        .arg("--region")
+        .arg("chr1:1-10")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "SequenceNotFound { name: \"chr1\" }",
+        ));
+    Ok(())
+}
+
+#[test]
+fn revcomp_ambiguous_bases() -> Result<(), Box<dyn Error>> {
+    // R (A or G) is complemented to Y (C or T), N to N.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("revcomp")
+        .arg("tests/data/ambiguous_dna.fasta")
+        .arg("--region")
+        .arg("chr1:7-20")
+        .assert()
+        .success()
+        .stdout(">chr1:7-20\nRYACGTACGTNNNN\n");
+    Ok(())
+}
+
+#[test]
+fn revcomp_rejects_protein() -> Result<(), Box<dyn Error>> {
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("revcomp")
+        .arg("tests/data/small_protein.fasta")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("NotNucleotide"));
+    Ok(())
+}
@@ -61,6 +61,16 @@ fn roundtrip_multiline_section() -> Result<(), Box<dyn Error>> {
     roundtrip_fasta(Path::new("tests/data/multiline_section.fasta"))
 }
 
+#[test]
+fn roundtrip_small_protein() -> Result<(), Box<dyn Error>> {
+    roundtrip_fasta(Path::new("tests/data/small_protein.fasta"))
+}
+
+#[test]
+fn roundtrip_ambiguous_dna() -> Result<(), Box<dyn Error>> {
+    roundtrip_fasta(Path::new("tests/data/ambiguous_dna.fasta"))
+}
+
 #[test]
 fn malformed_fasta_should_fail() -> Result<(), Box<dyn Error>> {
     Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
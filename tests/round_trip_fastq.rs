@@ -0,0 +1,51 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn roundtrip_small_reads() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let bastq_path = tmp_dir.path().join("reads.bastq");
+    let roundtrip_path = tmp_dir.path().join("roundtrip.fastq");
+
+    // FASTQ to BASTQ
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg("tests/data/small_reads.fastq")
+        .arg("--output")
+        .arg(&bastq_path)
+        .assert()
+        .success();
+
+    // BASTQ (back to) FASTQ, with the direction detected from the content.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(&bastq_path)
+        .arg("--output")
+        .arg(&roundtrip_path)
+        .assert()
+        .success();
+
+    // The separator line is written without repeating the descriptor.
+    let expected =
+        fs::read_to_string("tests/data/small_reads.fastq")?.replace("+read_2 lane=1", "+");
+    assert_eq!(expected, fs::read_to_string(&roundtrip_path)?);
+    Ok(())
+}
+
+#[test]
+fn fastq_output_path_is_inferred() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fastq_path = tmp_dir.path().join("reads.fq");
+    fs::copy("tests/data/small_reads.fastq", &fastq_path)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(&fastq_path)
+        .assert()
+        .success();
+
+    assert!(tmp_dir.path().join("reads.bq").is_file());
+    Ok(())
+}
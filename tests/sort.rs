@@ -0,0 +1,68 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+const CONTIGS: &str = ">chr10\nACGTACGTAC\n>chr2\nTTTT\n>chrX\nACG\n>chr1\nACGA\n";
+
+#[test]
+fn sort_fasta_and_basta_alike() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("contigs.fasta");
+    let basta_path = tmp_dir.path().join("contigs.basta");
+    fs::write(&fasta_path, CONTIGS)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg(&fasta_path)
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+
+    for input in [&fasta_path, &basta_path] {
+        Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+            .arg("sort")
+            .arg(input)
+            .assert()
+            .success()
+            .stdout(">chr1\nACGA\n>chr2\nTTTT\n>chr10\nACGTACGTAC\n>chrX\nACG\n");
+
+        Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+            .arg("sort")
+            .arg(input)
+            .arg("--by")
+            .arg("length")
+            .arg("--reverse")
+            .assert()
+            .success()
+            .stdout(">chr10\nACGTACGTAC\n>chr2\nTTTT\n>chr1\nACGA\n>chrX\nACG\n");
+    }
+    Ok(())
+}
+
+#[test]
+fn sort_by_sequence_in_runs() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("contigs.fasta");
+    let runs_dir = tmp_dir.path().join("runs");
+    fs::write(&fasta_path, CONTIGS)?;
+    fs::create_dir(&runs_dir)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("sort")
+        .arg(&fasta_path)
+        .arg("--by")
+        .arg("sequence")
+        .arg("--max-memory")
+        .arg("1")
+        .arg("--temp-dir")
+        .arg(&runs_dir)
+        .assert()
+        .success()
+        .stdout(">chrX\nACG\n>chr1\nACGA\n>chr10\nACGTACGTAC\n>chr2\nTTTT\n");
+
+    // The temporary files are removed.
+    assert_eq!(fs::read_dir(&runs_dir)?.count(), 0);
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+const RECORDS: &str = ">chr1 first\nAAAAAAAA\n>chr2\nCCCC\n>chrUn|x\nGGGGGG\n";
+
+#[test]
+fn split_per_sequence() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("genome.fasta");
+    let out_dir = tmp_dir.path().join("out");
+    fs::write(&fasta_path, RECORDS)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("split")
+        .arg(&fasta_path)
+        .arg("--per-sequence")
+        .arg("-d")
+        .arg(&out_dir)
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(out_dir.join("chr1.fasta"))?.trim_end(),
+        ">chr1 first\nAAAAAAAA"
+    );
+    assert_eq!(
+        fs::read_to_string(out_dir.join("chr2.fasta"))?.trim_end(),
+        ">chr2\nCCCC"
+    );
+    assert_eq!(
+        fs::read_to_string(out_dir.join("chrUn_x.fasta"))?.trim_end(),
+        ">chrUn|x\nGGGGGG"
+    );
+    Ok(())
+}
+
+#[test]
+fn split_by_bases_into_basta() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("genome.fasta");
+    fs::write(&fasta_path, RECORDS)?;
+
+    let chunk_1 = tmp_dir.path().join("genome.001.basta");
+    let chunk_2 = tmp_dir.path().join("genome.002.basta");
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("split")
+        .arg(&fasta_path)
+        .arg("--bases")
+        .arg("12")
+        .arg("--format")
+        .arg("basta")
+        .arg("-d")
+        .arg(tmp_dir.path())
+        .assert()
+        .success()
+        .stdout(format!("{}\n{}\n", chunk_1.display(), chunk_2.display()));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("head")
+        .arg(&chunk_1)
+        .assert()
+        .success()
+        .stdout(">chr1 first\nAAAAAAAA\n>chr2\nCCCC\n");
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("head")
+        .arg(&chunk_2)
+        .assert()
+        .success()
+        .stdout(">chrUn|x\nGGGGGG\n");
+
+    // Existing chunks are only replaced with --force.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("split")
+        .arg(&fasta_path)
+        .arg("--bases")
+        .arg("12")
+        .arg("--format")
+        .arg("basta")
+        .arg("-d")
+        .arg(tmp_dir.path())
+        .assert()
+        .failure();
+    Ok(())
+}
+
+#[test]
+fn split_by_sequence_count_with_prefix() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("genome.fasta");
+    fs::write(&fasta_path, RECORDS)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("split")
+        .arg(&fasta_path)
+        .arg("--sequences")
+        .arg("2")
+        .arg("--prefix")
+        .arg("part")
+        .arg("-d")
+        .arg(tmp_dir.path())
+        .assert()
+        .success();
+
+    assert!(tmp_dir.path().join("part.001.fasta").exists());
+    assert!(tmp_dir.path().join("part.002.fasta").exists());
+    assert!(!tmp_dir.path().join("part.003.fasta").exists());
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+const GENES: &str =
+    ">geneA first\nACGT\n>geneB second\nGGCC\n>geneC third\nTTAA\n>mito_geneD\nCCCC\n";
+
+#[test]
+fn subset_by_names_and_id_list() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("genes.fasta");
+    let basta_path = tmp_dir.path().join("genes.basta");
+    let ids_path = tmp_dir.path().join("ids.txt");
+    fs::write(&fasta_path, GENES)?;
+    fs::write(&ids_path, "geneC\nmissing\n")?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("encode")
+        .arg(&fasta_path)
+        .arg("-o")
+        .arg(&basta_path)
+        .assert()
+        .success();
+
+    // Sections come out in file order, whatever the order of the names.
+    for input in [&fasta_path, &basta_path] {
+        Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+            .arg("subset")
+            .arg(input)
+            .arg("--ids")
+            .arg(&ids_path)
+            .arg("-n")
+            .arg("geneA")
+            .assert()
+            .success()
+            .stdout(">geneA first\nACGT\n>geneC third\nTTAA\n");
+    }
+    Ok(())
+}
+
+#[test]
+fn subset_by_regex_to_basta() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("genes.fasta");
+    let subset_path = tmp_dir.path().join("subset.basta");
+    fs::write(&fasta_path, GENES)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("subset")
+        .arg(&fasta_path)
+        .arg("--regex")
+        .arg("^mito_|second")
+        .arg("-o")
+        .arg(&subset_path)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("decode")
+        .arg(&subset_path)
+        .arg("-o")
+        .arg(tmp_dir.path().join("subset.fasta"))
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(tmp_dir.path().join("subset.fasta"))?.trim_end(),
+        ">geneB second\nGGCC\n>mito_geneD\nCCCC"
+    );
+    Ok(())
+}
+
+#[test]
+fn subset_by_descriptor_field() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("uniprot.fasta");
+    fs::write(
+        &fasta_path,
+        ">sp|P69905|HBA_HUMAN Hemoglobin subunit alpha OS=Homo sapiens OX=9606\nMVLSPADK\n\
+         >sp|P01942|HBA_MOUSE Hemoglobin subunit alpha OS=Mus musculus OX=10090\nMVLSGEDK\n\
+         >sp|P01966|HBA_BOVIN Hemoglobin subunit alpha OS=Bos taurus OX=9913\nMVLSAADK\n",
+    )?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("subset")
+        .arg(&fasta_path)
+        .arg("--field")
+        .arg("OX=9606")
+        .arg("--field")
+        .arg("accession=P01966")
+        .assert()
+        .success()
+        .stdout(
+            ">sp|P69905|HBA_HUMAN Hemoglobin subunit alpha OS=Homo sapiens OX=9606\nMVLSPADK\n\
+             >sp|P01966|HBA_BOVIN Hemoglobin subunit alpha OS=Bos taurus OX=9913\nMVLSAADK\n",
+        );
+    Ok(())
+}
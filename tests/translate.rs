@@ -0,0 +1,109 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn translate_prints_protein_fasta() -> Result<(), Box<dyn Error>> {
+    // AAA ACC CCG GGG TTT TGG
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("translate")
+        .arg("tests/data/small_dna.fasta")
+        .assert()
+        .success()
+        .stdout(">Small example\nKTPGFW\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("translate")
+        .arg("tests/data/small_rna.fasta")
+        .arg("--frame")
+        .arg("-1")
+        .assert()
+        .success()
+        .stdout(">Small example\nGKPRGF\n");
+    Ok(())
+}
+
+#[test]
+fn translate_ambiguous_codons_as_x() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let fasta_path = tmp_dir.path().join("gene.fasta");
+    fs::write(&fasta_path, ">gene\nATGNNNTGGTAATGARCA\n")?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("translate")
+        .arg(&fasta_path)
+        .assert()
+        .success()
+        .stdout(">gene\nMXW**X\n");
+
+    // TAA codes for tyrosine in the Mesodinium nuclear code.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("translate")
+        .arg(&fasta_path)
+        .arg("--table")
+        .arg("29")
+        .assert()
+        .success()
+        .stdout(">gene\nMXWY*X\n");
+    Ok(())
+}
+
+#[test]
+fn translate_six_frames_to_basta() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let protein_path = tmp_dir.path().join("proteins.basta");
+    let fasta_path = tmp_dir.path().join("proteins.faa");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("translate")
+        .arg("tests/data/small_dna.fasta")
+        .arg("--six-frames")
+        .arg("-o")
+        .arg(&protein_path)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("decode")
+        .arg(&protein_path)
+        .arg("-o")
+        .arg(&fasta_path)
+        .assert()
+        .success();
+
+    let proteins = fs::read_to_string(&fasta_path)?;
+    let descriptors: Vec<&str> = proteins.lines().filter(|l| l.starts_with('>')).collect();
+    assert_eq!(
+        descriptors,
+        vec![
+            ">Small_frame=1 example",
+            ">Small_frame=2 example",
+            ">Small_frame=3 example",
+            ">Small_frame=-1 example",
+            ">Small_frame=-2 example",
+            ">Small_frame=-3 example",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn translate_rejects_unknown_table_and_protein() -> Result<(), Box<dyn Error>> {
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("translate")
+        .arg("tests/data/small_dna.fasta")
+        .arg("--table")
+        .arg("7")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("UnknownGeneticCode { id: 7 }"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("translate")
+        .arg("tests/data/small_protein.fasta")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("NotNucleotide"));
+    Ok(())
+}
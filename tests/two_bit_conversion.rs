@@ -0,0 +1,91 @@
+use assert_cmd::Command;
+use std::error::Error;
+use std::fs;
+use tempfile::tempdir;
+
+fn convert(input: &std::path::Path, output: &std::path::Path) {
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .assert()
+        .success();
+}
+
+#[test]
+fn fasta_to_two_bit_to_basta_to_fasta() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let two_bit_path = tmp_dir.path().join("genome.2bit");
+    let basta_path = tmp_dir.path().join("genome.basta");
+    let fasta_path = tmp_dir.path().join("genome.fasta");
+
+    convert(
+        std::path::Path::new("tests/data/ambiguous_dna.fasta"),
+        &two_bit_path,
+    );
+    assert_eq!(fs::read(&two_bit_path)?[..4], [0x43, 0x27, 0x41, 0x1a]);
+
+    convert(&two_bit_path, &basta_path);
+    convert(&basta_path, &fasta_path);
+
+    // 2bit only keeps the sequence IDs, and stores every ambiguous base as N.
+    let expected = fs::read_to_string("tests/data/ambiguous_dna.fasta")?
+        .replace(">chr1 with gaps", ">chr1")
+        .replace(['R', 'Y', 'K', 'M', 'S', 'W', 'B', 'D', 'H', 'V'], "N");
+    assert_eq!(expected, fs::read_to_string(&fasta_path)?);
+    Ok(())
+}
+
+#[test]
+fn two_bit_rejects_duplicate_ids() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let two_bit_path = tmp_dir.path().join("genome.2bit");
+
+    // "Multiline section" and "Multiline section 2" have the same ID.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg("tests/data/multiline_section.fasta")
+        .arg("--output")
+        .arg(&two_bit_path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "DuplicateSequence { name: \"Multiline\" }",
+        ));
+    assert!(!two_bit_path.exists());
+    Ok(())
+}
+
+#[test]
+fn two_bit_keeps_n_and_soft_masked_bases() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempdir()?;
+    let input_path = tmp_dir.path().join("masked.fa");
+    fs::write(&input_path, ">chr1\nNNNNacgtacgtACGTNNAC\n")?;
+    let two_bit_path = tmp_dir.path().join("masked.2bit");
+
+    convert(&input_path, &two_bit_path);
+
+    // The default output of a 2bit file is FASTA.
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("--input")
+        .arg(&two_bit_path)
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(tmp_dir.path().join("masked.fasta"))?,
+        ">chr1\nNNNNacgtacgtACGTNNAC\n"
+    );
+
+    // BASTA keeps the N bases, but not the soft-masking.
+    let basta_path = tmp_dir.path().join("masked.basta");
+    convert(&two_bit_path, &basta_path);
+    Command::new(assert_cmd::cargo::cargo_bin!("binary_fasta"))
+        .arg("head")
+        .arg(&basta_path)
+        .assert()
+        .success()
+        .stdout(">chr1\nNNNNACGTACGTACGTNNAC\n");
+    Ok(())
+}